@@ -0,0 +1,273 @@
+use crate::decompositions::{Derivation, Equation, Formula};
+use crate::errors::Error;
+use crate::matrices::Matrix;
+use crate::traits::{LaTeXable, MatrixNumber};
+use anyhow::bail;
+
+/// Compact plain text representation of a matrix in a single line, e.g.
+/// `[1, 2; 3, 4]`.
+fn inline_plain<T: MatrixNumber>(m: &Matrix<T>) -> String {
+    let rows: Vec<String> = m
+        .get_data()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|x| x.to_plain())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect();
+    format!("[{}]", rows.join("; "))
+}
+
+/// Equation `symbol = value` of a scalar.
+fn scalar_equation<T: MatrixNumber>(symbol: String, value: &T) -> Equation<T> {
+    Equation {
+        symbol,
+        formula: Some(Formula {
+            latex: value.to_latex(),
+            plain: value.to_plain(),
+        }),
+        value: None,
+    }
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the matrix without the row `i` and the column `j`, both
+    /// indexed from 0.
+    pub fn submatrix(&self, i: usize, j: usize) -> anyhow::Result<Self> {
+        let (rows, cols) = self.get_shape();
+        if i >= rows {
+            bail!(Error::IndexOutOfRange {
+                index: i + 1,
+                size: rows
+            });
+        }
+        if j >= cols {
+            bail!(Error::IndexOutOfRange {
+                index: j + 1,
+                size: cols
+            });
+        }
+        Ok(Matrix::filled((rows - 1, cols - 1), |r, c| {
+            self.get_data()[r + usize::from(r >= i)][c + usize::from(c >= j)].clone()
+        }))
+    }
+
+    /// Returns the minor `M_{ij}` - the determinant of the matrix without the
+    /// row `i` and the column `j`, both indexed from 0.
+    pub fn minor(&self, i: usize, j: usize) -> anyhow::Result<T> {
+        let (rows, cols) = self.get_shape();
+        if rows != cols {
+            bail!(Error::NotSquare);
+        }
+        self.submatrix(i, j)?.determinant()
+    }
+
+    /// Returns the cofactor `C_{ij} = (-1)^{i+j} M_{ij}`.
+    pub fn cofactor(&self, i: usize, j: usize) -> anyhow::Result<T> {
+        let minor = self.minor(i, j)?;
+        match (i + j) % 2 {
+            0 => Ok(minor),
+            _ => Ok(T::zero()
+                .checked_sub(&minor)
+                .ok_or_else(T::checked_ops_error)?),
+        }
+    }
+
+    /// Returns the matrix of all cofactors.
+    pub fn cofactor_matrix(&self) -> anyhow::Result<Self> {
+        let (rows, cols) = self.get_shape();
+        if rows != cols {
+            bail!(Error::NotSquare);
+        }
+        let cofactors = (0..rows)
+            .map(|i| (0..cols).map(|j| self.cofactor(i, j)).collect())
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Matrix::new_unsafe(cofactors))
+    }
+
+    /// Returns the adjugate - the transposed cofactor matrix, which satisfies
+    /// `A \operatorname{adj}(A) = \det(A) I`.
+    pub fn adjugate(&self) -> anyhow::Result<Self> {
+        Ok(self.cofactor_matrix()?.transpose())
+    }
+
+    /// Returns the derivation of the adjugate listing every cofactor with the
+    /// determinant it is computed from, e.g.
+    /// `C_{1,2} = (-1)^{1+2}\det [3] = -3`. The results are the cofactor
+    /// matrix `C` and the adjugate `C^{T}`.
+    pub fn adjugate_derivation(&self) -> anyhow::Result<Derivation<T>> {
+        let cofactors = self.cofactor_matrix()?;
+        let n = cofactors.get_shape().0;
+        let mut equations = vec![];
+        for i in 0..n {
+            for j in 0..n {
+                let submatrix = self.submatrix(i, j)?;
+                let cofactor = &cofactors.get_data()[i][j];
+                let (row, col) = (i + 1, j + 1);
+                equations.push(Equation {
+                    symbol: format!("C_{{{row},{col}}}"),
+                    formula: Some(Formula {
+                        latex: format!(
+                            r"(-1)^{{{row}+{col}}}\det {} = {}",
+                            submatrix.to_latex(),
+                            cofactor.to_latex()
+                        ),
+                        plain: format!(
+                            "(-1)^({row}+{col}) det {} = {}",
+                            inline_plain(&submatrix),
+                            cofactor.to_plain()
+                        ),
+                    }),
+                    value: None,
+                });
+            }
+        }
+        Ok(Derivation {
+            initial: self.clone(),
+            equations,
+            results: vec![
+                ("C".to_string(), cofactors.clone()),
+                ("C^{T}".to_string(), cofactors.transpose()),
+            ],
+        })
+    }
+
+    /// Solves the system `Ax = b` with a square invertible `A` by Cramer's
+    /// rule `x_i = \det(A_i) / \det(A)`, where `A_i` is `A` with the column
+    /// `i` replaced by `b`. The derivation lists every determinant used and
+    /// results in `x`.
+    pub fn cramer(&self, b: &Self) -> anyhow::Result<Derivation<T>> {
+        let (rows, cols) = self.get_shape();
+        if rows != cols {
+            bail!(Error::NotSquare);
+        }
+        if b.get_shape() != (rows, 1) {
+            bail!(Error::ExpectedColumnVector);
+        }
+        let determinant = self.determinant()?;
+        if determinant.is_zero() {
+            bail!(Error::NotInvertible);
+        }
+
+        let mut equations = vec![
+            Equation {
+                symbol: "b".to_string(),
+                formula: None,
+                value: Some(b.clone()),
+            },
+            scalar_equation(r"\det(A)".to_string(), &determinant),
+        ];
+        let mut solution = vec![];
+        for i in 0..cols {
+            let replaced = Matrix::filled((rows, cols), |r, c| match c == i {
+                true => b.get_data()[r][0].clone(),
+                false => self.get_data()[r][c].clone(),
+            });
+            let replaced_determinant = replaced.determinant()?;
+            let x = replaced_determinant
+                .checked_div(&determinant)
+                .ok_or_else(T::checked_ops_error)?;
+            let k = i + 1;
+            equations.extend([
+                Equation {
+                    symbol: format!("A_{{{k}}}"),
+                    formula: None,
+                    value: Some(replaced),
+                },
+                scalar_equation(format!(r"\det(A_{{{k}}})"), &replaced_determinant),
+                Equation {
+                    symbol: format!("x_{{{k}}}"),
+                    formula: Some(Formula {
+                        latex: format!(r"\frac{{\det(A_{{{k}}})}}{{\det(A)}} = {}", x.to_latex()),
+                        plain: format!("det(A{k}) / det(A) = {}", x.to_plain()),
+                    }),
+                    value: None,
+                },
+            ]);
+            solution.push(vec![x]);
+        }
+
+        Ok(Derivation {
+            initial: self.clone(),
+            equations,
+            results: vec![("x".to_string(), Matrix::new_unsafe(solution))],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{im, ri, rm, rv};
+    use num_rational::Rational64;
+
+    #[test]
+    fn test_minors_and_cofactors() {
+        let m = im![1, 2, 3; 0, 4, 5; 1, 0, 6];
+        assert_eq!(m.submatrix(0, 1).unwrap(), im![0, 5; 1, 6]);
+        assert_eq!(m.minor(0, 1).unwrap(), -5);
+        assert_eq!(m.cofactor(0, 1).unwrap(), 5);
+        assert_eq!(
+            m.cofactor_matrix().unwrap(),
+            im![24, 5, -4; -12, 3, 2; -2, -5, 4]
+        );
+        assert_eq!(m.adjugate().unwrap(), im![24, -12, -2; 5, 3, -5; -4, 2, 4]);
+        // A adj(A) = det(A) I
+        assert_eq!(
+            m.checked_mul(&m.adjugate().unwrap()).unwrap(),
+            Matrix::identity(3).checked_mul_scl(&22).unwrap()
+        );
+
+        assert_eq!(im![7].adjugate().unwrap(), im![1]);
+        assert!(im![1, 2].minor(0, 0).is_err());
+        assert!(matches!(
+            m.minor(3, 0).unwrap_err().downcast::<Error>().unwrap(),
+            Error::IndexOutOfRange { index: 4, size: 3 }
+        ));
+    }
+
+    #[test]
+    fn test_adjugate_derivation() {
+        let derivation = rm![1, 2; 3, 4].adjugate_derivation().unwrap();
+        let steps = derivation.latex_steps();
+        assert_eq!(steps.len(), 7);
+        assert!(steps[2].starts_with(r"C_{1,2} = (-1)^{1+2}\det "));
+        assert!(steps[2].ends_with(" = -3"));
+        assert_eq!(
+            derivation.plain_steps()[2],
+            "C1,2 = (-1)^(1+2) det [3] = -3"
+        );
+        assert_eq!(derivation.results[1].1, rm![4, -2; -3, 1]);
+    }
+
+    #[test]
+    fn test_cramer() {
+        let a = rm![2, 1; 1, 3];
+        let derivation = a.cramer(&rm![3; 5]).unwrap();
+        assert_eq!(
+            derivation.results,
+            vec![(
+                "x".to_string(),
+                Matrix::new(vec![
+                    vec![Rational64::new(4, 5)],
+                    vec![Rational64::new(7, 5)]
+                ])
+                .unwrap()
+            )]
+        );
+        let plain = derivation.plain_steps();
+        assert_eq!(plain[2], "det(A) = 5");
+        assert_eq!(plain[4], "det(A1) = 4");
+        assert_eq!(plain[5], "x1 = det(A1) / det(A) = 4/5");
+        assert_eq!(
+            derivation.latex_steps()[5],
+            r"x_{1} = \frac{\det(A_{1})}{\det(A)} = \frac{4}{5}"
+        );
+
+        assert!(rm![1, 2; 2, 4].cramer(&rm![1; 2]).is_err());
+        assert!(a.cramer(&rm![1, 2]).is_err());
+        assert!(rm![1, 2].cramer(&rm![1]).is_err());
+    }
+}
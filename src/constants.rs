@@ -4,6 +4,8 @@ pub const DEFAULT_HEIGHT: f32 = 600.0;
 pub const DEFAULT_LEFT_PANEL_WIDTH: f32 = DEFAULT_WIDTH * 0.20;
 
 pub const TEXT_COLOR: egui::Color32 = egui::Color32::LIGHT_GRAY;
+pub const PIVOT_COLOR: egui::Color32 = egui::Color32::GOLD;
+pub const MODIFIED_ROW_COLOR: egui::Color32 = egui::Color32::LIGHT_BLUE;
 pub const FONT_ID: egui::FontId = egui::FontId::proportional(18.);
 
 pub const MATRIX_HPADDING: f32 = 15.;
@@ -13,9 +15,35 @@ pub const FRACTION_HMARGIN: f32 = 2.;
 pub const FRACTION_VMARGIN: f32 = 1.;
 pub const FRACTION_LINE_WIDTH: f32 = 1.;
 pub const VALUE_PADDING: f32 = 15.;
+pub const REPLAY_STEP_DURATION: std::time::Duration = std::time::Duration::from_millis(1500);
 
 pub const FLOAT_STRING_PRECISION: usize = 3;
+/// Floats with at least this many integer digits are written in scientific notation.
+pub const SCIENTIFIC_NOTATION_DIGITS: i32 = 7;
+/// Floats closer than this are considered equal.
+pub const FLOAT_ABSOLUTE_EPSILON: f64 = 1e-10;
+/// Floats whose difference relative to the larger of them is smaller than this are considered equal.
+pub const FLOAT_RELATIVE_EPSILON: f64 = 1e-10;
+/// Largest denominator of rationals approximating floats.
+pub const MAX_APPROXIMATION_DENOMINATOR: i64 = 1_000_000;
+/// Largest number of partial quotients of a continued fraction expansion.
+pub const MAX_CONTINUED_FRACTION_TERMS: usize = 20;
+/// Rationals whose decimal expansion does not repeat within this many digits are displayed as fractions.
+pub const MAX_REPEATING_DIGITS: usize = 32;
+/// Largest number whose square root can be simplified, e.g. `sqrt(12)` to `2 sqrt(3)`.
+pub const MAX_RADICAND: u64 = 1_000_000_000_000;
+/// Largest number of sweeps of the Jacobi rotations computing singular values and eigenvalues.
+pub const MAX_JACOBI_SWEEPS: usize = 64;
+/// Largest order of matrices whose definiteness can be decided by checking all principal minors.
+pub const MAX_PRINCIPAL_MINORS_ORDER: usize = 12;
+/// Largest number of iterations of the Denman-Beavers method computing square roots of matrices.
+pub const MAX_DENMAN_BEAVERS_ITERATIONS: usize = 100;
+/// Largest absolute value of the entries of the triangular factors of random invertible matrices.
+pub const RANDOM_INVERTIBLE_MAX_ENTRY: i64 = 2;
+/// Largest absolute value of the random determinants of the generated exercises.
+pub const EXERCISE_MAX_DETERMINANT: i64 = 5;
 
 pub const ICON_PATH: &str = "assets/icon.png";
+pub const LOCALES_PATH: &str = "assets/locales";
 #[cfg(feature = "fft")]
 pub const DFT_PATH: &str = "assets/dft_result.json";
@@ -0,0 +1,536 @@
+use crate::errors::Error;
+use crate::float::Float64;
+use crate::matrices::Matrix;
+use crate::traits::{CheckedSqrt, ExportFormat, Exportable, LaTeXable, MatrixNumber};
+use anyhow::bail;
+use num_traits::{One, Signed, Zero};
+
+/// Derivation of an orthogonalization or a decomposition - intermediate
+/// results written as equations, e.g. `v_{2} = a_{2} - \frac{1}{2}v_{1}`,
+/// followed by the named resulting matrices.
+#[derive(Debug, Clone)]
+pub struct Derivation<T: MatrixNumber> {
+    /// Matrix the derivation starts from.
+    pub initial: Matrix<T>,
+    pub equations: Vec<Equation<T>>,
+    /// Resulting matrices with their symbols, e.g. `Q` and `R`.
+    pub results: Vec<(String, Matrix<T>)>,
+}
+
+/// Single equation of a derivation - the symbol of the computed value, an
+/// optional formula it is computed with and the value itself, if it is a
+/// matrix, e.g. `p(\lambda) = \det(\lambda I - A) = \lambda^{2} - 1` has
+/// no value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation<T: MatrixNumber> {
+    pub symbol: String,
+    pub formula: Option<Formula>,
+    pub value: Option<Matrix<T>>,
+}
+
+/// Formula in LaTeX and in plain text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formula {
+    pub latex: String,
+    pub plain: String,
+}
+
+impl<T: MatrixNumber> Equation<T> {
+    pub fn to_latex(&self) -> String {
+        std::iter::once(self.symbol.clone())
+            .chain(self.formula.as_ref().map(|formula| formula.latex.clone()))
+            .chain(self.value.as_ref().map(|value| value.to_latex()))
+            .collect::<Vec<_>>()
+            .join(" = ")
+    }
+
+    pub fn to_plain(&self) -> String {
+        let symbol = self.symbol.replace(['{', '}', '_', '\\'], "");
+        let equation = match &self.formula {
+            Some(formula) => format!("{symbol} = {}", formula.plain),
+            None => format!("{symbol} ="),
+        };
+        match &self.value {
+            Some(value) => format!("{equation}\n{}", value.export(ExportFormat::Ascii)),
+            None => equation,
+        }
+    }
+}
+
+impl<T: MatrixNumber> Derivation<T> {
+    /// The initial matrix, all equations and the results in LaTeX.
+    pub fn latex_steps(&self) -> Vec<String> {
+        self.all_equations()
+            .iter()
+            .map(Equation::to_latex)
+            .collect()
+    }
+
+    /// The initial matrix, all equations and the results in plain text.
+    pub fn plain_steps(&self) -> Vec<String> {
+        self.all_equations()
+            .iter()
+            .map(Equation::to_plain)
+            .collect()
+    }
+
+    fn all_equations(&self) -> Vec<Equation<T>> {
+        let value = |symbol: &str, value: &Matrix<T>| Equation {
+            symbol: symbol.to_string(),
+            formula: None,
+            value: Some(value.clone()),
+        };
+        std::iter::once(value("A", &self.initial))
+            .chain(self.equations.iter().cloned())
+            .chain(
+                self.results
+                    .iter()
+                    .map(|(symbol, result)| value(symbol, result)),
+            )
+            .collect()
+    }
+
+    /// Exports the derivation in the given format. The equations are
+    /// available in LaTeX, Markdown (as LaTeX math blocks) and ASCII, other
+    /// formats only contain the results.
+    pub fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::LaTeX => self.latex_steps().join("\n"),
+            ExportFormat::Markdown => self
+                .latex_steps()
+                .iter()
+                .map(|step| format!("$$\n{step}\n$$"))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            ExportFormat::Ascii => self.plain_steps().join("\n\n"),
+            _ => self
+                .results
+                .iter()
+                .map(|(_, result)| result.export(format))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        }
+    }
+
+    /// Converts all matrices of the derivation with `convert`, e.g. from
+    /// approximate numbers back to the field of the application.
+    pub fn convert<U: MatrixNumber>(
+        &self,
+        convert: impl Fn(&T) -> Option<U>,
+    ) -> Option<Derivation<U>> {
+        Some(Derivation {
+            initial: self.initial.convert(&convert)?,
+            equations: self
+                .equations
+                .iter()
+                .map(|equation| {
+                    Some(Equation {
+                        symbol: equation.symbol.clone(),
+                        formula: equation.formula.clone(),
+                        value: match &equation.value {
+                            Some(value) => Some(value.convert(&convert)?),
+                            None => None,
+                        },
+                    })
+                })
+                .collect::<Option<_>>()?,
+            results: self
+                .results
+                .iter()
+                .map(|(symbol, result)| Some((symbol.clone(), result.convert(&convert)?)))
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+pub(crate) fn dot_product<T: MatrixNumber>(u: &[T], v: &[T]) -> Option<T> {
+    u.iter()
+        .zip(v)
+        .try_fold(T::zero(), |sum, (a, b)| sum.checked_add(&a.checked_mul(b)?))
+}
+
+fn column_matrix<T: MatrixNumber>(column: &[T]) -> Matrix<T> {
+    Matrix::new_unsafe(column.iter().map(|x| vec![x.clone()]).collect())
+}
+
+/// Term `+ c symbol` of a linear combination in LaTeX and in plain text,
+/// e.g. `- \frac{1}{2}v_{1}` and `- 1/2 v1`.
+fn term<T: MatrixNumber>(coefficient: &T, symbol: &str, plain_symbol: &str) -> (String, String) {
+    let (sign, abs) = match coefficient.is_negative() {
+        true => ('-', T::zero().checked_sub(coefficient)),
+        false => ('+', Some(coefficient.clone())),
+    };
+    match abs {
+        Some(abs) if abs.is_one() => (
+            format!(" {sign} {symbol}"),
+            format!(" {sign} {plain_symbol}"),
+        ),
+        Some(abs) => (
+            format!(" {sign} {}{symbol}", abs.to_latex()),
+            format!(" {sign} {} {plain_symbol}", abs.to_plain()),
+        ),
+        None => (
+            format!(" + {}{symbol}", coefficient.to_latex_single()),
+            format!(" + ({}) {plain_symbol}", coefficient.to_plain()),
+        ),
+    }
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the entries of a column vector.
+    pub(crate) fn column_vector(&self) -> anyhow::Result<Vec<T>> {
+        match self.get_shape() {
+            (rows, 1) if rows > 0 => Ok(self.get_data().iter().map(|row| row[0].clone()).collect()),
+            _ => bail!(Error::ExpectedColumnVector),
+        }
+    }
+
+    pub(crate) fn columns(&self) -> Vec<Vec<T>> {
+        let (rows, cols) = self.get_shape();
+        (0..cols)
+            .map(|j| (0..rows).map(|i| self.get_data()[i][j].clone()).collect())
+            .collect()
+    }
+
+    pub(crate) fn from_columns(columns: &[Vec<T>]) -> Self {
+        let rows = columns.first().map_or(0, Vec::len);
+        Matrix::new_unsafe(
+            (0..rows)
+                .map(|i| columns.iter().map(|column| column[i].clone()).collect())
+                .collect(),
+        )
+    }
+
+    /// Converts the matrix to floats, e.g. to run the approximate algorithms.
+    pub fn to_float(&self) -> anyhow::Result<Matrix<Float64>> {
+        Ok(self
+            .convert(|x| x.to_f64().map(Float64::from))
+            .ok_or(Error::ConversionFailed)?)
+    }
+
+    /// Returns the dot product of two column vectors.
+    /// # Example
+    /// ```
+    /// # use jp2gmd_lib::Matrix;
+    /// let u = Matrix::new(vec![vec![1], vec![2]]).unwrap();
+    /// let v = Matrix::new(vec![vec![3], vec![-1]]).unwrap();
+    /// assert_eq!(u.dot(&v).unwrap(), 1);
+    /// ```
+    pub fn dot(&self, other: &Self) -> anyhow::Result<T> {
+        let (u, v) = (self.column_vector()?, other.column_vector()?);
+        if u.len() != v.len() {
+            bail!(Error::DifferentShapes);
+        }
+        Ok(dot_product(&u, &v).ok_or_else(T::checked_ops_error)?)
+    }
+
+    /// Returns the Euclidean norm of a column vector. Fails if the square
+    /// root cannot be represented, e.g. for rationals which are not squares.
+    pub fn norm(&self) -> anyhow::Result<T> {
+        let square = self.dot(self)?;
+        Ok(square
+            .checked_sqrt()
+            .ok_or_else(|| Error::UnsupportedSquareRoot {
+                value: square.to_string(),
+            })?)
+    }
+
+    /// Returns the orthogonal projection of the column vector onto `onto`.
+    pub fn project_onto(&self, onto: &Self) -> anyhow::Result<Self> {
+        let square = onto.dot(onto)?;
+        if square.is_zero() {
+            bail!(Error::DivisionByZero);
+        }
+        let coefficient = self
+            .dot(onto)?
+            .checked_div(&square)
+            .ok_or_else(T::checked_ops_error)?;
+        onto.checked_mul_scl(&coefficient)
+    }
+
+    /// Returns a matrix whose columns form an orthogonal (not normalized)
+    /// basis of the space spanned by the columns of the matrix, computed with
+    /// the Gram-Schmidt process: `v_k = a_k - sum_j <a_k, v_j>/<v_j, v_j> v_j`.
+    /// Linearly dependent columns become zero columns.
+    pub fn gram_schmidt(&self) -> anyhow::Result<Derivation<T>> {
+        let mut orthogonal: Vec<Vec<T>> = vec![];
+        let mut equations = vec![];
+
+        for (k, column) in self.columns().into_iter().enumerate() {
+            let mut vector = column;
+            let mut latex = format!("a_{{{}}}", k + 1);
+            let mut plain = format!("a{}", k + 1);
+            for (j, previous) in orthogonal.iter().enumerate() {
+                let square = dot_product(previous, previous).ok_or(Error::CalculationFailed)?;
+                if square.is_zero() {
+                    continue;
+                }
+                let coefficient = dot_product(&vector, previous)
+                    .and_then(|product| product.checked_div(&square))
+                    .ok_or(Error::CalculationFailed)?;
+                if coefficient.is_zero() {
+                    continue;
+                }
+                for (x, y) in vector.iter_mut().zip(previous) {
+                    *x = x
+                        .checked_sub(
+                            &y.checked_mul(&coefficient)
+                                .ok_or(Error::CalculationFailed)?,
+                        )
+                        .ok_or(Error::CalculationFailed)?;
+                    // rounding errors of approximate numbers are dropped
+                    if x.is_zero() {
+                        *x = T::zero();
+                    }
+                }
+                let negated = T::zero()
+                    .checked_sub(&coefficient)
+                    .ok_or(Error::CalculationFailed)?;
+                let (latex_term, plain_term) = term(
+                    &negated,
+                    &format!("v_{{{}}}", j + 1),
+                    &format!("v{}", j + 1),
+                );
+                latex += &latex_term;
+                plain += &plain_term;
+            }
+            equations.push(Equation {
+                symbol: format!("v_{{{}}}", k + 1),
+                formula: Some(Formula { latex, plain }),
+                value: Some(column_matrix(&vector)),
+            });
+            orthogonal.push(vector);
+        }
+
+        Ok(Derivation {
+            initial: self.clone(),
+            equations,
+            results: vec![("V".to_string(), Self::from_columns(&orthogonal))],
+        })
+    }
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the lower triangular `L` with positive diagonal such that
+    /// `A = LL^T`, the Cholesky decomposition of a symmetric positive definite
+    /// matrix. Over exact fields it fails if a square root on the diagonal
+    /// cannot be represented.
+    pub fn cholesky(&self) -> anyhow::Result<Self> {
+        if !self.is_symmetric() {
+            bail!(Error::NotSymmetric);
+        }
+        let a = self.get_data();
+        let n = a.len();
+        let mut l = Self::zeros((n, n)).consume();
+        for j in 0..n {
+            let square = (0..j)
+                .try_fold(a[j][j].clone(), |sum, k| {
+                    sum.checked_sub(&l[j][k].checked_mul(&l[j][k])?)
+                })
+                .ok_or_else(T::checked_ops_error)?;
+            if square.is_zero() || square.is_negative() {
+                bail!(Error::NotPositiveDefinite);
+            }
+            l[j][j] = square
+                .checked_sqrt()
+                .ok_or_else(|| Error::UnsupportedSquareRoot {
+                    value: square.to_string(),
+                })?;
+            for i in j + 1..n {
+                l[i][j] = (0..j)
+                    .try_fold(a[i][j].clone(), |sum, k| {
+                        sum.checked_sub(&l[i][k].checked_mul(&l[j][k])?)
+                    })
+                    .and_then(|x| x.checked_div(&l[j][j]))
+                    .ok_or_else(T::checked_ops_error)?;
+            }
+        }
+        Ok(Matrix::new_unsafe(l))
+    }
+}
+
+impl Matrix<Float64> {
+    /// Returns the QR decomposition `A = QR` with orthogonal `Q` and upper
+    /// triangular `R`, computed with Householder reflections
+    /// `H_k = I - 2 vv^T / v^Tv`, which zero the entries under the diagonal
+    /// column by column.
+    pub fn qr(&self) -> anyhow::Result<Derivation<Float64>> {
+        let (rows, cols) = self.get_shape();
+        let mut q = Matrix::identity(rows);
+        let mut r = self.clone().with_separator(None);
+        let mut equations = vec![];
+        let mut previous = "A".to_string();
+
+        for k in 0..cols.min(rows.saturating_sub(1)) {
+            let x: Vec<Float64> = (k..rows).map(|i| r.get_data()[i][k]).collect();
+            let norm = dot_product(&x, &x)
+                .and_then(|square| square.checked_sqrt())
+                .ok_or(Error::CalculationFailed)?;
+            if norm.is_zero() {
+                continue;
+            }
+            // the sign is chosen so that no cancellation occurs
+            let alpha = if x[0].is_negative() { norm } else { -norm };
+            let mut v = x;
+            v[0] = v[0] - alpha;
+            let square = dot_product(&v, &v).ok_or(Error::CalculationFailed)?;
+            if square.is_zero() {
+                continue;
+            }
+
+            let two = Float64::from(2.);
+            let reflection = Matrix::filled((rows, rows), |i, j| {
+                let identity = if i == j {
+                    Float64::one()
+                } else {
+                    Float64::zero()
+                };
+                match (i.checked_sub(k), j.checked_sub(k)) {
+                    (Some(a), Some(b)) => identity - two * v[a] * v[b] / square,
+                    _ => identity,
+                }
+            });
+            r = reflection.checked_mul(&r)?.snapped();
+            q = q.checked_mul(&reflection)?;
+
+            let symbol = format!("H_{{{}}}", equations.len() / 2 + 1);
+            equations.push(Equation {
+                symbol: symbol.clone(),
+                formula: Some(Formula {
+                    latex: r"I - 2\frac{vv^T}{v^Tv}".to_string(),
+                    plain: "I - 2 vv^T / v^Tv".to_string(),
+                }),
+                value: Some(reflection),
+            });
+            let r_symbol = format!("R_{{{}}}", equations.len() / 2 + 1);
+            equations.push(Equation {
+                symbol: r_symbol.clone(),
+                formula: Some(Formula {
+                    latex: format!("{symbol}{previous}"),
+                    plain: format!("{} {}", symbol, previous).replace(['{', '}', '_'], ""),
+                }),
+                value: Some(r.clone()),
+            });
+            previous = r_symbol;
+        }
+
+        Ok(Derivation {
+            initial: self.clone(),
+            equations,
+            results: vec![("Q".to_string(), q.snapped()), ("R".to_string(), r)],
+        })
+    }
+
+    /// Converts the matrix of floats back to the field `T`.
+    pub fn to_field<T: MatrixNumber>(&self) -> anyhow::Result<Matrix<T>> {
+        Ok(self
+            .convert(|x| T::from_f64(f64::from(*x)))
+            .ok_or(Error::ConversionFailed)?)
+    }
+
+    /// Replaces entries within the tolerance of zero with zeros.
+    pub(crate) fn snapped(self) -> Self {
+        let separator = self.get_separator();
+        Matrix::new_unsafe(
+            self.consume()
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|x| if x.is_zero() { Float64::zero() } else { x })
+                        .collect()
+                })
+                .collect(),
+        )
+        .with_separator(separator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fm, ri, rm, rv};
+    use num_rational::Rational64;
+
+    #[test]
+    fn test_vector_functions() {
+        let u = rm![1; 2; 2];
+        let v = rm![1; 0; 1];
+        assert_eq!(u.dot(&v).unwrap(), ri!(3));
+        assert_eq!(u.norm().unwrap(), ri!(3));
+        assert_eq!(
+            u.project_onto(&v).unwrap(),
+            Matrix::new(vec![
+                vec![Rational64::new(3, 2)],
+                vec![ri!(0)],
+                vec![Rational64::new(3, 2)]
+            ])
+            .unwrap()
+        );
+        assert!(v.norm().is_err());
+        assert!(rm![1, 2].dot(&rm![1, 2]).is_err());
+        assert!(u.dot(&rm![1; 2]).is_err());
+        assert!(u.project_onto(&rm![0; 0; 0]).is_err());
+    }
+
+    #[test]
+    fn test_gram_schmidt() {
+        let m = rm![1, 1, 2; 1, 0, 1; 0, 1, 1];
+        let derivation = m.gram_schmidt().unwrap();
+        let result = &derivation.results[0].1;
+
+        let columns = result.columns();
+        for i in 0..3 {
+            for j in 0..i {
+                assert_eq!(dot_product(&columns[i], &columns[j]), Some(ri!(0)));
+            }
+        }
+        // the third column is the sum of the first two
+        assert_eq!(columns[2], vec![ri!(0); 3]);
+        assert_eq!(
+            derivation.equations[1].to_latex(),
+            r"v_{2} = a_{2} - \frac{1}{2}v_{1} = \left[\begin{array}{c}\frac{1}{2}\\-\frac{1}{2}\\1\end{array}\right]"
+        );
+        assert_eq!(
+            derivation.equations[1].formula.as_ref().unwrap().plain,
+            "a2 - 1/2 v1"
+        );
+        assert_eq!(derivation.latex_steps().len(), 5);
+    }
+
+    #[test]
+    fn test_cholesky() {
+        let a = rm![4, 2, -2; 2, 10, 2; -2, 2, 6];
+        let l = a.cholesky().unwrap();
+        assert_eq!(l, rm![2, 0, 0; 1, 3, 0; -1, 1, 2]);
+        assert_eq!(l.checked_mul(&l.transpose()).unwrap(), a);
+
+        assert!(rm![1, 2; 3, 4].cholesky().is_err());
+        assert!(rm![1, 2; 2, 1].cholesky().is_err());
+        // sqrt(2) is not rational
+        assert!(rm![2, 0; 0, 1].cholesky().is_err());
+
+        let b = fm![2., 1.; 1., 2.];
+        let l = b.cholesky().unwrap();
+        assert_eq!(l.checked_mul(&l.transpose()).unwrap(), b);
+    }
+
+    #[test]
+    fn test_qr() {
+        let a = fm![12., -51., 4.; 6., 167., -68.; -4., 24., -41.];
+        let derivation = a.qr().unwrap();
+        let (q, r) = (&derivation.results[0].1, &derivation.results[1].1);
+
+        assert_eq!(q.checked_mul(r).unwrap(), a);
+        assert_eq!(q.transpose().checked_mul(q).unwrap(), Matrix::identity(3));
+        for i in 0..3 {
+            for j in 0..i {
+                assert!(r.get_data()[i][j].is_zero());
+            }
+        }
+        assert_eq!(f64::from(r.get_data()[0][0]).abs(), 14.);
+        assert_eq!(derivation.equations.len(), 4);
+        assert!(derivation
+            .export(ExportFormat::Ascii)
+            .contains("R2 = H2 R1"));
+    }
+}
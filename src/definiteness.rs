@@ -0,0 +1,362 @@
+use crate::constants::{MAX_JACOBI_SWEEPS, MAX_PRINCIPAL_MINORS_ORDER};
+use crate::errors::Error;
+use crate::float::{Float64, Tolerance};
+use crate::matrices::Matrix;
+use crate::traits::MatrixNumber;
+use anyhow::bail;
+
+/// Definiteness of a symmetric matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Definiteness {
+    PositiveDefinite,
+    PositiveSemidefinite,
+    NegativeDefinite,
+    NegativeSemidefinite,
+    Indefinite,
+}
+
+impl Definiteness {
+    /// Name of the definiteness, also used as the translation key.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Definiteness::PositiveDefinite => "Positive definite",
+            Definiteness::PositiveSemidefinite => "Positive semidefinite",
+            Definiteness::NegativeDefinite => "Negative definite",
+            Definiteness::NegativeSemidefinite => "Negative semidefinite",
+            Definiteness::Indefinite => "Indefinite",
+        }
+    }
+
+    /// Number representing the definiteness in the shell - `2` and `1` for
+    /// positive definite and semidefinite matrices, `-2` and `-1` for the
+    /// negative ones and `0` for indefinite matrices.
+    pub fn value(&self) -> i64 {
+        match self {
+            Definiteness::PositiveDefinite => 2,
+            Definiteness::PositiveSemidefinite => 1,
+            Definiteness::NegativeDefinite => -2,
+            Definiteness::NegativeSemidefinite => -1,
+            Definiteness::Indefinite => 0,
+        }
+    }
+
+    fn to_latex(self) -> &'static str {
+        match self {
+            Definiteness::PositiveDefinite => r"A \succ 0",
+            Definiteness::PositiveSemidefinite => r"A \succeq 0",
+            Definiteness::NegativeDefinite => r"A \prec 0",
+            Definiteness::NegativeSemidefinite => r"A \preceq 0",
+            Definiteness::Indefinite => r"A \not\succeq 0 \land A \not\preceq 0",
+        }
+    }
+}
+
+/// Sign of a value checked by the test, -1, 0 or 1.
+fn sign<T: MatrixNumber>(value: &T) -> i8 {
+    match (value.is_zero(), value.is_negative()) {
+        (true, _) => 0,
+        (false, true) => -1,
+        (false, false) => 1,
+    }
+}
+
+/// Value whose sign decides the definiteness, e.g. a leading principal minor
+/// `\Delta_{2}` or an eigenvalue `\lambda_{1}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignCheck<T: MatrixNumber> {
+    pub symbol: String,
+    pub value: T,
+}
+
+impl<T: MatrixNumber> SignCheck<T> {
+    pub fn to_latex(&self) -> String {
+        let relation = match sign(&self.value) {
+            -1 => " < 0",
+            1 => " > 0",
+            _ => "",
+        };
+        format!("{} = {}{}", self.symbol, self.value.to_latex(), relation)
+    }
+}
+
+/// Definiteness of a matrix along with all values whose signs were checked.
+#[derive(Debug, Clone)]
+pub struct DefinitenessTest<T: MatrixNumber> {
+    pub definiteness: Definiteness,
+    pub checks: Vec<SignCheck<T>>,
+}
+
+impl<T: MatrixNumber> DefinitenessTest<T> {
+    /// All checked values followed by the conclusion, each in LaTeX.
+    pub fn latex_steps(&self) -> Vec<String> {
+        self.checks
+            .iter()
+            .map(SignCheck::to_latex)
+            .chain(std::iter::once(format!(
+                r"\Rightarrow {}",
+                self.definiteness.to_latex()
+            )))
+            .collect()
+    }
+
+    pub fn to_latex(&self) -> String {
+        self.latex_steps().join("\n")
+    }
+}
+
+/// Eigenvalues of a symmetric matrix in descending order, computed with the
+/// Jacobi eigenvalue method - rotations zeroing the off-diagonal entries one
+/// by one.
+fn symmetric_eigenvalues(m: &Matrix<Float64>) -> anyhow::Result<Vec<f64>> {
+    let mut a: Vec<Vec<f64>> = m
+        .get_data()
+        .iter()
+        .map(|row| row.iter().map(|&x| f64::from(x)).collect())
+        .collect();
+    let n = a.len();
+    let norm = a.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+
+    for _ in 0..MAX_JACOBI_SWEEPS {
+        let mut rotated = false;
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() <= f64::EPSILON * norm {
+                    continue;
+                }
+                rotated = true;
+                let theta = (a[q][q] - a[p][p]) / (2. * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+                let c = 1. / (t * t + 1.).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    (row[p], row[q]) = (c * row[p] - s * row[q], s * row[p] + c * row[q]);
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                for (k, (x, y)) in row_p.into_iter().zip(row_q).enumerate() {
+                    (a[p][k], a[q][k]) = (c * x - s * y, s * x + c * y);
+                }
+            }
+        }
+        if !rotated {
+            let mut eigenvalues: Vec<f64> = (0..n).map(|i| a[i][i]).collect();
+            eigenvalues.sort_by(|x, y| y.total_cmp(x));
+            return Ok(eigenvalues);
+        }
+    }
+    bail!(Error::NotConverged)
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the definiteness of a symmetric matrix. Over exact fields it is
+    /// decided by the signs of the principal minors (Sylvester's criterion),
+    /// over approximate ones by the signs of the eigenvalues.
+    pub fn definiteness(&self) -> anyhow::Result<DefinitenessTest<T>> {
+        if !self.is_symmetric() {
+            bail!(Error::NotSymmetric);
+        }
+        match T::IS_EXACT {
+            true => self.minors_test(),
+            false => self.eigenvalues_test(),
+        }
+    }
+
+    fn principal_minor(&self, indices: &[usize]) -> anyhow::Result<T> {
+        let a = self.get_data();
+        Matrix::filled((indices.len(), indices.len()), |i, j| {
+            a[indices[i]][indices[j]].clone()
+        })
+        .determinant()
+    }
+
+    /// The matrix is positive definite if all leading principal minors are
+    /// positive and negative definite if their signs alternate starting with
+    /// a negative one. Otherwise, all principal minors have to be checked for
+    /// semidefiniteness.
+    fn minors_test(&self) -> anyhow::Result<DefinitenessTest<T>> {
+        let n = self.get_shape().0;
+        // minors of odd orders of negative (semi)definite matrices are negative
+        let alternating = |order: usize| if order % 2 == 1 { -1 } else { 1 };
+
+        let leading = (1..=n)
+            .map(|k| {
+                Ok(SignCheck {
+                    symbol: format!(r"\Delta_{{{k}}}"),
+                    value: self.principal_minor(&(0..k).collect::<Vec<_>>())?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if leading.iter().all(|check| sign(&check.value) == 1) {
+            return Ok(DefinitenessTest {
+                definiteness: Definiteness::PositiveDefinite,
+                checks: leading,
+            });
+        }
+        if (1..=n)
+            .zip(&leading)
+            .all(|(k, check)| sign(&check.value) == alternating(k))
+        {
+            return Ok(DefinitenessTest {
+                definiteness: Definiteness::NegativeDefinite,
+                checks: leading,
+            });
+        }
+
+        if n > MAX_PRINCIPAL_MINORS_ORDER {
+            bail!(Error::MatrixTooLarge {
+                limit: MAX_PRINCIPAL_MINORS_ORDER
+            });
+        }
+        let (mut positive, mut negative) = (true, true);
+        let mut checks = vec![];
+        for order in 1..=n {
+            for mask in (1u32..1 << n).filter(|mask| mask.count_ones() as usize == order) {
+                let indices: Vec<usize> = (0..n).filter(|i| mask & (1 << i) != 0).collect();
+                let value = self.principal_minor(&indices)?;
+                positive &= sign(&value) >= 0;
+                negative &= sign(&value) * alternating(order) >= 0;
+                let rows: Vec<String> = indices.iter().map(|i| (i + 1).to_string()).collect();
+                checks.push(SignCheck {
+                    symbol: format!(r"\det A_{{\{{{}\}}}}", rows.join(", ")),
+                    value,
+                });
+                if !positive && !negative {
+                    return Ok(DefinitenessTest {
+                        definiteness: Definiteness::Indefinite,
+                        checks,
+                    });
+                }
+            }
+        }
+        Ok(DefinitenessTest {
+            definiteness: match positive {
+                true => Definiteness::PositiveSemidefinite,
+                false => Definiteness::NegativeSemidefinite,
+            },
+            checks,
+        })
+    }
+
+    /// Classifies the matrix by the signs of its eigenvalues, treating the
+    /// ones within the tolerance of zero as zeros.
+    fn eigenvalues_test(&self) -> anyhow::Result<DefinitenessTest<T>> {
+        let eigenvalues = symmetric_eigenvalues(&self.to_float()?)?;
+        let tolerance = Tolerance::current();
+        let signs: Vec<i8> = eigenvalues
+            .iter()
+            .map(|&x| match x {
+                x if tolerance.is_zero(x) => 0,
+                x if x < 0. => -1,
+                _ => 1,
+            })
+            .collect();
+
+        let definiteness = match (signs.contains(&1), signs.contains(&0), signs.contains(&-1)) {
+            (true, true, true) | (true, false, true) => Definiteness::Indefinite,
+            (_, false, false) => Definiteness::PositiveDefinite,
+            (false, false, true) => Definiteness::NegativeDefinite,
+            (true, true, false) | (false, true, false) => Definiteness::PositiveSemidefinite,
+            (false, true, true) => Definiteness::NegativeSemidefinite,
+        };
+        let checks = eigenvalues
+            .into_iter()
+            .zip(signs)
+            .enumerate()
+            .map(|(i, (x, sign))| {
+                Ok(SignCheck {
+                    symbol: format!(r"\lambda_{{{}}}", i + 1),
+                    value: match sign {
+                        0 => T::zero(),
+                        _ => T::from_f64(x).ok_or(Error::ConversionFailed)?,
+                    },
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(DefinitenessTest {
+            definiteness,
+            checks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fm, ri, rm, rv};
+    use num_rational::Rational64;
+
+    #[test]
+    fn test_definiteness_by_minors() {
+        let definiteness = |m: Matrix<Rational64>| m.definiteness().unwrap().definiteness;
+        assert_eq!(
+            definiteness(rm![2, -1, 0; -1, 2, -1; 0, -1, 2]),
+            Definiteness::PositiveDefinite
+        );
+        assert_eq!(
+            definiteness(rm![-2, 1; 1, -2]),
+            Definiteness::NegativeDefinite
+        );
+        assert_eq!(
+            definiteness(rm![1, 1; 1, 1]),
+            Definiteness::PositiveSemidefinite
+        );
+        // the leading minors are all zero
+        assert_eq!(
+            definiteness(rm![0, 0; 0, -1]),
+            Definiteness::NegativeSemidefinite
+        );
+        assert_eq!(definiteness(rm![1, 2; 2, 1]), Definiteness::Indefinite);
+        assert_eq!(definiteness(rm![0, 1; 1, 0]), Definiteness::Indefinite);
+        assert!(rm![1, 2; 3, 4].definiteness().is_err());
+    }
+
+    #[test]
+    fn test_definiteness_latex() {
+        let test = rm![2, 1; 1, 2].definiteness().unwrap();
+        assert_eq!(
+            test.latex_steps(),
+            vec![
+                r"\Delta_{1} = 2 > 0",
+                r"\Delta_{2} = 3 > 0",
+                r"\Rightarrow A \succ 0"
+            ]
+        );
+        let test = rm![0, 0; 0, -1].definiteness().unwrap();
+        assert_eq!(
+            test.to_latex(),
+            [
+                r"\det A_{\{1\}} = 0",
+                r"\det A_{\{2\}} = -1 < 0",
+                r"\det A_{\{1, 2\}} = 0",
+                r"\Rightarrow A \preceq 0"
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_definiteness_by_eigenvalues() {
+        let definiteness = |m: Matrix<Float64>| m.definiteness().unwrap().definiteness;
+        assert_eq!(
+            definiteness(fm![2., -1., 0.; -1., 2., -1.; 0., -1., 2.]),
+            Definiteness::PositiveDefinite
+        );
+        assert_eq!(
+            definiteness(fm![1., 2.; 2., 4.]),
+            Definiteness::PositiveSemidefinite
+        );
+        assert_eq!(
+            definiteness(fm![-1., 0.; 0., -3.]),
+            Definiteness::NegativeDefinite
+        );
+        assert_eq!(definiteness(fm![1., 2.; 2., 1.]), Definiteness::Indefinite);
+
+        let test = fm![2., 1.; 1., 2.].definiteness().unwrap();
+        assert_eq!(
+            test.checks
+                .iter()
+                .map(|check| check.value)
+                .collect::<Vec<_>>(),
+            vec![Float64::from(3.), Float64::from(1.)]
+        );
+    }
+}
@@ -1,16 +1,23 @@
 use crate::env_gui::insert_to_env;
 use crate::environment::{Environment, Identifier, Type};
+use crate::errors::Error;
+use crate::import::split_cells_with_separator;
 use crate::locale::Locale;
 use crate::matrices::Matrix;
 use crate::parser::parse_expression;
+use crate::rationals::rationalize_decimal;
 use crate::traits::MatrixNumber;
-use crate::{State, WindowState};
-use anyhow::bail;
+use crate::{toasts_error, State, WindowState};
+use anyhow::{bail, Context};
+use arboard::Clipboard;
 use egui::{Sense, Ui};
+use egui_toast::Toasts;
 use std::collections::HashMap;
+use std::time::Duration;
 
 pub enum EditorType {
-    Matrix(usize, usize, Vec<String>),
+    /// Height, width, cells and the position of the vertical separator.
+    Matrix(usize, usize, Vec<String>, Option<usize>),
     Scalar(String),
 }
 
@@ -31,6 +38,7 @@ pub fn display_editor<K: MatrixNumber>(ctx: &egui::Context, state: &mut State<K>
             editor_content,
             &mut state.env,
             &mut state.windows,
+            (&mut state.clipboard, &mut state.toasts),
             locale,
         );
         match result {
@@ -54,6 +62,7 @@ pub fn set_editor_to_matrix(state: &mut EditorState, def: &str) {
             DEFAULT_ROWS,
             DEFAULT_COLS,
             vec![String::from(def); DEFAULT_ROWS * DEFAULT_COLS],
+            None,
         ),
     });
 }
@@ -68,7 +77,7 @@ pub fn set_editor_to_existing_matrix<K: MatrixNumber>(
     let data = data.iter().map(|el| el.to_string()).collect();
     state.editor_content = Some(EditorContent {
         identifier_name: name,
-        editor_type: EditorType::Matrix(w, h, data),
+        editor_type: EditorType::Matrix(w, h, data, matrix.get_separator()),
     });
 }
 
@@ -95,6 +104,7 @@ fn display_editor_is_some<K: MatrixNumber>(
     content: &mut EditorContent,
     env: &mut Environment<K>,
     windows: &mut HashMap<Identifier, WindowState>,
+    (clipboard, toasts): (&mut Clipboard, &mut Toasts),
     locale: &Locale,
 ) -> anyhow::Result<bool> {
     let mut handled: anyhow::Result<bool> = Ok(false);
@@ -110,9 +120,17 @@ fn display_editor_is_some<K: MatrixNumber>(
             ui.label(locale.get_translated("Identifier:"));
             ui.text_edit_singleline(identifier_name);
             let result = match editor_type {
-                EditorType::Matrix(h, w, data) => {
-                    display_matrix_editor((h, w), data, ui, locale);
-                    parse_matrix_data::<K>((h, w), data, env)
+                EditorType::Matrix(h, w, data, separator) => {
+                    display_matrix_import(
+                        (h, w),
+                        (data, separator),
+                        ui,
+                        (clipboard, toasts),
+                        locale,
+                        K::IS_EXACT,
+                    );
+                    display_matrix_editor((h, w), (data, separator), ui, locale);
+                    parse_matrix_data::<K>((h, w), data, *separator, env)
                 }
                 EditorType::Scalar(data) => {
                     display_scalar_editor(data, ui, locale);
@@ -126,7 +144,9 @@ fn display_editor_is_some<K: MatrixNumber>(
             };
             if let Err(err) = &result {
                 err_msg = Some(
-                    locale.get_translated("Matrix is invalid!") + "\n" + err.to_string().as_str(),
+                    locale.get_translated("Matrix is invalid!")
+                        + "\n"
+                        + &locale.get_translated_error(err),
                 );
             };
             ui.horizontal(|ui| {
@@ -162,13 +182,16 @@ fn display_editor_is_some<K: MatrixNumber>(
 fn parse_matrix_data<K: MatrixNumber>(
     (h, w): (&mut usize, &mut usize),
     data: &mut [String],
+    separator: Option<usize>,
     env: &Environment<K>,
 ) -> anyhow::Result<Type<K>> {
     let mut result: Vec<K> = vec![];
     for element in data.iter() {
         result.push(parse_scalar_with_env(element, env)?)
     }
-    Ok(Type::Matrix(Matrix::from_vec(result, (*h, *w))?))
+    Ok(Type::Matrix(
+        Matrix::from_vec(result, (*h, *w))?.with_separator(separator),
+    ))
 }
 
 fn parse_scalar_data<K: MatrixNumber>(
@@ -181,13 +204,77 @@ fn parse_scalar_data<K: MatrixNumber>(
 fn parse_scalar_with_env<K: MatrixNumber>(data: &str, env: &Environment<K>) -> anyhow::Result<K> {
     match parse_expression(data, env)? {
         Type::Scalar(scalar) => Ok(scalar),
-        Type::Matrix(_) => bail!("Invalid expression! Result is not a scalar."),
+        Type::Matrix(_) => bail!(Error::ExpectedScalar),
+    }
+}
+
+fn display_matrix_import(
+    (h, w): (&mut usize, &mut usize),
+    (data, separator): (&mut Vec<String>, &mut Option<usize>),
+    ui: &mut Ui,
+    (clipboard, toasts): (&mut Clipboard, &mut Toasts),
+    locale: &Locale,
+    exact: bool,
+) {
+    let mut imported = None;
+    ui.horizontal(|ui| {
+        if ui.button(locale.get_translated("Paste")).clicked() {
+            imported = Some(
+                clipboard
+                    .get_text()
+                    .context(locale.get_translated("Clipboard does not contain text"))
+                    .and_then(|text| split_cells_with_separator(&text)),
+            );
+        }
+        if ui.button(locale.get_translated("Open file")).clicked() {
+            imported = rfd::FileDialog::new()
+                .add_filter(
+                    locale.get_translated("Matrix files"),
+                    &["csv", "tsv", "txt", "tex"],
+                )
+                .pick_file()
+                .map(|path| {
+                    std::fs::read_to_string(&path)
+                        .with_context(|| format!("Cannot read file {:?}!", path.display()))
+                        .and_then(|text| split_cells_with_separator(&text))
+                });
+        }
+    });
+
+    match imported {
+        Some(Ok((cells, imported_separator))) => {
+            *h = cells.len();
+            *w = cells[0].len();
+            *data = cells.into_iter().flatten().collect();
+            *separator = imported_separator;
+            if exact {
+                rationalize_decimals(data);
+            }
+        }
+        Some(Err(error)) => toasts_error(
+            toasts,
+            locale.get_translated("Failed to import matrix")
+                + "\n"
+                + &locale.get_translated_error(&error),
+            Duration::from_secs(5),
+        ),
+        None => {}
+    }
+}
+
+/// Replaces decimal cells with the fractions they are rounded from, e.g.
+/// `0.3333` with `1/3`, so that pasted values stay exact.
+fn rationalize_decimals(data: &mut [String]) {
+    for cell in data {
+        if let Some(fraction) = rationalize_decimal(cell) {
+            *cell = fraction.to_string();
+        }
     }
 }
 
 fn display_matrix_editor(
     (h, w): (&mut usize, &mut usize),
-    data: &mut Vec<String>,
+    (data, separator): (&mut Vec<String>, &mut Option<usize>),
     ui: &mut Ui,
     locale: &Locale,
 ) {
@@ -199,6 +286,12 @@ fn display_matrix_editor(
         ui.label(locale.get_translated("Width"));
         ui.add(egui::DragValue::new(w));
         ui.end_row();
+        // 0 stands for no separator
+        let mut column = separator.unwrap_or(0);
+        ui.label(locale.get_translated("Separator after column"));
+        ui.add(egui::DragValue::new(&mut column).clamp_range(0..=w.saturating_sub(1)));
+        ui.end_row();
+        *separator = (column > 0 && column < *w).then_some(column);
     });
     ui.separator();
     if data.len() != *h * *w {
@@ -0,0 +1,596 @@
+use crate::decompositions::{dot_product, Derivation, Equation, Formula};
+use crate::errors::Error;
+use crate::matrices::Matrix;
+use crate::traits::{LaTeXable, MatrixNumber};
+use anyhow::bail;
+use num_rational::Rational64;
+use num_traits::{CheckedAdd, CheckedMul, One, Signed, Zero};
+use std::collections::BTreeSet;
+
+/// Rational eigenvalue with its algebraic multiplicity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eigenvalue<T: MatrixNumber> {
+    pub value: T,
+    pub multiplicity: usize,
+}
+
+/// Characteristic polynomial `\det(\lambda I - A)` of a matrix factored over
+/// the rationals - the rational eigenvalues in ascending order and the
+/// remaining factor without rational roots. Polynomials are represented by
+/// their coefficients starting from the constant term.
+#[derive(Debug, Clone)]
+pub struct Spectrum<T: MatrixNumber> {
+    pub polynomial: Vec<Rational64>,
+    pub eigenvalues: Vec<Eigenvalue<T>>,
+    pub remainder: Vec<Rational64>,
+}
+
+/// Roots with their multiplicities.
+type Roots = Vec<(Rational64, usize)>;
+
+fn gcd(a: i64, b: i64) -> i64 {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
+
+fn divisors(n: u64) -> Vec<u64> {
+    let mut divisors = vec![];
+    let mut d = 1;
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            divisors.push(d);
+            divisors.push(n / d);
+        }
+        d += 1;
+    }
+    divisors
+}
+
+fn evaluate(polynomial: &[Rational64], x: &Rational64) -> Option<Rational64> {
+    polynomial
+        .iter()
+        .rev()
+        .try_fold(Rational64::zero(), |value, coefficient| {
+            value.checked_mul(x)?.checked_add(coefficient)
+        })
+}
+
+/// Divides the polynomial by `\lambda - root` with the synthetic division,
+/// the remainder is dropped.
+fn deflate(polynomial: &[Rational64], root: &Rational64) -> Option<Vec<Rational64>> {
+    let mut quotient = vec![Rational64::zero(); polynomial.len() - 1];
+    let mut carry = Rational64::zero();
+    for k in (1..polynomial.len()).rev() {
+        carry = carry.checked_mul(root)?.checked_add(&polynomial[k])?;
+        quotient[k - 1] = carry;
+    }
+    Some(quotient)
+}
+
+/// Rational roots of a polynomial with their multiplicities in ascending
+/// order and the polynomial divided by all the corresponding factors. The
+/// roots are found with the rational root theorem - the numerator of a root
+/// divides the constant term and its denominator divides the leading
+/// coefficient of the polynomial scaled to integer coefficients.
+fn rational_roots(mut polynomial: Vec<Rational64>) -> Option<(Roots, Vec<Rational64>)> {
+    let mut roots = vec![];
+    let zeros = polynomial.iter().take_while(|c| c.is_zero()).count();
+    if zeros > 0 && zeros < polynomial.len() {
+        polynomial.drain(..zeros);
+        roots.push((Rational64::zero(), zeros));
+    }
+    if polynomial.len() < 2 {
+        return Some((roots, polynomial));
+    }
+
+    let scale = polynomial.iter().try_fold(1_i64, |lcm, c| {
+        (lcm / gcd(lcm, *c.denom())).checked_mul(*c.denom())
+    })?;
+    let integer = |c: &Rational64| {
+        Some(
+            c.checked_mul(&Rational64::from_integer(scale))?
+                .to_integer(),
+        )
+    };
+    let constant = integer(polynomial.first()?)?.unsigned_abs();
+    let leading = integer(polynomial.last()?)?.unsigned_abs();
+
+    let mut candidates = BTreeSet::new();
+    for denominator in divisors(leading) {
+        for numerator in divisors(constant) {
+            let root = Rational64::new(
+                i64::try_from(numerator).ok()?,
+                i64::try_from(denominator).ok()?,
+            );
+            candidates.insert(root);
+            candidates.insert(-root);
+        }
+    }
+    for candidate in candidates {
+        let mut multiplicity = 0;
+        while polynomial.len() > 1 && evaluate(&polynomial, &candidate)?.is_zero() {
+            polynomial = deflate(&polynomial, &candidate)?;
+            multiplicity += 1;
+        }
+        if multiplicity > 0 {
+            roots.push((candidate, multiplicity));
+        }
+    }
+    roots.sort();
+    Some((roots, polynomial))
+}
+
+/// Polynomial in `\lambda` in LaTeX and in plain text, e.g.
+/// `\lambda^{2} - \frac{1}{2}\lambda + 3` and `lambda^2 - 1/2 lambda + 3`.
+fn polynomial_text(polynomial: &[Rational64]) -> (String, String) {
+    let (mut latex, mut plain) = (String::new(), String::new());
+    for (k, coefficient) in polynomial.iter().enumerate().rev() {
+        if coefficient.is_zero() {
+            continue;
+        }
+        let sign = match (latex.is_empty(), coefficient.is_negative()) {
+            (true, true) => "-",
+            (true, false) => "",
+            (false, true) => " - ",
+            (false, false) => " + ",
+        };
+        let abs = coefficient.abs();
+        let (number_latex, number_plain) = match abs.is_one() && k > 0 {
+            true => (String::new(), String::new()),
+            false => (
+                abs.to_latex(),
+                abs.to_string() + if k > 0 { " " } else { "" },
+            ),
+        };
+        let (variable_latex, variable_plain) = match k {
+            0 => (String::new(), String::new()),
+            1 => (r"\lambda".to_string(), "lambda".to_string()),
+            _ => (format!(r"\lambda^{{{k}}}"), format!("lambda^{k}")),
+        };
+        latex += &format!("{sign}{number_latex}{variable_latex}");
+        plain += &format!("{sign}{number_plain}{variable_plain}");
+    }
+    match latex.is_empty() {
+        true => ("0".to_string(), "0".to_string()),
+        false => (latex, plain),
+    }
+}
+
+/// Matrix `A - \lambda I` in LaTeX and in plain text, e.g. `A + 2I`.
+fn shifted_text<T: MatrixNumber>(eigenvalue: &T) -> (String, String) {
+    match (eigenvalue.is_zero(), eigenvalue.is_negative()) {
+        (true, _) => ("A".to_string(), "A".to_string()),
+        (false, negative) => {
+            let sign = if negative { '+' } else { '-' };
+            let abs = eigenvalue.abs();
+            let (latex, plain) = match abs.is_one() {
+                true => (String::new(), String::new()),
+                false => (abs.to_latex_single(), abs.to_plain() + " "),
+            };
+            (format!("A {sign} {latex}I"), format!("A {sign} {plain}I"))
+        }
+    }
+}
+
+impl<T: MatrixNumber> Spectrum<T> {
+    /// Equation of the expanded and the factored characteristic polynomial.
+    pub fn equation(&self) -> Equation<T> {
+        let (expanded_latex, expanded_plain) = polynomial_text(&self.polynomial);
+        let (mut factored_latex, mut factored_plain) = (String::new(), String::new());
+        for eigenvalue in &self.eigenvalues {
+            let exponent = eigenvalue.multiplicity;
+            let root = match eigenvalue.value.to_rational() {
+                Some(root) => root,
+                None => continue,
+            };
+            let factor = polynomial_text(&[-root, Rational64::one()]);
+            let (latex, plain) = match (root.is_zero(), exponent) {
+                (true, 1) => (factor.0, factor.1),
+                (true, _) => (
+                    format!("{}^{{{exponent}}}", factor.0),
+                    format!("{}^{exponent}", factor.1),
+                ),
+                (false, 1) => (format!("({})", factor.0), format!("({})", factor.1)),
+                (false, _) => (
+                    format!("({})^{{{exponent}}}", factor.0),
+                    format!("({})^{exponent}", factor.1),
+                ),
+            };
+            factored_latex += &latex;
+            factored_plain += &plain;
+        }
+        if self.remainder.len() > 1 {
+            let (latex, plain) = polynomial_text(&self.remainder);
+            factored_latex += &format!("({latex})");
+            factored_plain += &format!("({plain})");
+        }
+
+        let mut latex = format!(r"\det(\lambda I - A) = {expanded_latex}");
+        let mut plain = format!("det(lambda I - A) = {expanded_plain}");
+        if !self.eigenvalues.is_empty() && factored_latex != expanded_latex {
+            latex += &format!(" = {factored_latex}");
+            plain += &format!(" = {factored_plain}");
+        }
+        Equation {
+            symbol: r"p(\lambda)".to_string(),
+            formula: Some(Formula { latex, plain }),
+            value: None,
+        }
+    }
+
+    /// Whether all eigenvalues are rational.
+    pub fn is_rational(&self) -> bool {
+        self.remainder.len() < 2
+    }
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the coefficients of the characteristic polynomial
+    /// `\det(\lambda I - A)` starting from the constant term, computed with
+    /// the Faddeev-LeVerrier algorithm.
+    pub fn characteristic_polynomial(&self) -> anyhow::Result<Vec<T>> {
+        let (n, cols) = self.get_shape();
+        if n != cols {
+            bail!(Error::NotSquare);
+        }
+        let mut coefficients = vec![T::zero(); n + 1];
+        coefficients[n] = T::one();
+        let mut m = Matrix::zeros((n, n));
+        for k in 1..=n {
+            m = self
+                .checked_mul(&m)?
+                .checked_add(&Matrix::identity(n).checked_mul_scl(&coefficients[n - k + 1])?)?;
+            let trace = self.checked_mul(&m)?.trace()?;
+            coefficients[n - k] = T::from_usize(k)
+                .and_then(|k| T::zero().checked_sub(&trace)?.checked_div(&k))
+                .ok_or_else(T::checked_ops_error)?;
+        }
+        Ok(coefficients)
+    }
+
+    /// Returns the characteristic polynomial factored over the rationals.
+    pub fn spectrum(&self) -> anyhow::Result<Spectrum<T>> {
+        let polynomial = self
+            .characteristic_polynomial()?
+            .iter()
+            .map(T::to_rational)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::NotRational)?;
+        let (roots, remainder) = rational_roots(polynomial.clone()).ok_or(Error::Overflow)?;
+        let eigenvalues = roots
+            .into_iter()
+            .map(|(root, multiplicity)| {
+                Ok(Eigenvalue {
+                    value: T::from_rational(root).ok_or(Error::NotRational)?,
+                    multiplicity,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Spectrum {
+            polynomial,
+            eigenvalues,
+            remainder,
+        })
+    }
+
+    /// Returns a basis of the kernel (null space) of the matrix, read from
+    /// its reduced row echelon form - one vector for every free column.
+    pub fn kernel_basis(&self) -> anyhow::Result<Vec<Vec<T>>> {
+        let cols = self.get_shape().1;
+        let reduced = self.clone().with_separator(None).echelon()?.result;
+        let pivots: Vec<(usize, usize)> = reduced
+            .get_data()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| Some((i, row.iter().position(|x| !x.is_zero())?)))
+            .collect();
+        (0..cols)
+            .filter(|free| pivots.iter().all(|(_, pivot)| pivot != free))
+            .map(|free| {
+                let mut vector = vec![T::zero(); cols];
+                vector[free] = T::one();
+                for &(i, pivot) in &pivots {
+                    vector[pivot] = T::zero()
+                        .checked_sub(&reduced.get_data()[i][free])
+                        .ok_or_else(T::checked_ops_error)?;
+                }
+                Ok(vector)
+            })
+            .collect()
+    }
+
+    /// Returns `A - \lambda I`.
+    fn shifted(&self, eigenvalue: &T) -> anyhow::Result<Self> {
+        self.checked_sub(&Matrix::identity(self.get_shape().0).checked_mul_scl(eigenvalue)?)
+    }
+
+    fn apply(&self, vector: &[T]) -> anyhow::Result<Vec<T>> {
+        self.get_data()
+            .iter()
+            .map(|row| Ok(dot_product(row, vector).ok_or_else(T::checked_ops_error)?))
+            .collect()
+    }
+
+    /// Returns the rational spectrum of the matrix, failing if some
+    /// eigenvalues are not rational.
+    fn rational_spectrum(&self) -> anyhow::Result<Spectrum<T>> {
+        let spectrum = self.spectrum()?;
+        if !spectrum.is_rational() {
+            bail!(Error::IrrationalEigenvalues);
+        }
+        Ok(spectrum)
+    }
+
+    /// Returns the diagonalization `A = PDP^{-1}` with the eigenvectors in the
+    /// columns of `P` and the eigenvalues on the diagonal of `D`. Fails with
+    /// an explanation if the matrix is not diagonalizable over the
+    /// rationals - if some eigenvalues are not rational or if an eigenvalue
+    /// has fewer linearly independent eigenvectors than its multiplicity.
+    pub fn diagonalize(&self) -> anyhow::Result<Derivation<T>> {
+        let spectrum = self.rational_spectrum()?;
+        let mut equations = vec![spectrum.equation()];
+        let (mut vectors, mut diagonal) = (vec![], vec![]);
+        for Eigenvalue {
+            value,
+            multiplicity,
+        } in &spectrum.eigenvalues
+        {
+            let kernel = self.shifted(value)?.kernel_basis()?;
+            let (latex, _) = shifted_text(value);
+            equations.push(Equation {
+                symbol: format!(r"\ker({latex})"),
+                formula: None,
+                value: Some(Matrix::from_columns(&kernel)),
+            });
+            if kernel.len() < *multiplicity {
+                bail!(Error::DefectiveEigenvalue {
+                    eigenvalue: value.to_plain(),
+                    algebraic: *multiplicity,
+                    geometric: kernel.len(),
+                });
+            }
+            diagonal.extend(std::iter::repeat_n(value.clone(), kernel.len()));
+            vectors.extend(kernel);
+        }
+
+        let n = diagonal.len();
+        Ok(Derivation {
+            initial: self.clone(),
+            equations,
+            results: vec![
+                ("P".to_string(), Matrix::from_columns(&vectors)),
+                ("D".to_string(), diagonal_matrix(&diagonal, &vec![false; n])),
+            ],
+        })
+    }
+
+    /// Returns the Jordan normal form `A = PJP^{-1}` of a matrix with rational
+    /// eigenvalues. The columns of `P` are Jordan chains - generalized
+    /// eigenvectors `N^{k-1}x, ..., Nx, x` of `N = A - \lambda I`, where `x`
+    /// belongs to the kernel of `N^k`, but not of `N^{k-1}`.
+    pub fn jordan(&self) -> anyhow::Result<Derivation<T>> {
+        let spectrum = self.rational_spectrum()?;
+        let mut equations = vec![spectrum.equation()];
+        let (mut vectors, mut diagonal, mut superdiagonal) = (vec![], vec![], vec![]);
+        for Eigenvalue {
+            value,
+            multiplicity,
+        } in &spectrum.eigenvalues
+        {
+            let shifted = self.shifted(value)?;
+            let (latex, _) = shifted_text(value);
+
+            // kernels of the powers of N until they contain all generalized eigenvectors
+            let mut kernels: Vec<Vec<Vec<T>>> = vec![vec![]];
+            let mut power = Matrix::identity(self.get_shape().0);
+            while kernels.last().map_or(0, Vec::len) < *multiplicity {
+                power = power.checked_mul(&shifted)?;
+                let kernel = power.kernel_basis()?;
+                let k = kernels.len();
+                equations.push(Equation {
+                    symbol: match k {
+                        1 => format!(r"\ker({latex})"),
+                        _ => format!(r"\ker\left(({latex})^{{{k}}}\right)"),
+                    },
+                    formula: None,
+                    value: Some(Matrix::from_columns(&kernel)),
+                });
+                if kernel.len() <= kernels.last().map_or(0, Vec::len) {
+                    bail!(Error::CalculationFailed);
+                }
+                kernels.push(kernel);
+            }
+
+            // chains from the longest, each vector has to be independent of
+            // the smaller kernel and of the chains started before
+            let mut chains: Vec<Vec<Vec<T>>> = vec![];
+            for k in (1..kernels.len()).rev() {
+                let mut span: Vec<Vec<T>> = kernels[k - 1].clone();
+                span.extend(chains.iter().map(|chain| chain[k - 1].clone()));
+                let mut rank = rank_of(&span)?;
+                for candidate in &kernels[k] {
+                    span.push(candidate.clone());
+                    let extended = rank_of(&span)?;
+                    if extended == rank {
+                        span.pop();
+                        continue;
+                    }
+                    rank = extended;
+                    let mut chain = vec![candidate.clone()];
+                    for _ in 1..k {
+                        chain.insert(0, shifted.apply(&chain[0])?);
+                    }
+                    chains.push(chain);
+                }
+            }
+
+            for chain in chains {
+                for i in 0..chain.len() {
+                    diagonal.push(value.clone());
+                    superdiagonal.push(i + 1 < chain.len());
+                }
+                vectors.extend(chain);
+            }
+        }
+
+        Ok(Derivation {
+            initial: self.clone(),
+            equations,
+            results: vec![
+                ("P".to_string(), Matrix::from_columns(&vectors)),
+                ("J".to_string(), diagonal_matrix(&diagonal, &superdiagonal)),
+            ],
+        })
+    }
+}
+
+/// Number of linearly independent vectors.
+fn rank_of<T: MatrixNumber>(vectors: &[Vec<T>]) -> anyhow::Result<usize> {
+    if vectors.is_empty() {
+        return Ok(0);
+    }
+    let reduced = Matrix::new_unsafe(vectors.to_vec()).echelon()?.result;
+    Ok(reduced
+        .get_data()
+        .iter()
+        .filter(|row| row.iter().any(|x| !x.is_zero()))
+        .count())
+}
+
+/// Matrix with the given diagonal and ones above the diagonal entries marked
+/// in `superdiagonal`.
+fn diagonal_matrix<T: MatrixNumber>(diagonal: &[T], superdiagonal: &[bool]) -> Matrix<T> {
+    let n = diagonal.len();
+    Matrix::filled((n, n), |i, j| {
+        match (i == j, j == i + 1 && superdiagonal[i]) {
+            (true, _) => diagonal[i].clone(),
+            (false, true) => T::one(),
+            (false, false) => T::zero(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ri, rm, rv};
+
+    fn check_similar(a: &Matrix<Rational64>, derivation: &Derivation<Rational64>) {
+        let (p, j) = (&derivation.results[0].1, &derivation.results[1].1);
+        assert_eq!(a.checked_mul(p).unwrap(), p.checked_mul(j).unwrap());
+        assert!(p.inverse().is_ok());
+    }
+
+    #[test]
+    fn test_characteristic_polynomial() {
+        assert_eq!(
+            rm![2, 1; 1, 2].characteristic_polynomial().unwrap(),
+            vec![ri!(3), ri!(-4), ri!(1)]
+        );
+        let spectrum = rm![0, -1; 1, 0].spectrum().unwrap();
+        assert!(!spectrum.is_rational());
+        assert_eq!(
+            spectrum.equation().to_latex(),
+            r"p(\lambda) = \det(\lambda I - A) = \lambda^{2} + 1"
+        );
+
+        let spectrum = rm![1, 1, 0; 0, 1, 0; 0, 0, -2].spectrum().unwrap();
+        assert_eq!(
+            spectrum.eigenvalues,
+            vec![
+                Eigenvalue {
+                    value: ri!(-2),
+                    multiplicity: 1
+                },
+                Eigenvalue {
+                    value: ri!(1),
+                    multiplicity: 2
+                }
+            ]
+        );
+        assert_eq!(
+            spectrum.equation().formula.unwrap().plain,
+            "det(lambda I - A) = lambda^3 - 3 lambda + 2 = (lambda + 2)(lambda - 1)^2"
+        );
+    }
+
+    #[test]
+    fn test_rational_roots() {
+        let half = Rational64::new(1, 2);
+        // 2x^3 - x^2 = x^2 (2x - 1)
+        let (roots, remainder) = rational_roots(vec![ri!(0), ri!(0), ri!(-1), ri!(2)]).unwrap();
+        assert_eq!(roots, vec![(ri!(0), 2), (half, 1)]);
+        assert_eq!(remainder, vec![ri!(2)]);
+        // (x^2 - 2)(x + 1/3)
+        let (roots, remainder) = rational_roots(vec![
+            Rational64::new(-2, 3),
+            ri!(-2),
+            Rational64::new(1, 3),
+            ri!(1),
+        ])
+        .unwrap();
+        assert_eq!(roots, vec![(Rational64::new(-1, 3), 1)]);
+        assert_eq!(remainder, vec![ri!(-2), ri!(0), ri!(1)]);
+    }
+
+    #[test]
+    fn test_kernel_basis() {
+        assert_eq!(
+            rm![1, 2, 3; 2, 4, 6].kernel_basis().unwrap(),
+            vec![vec![ri!(-2), ri!(1), ri!(0)], vec![ri!(-3), ri!(0), ri!(1)]]
+        );
+        assert!(rm![1, 0; 0, 1].kernel_basis().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diagonalize() {
+        let a = rm![4, 1; 2, 3];
+        let derivation = a.diagonalize().unwrap();
+        check_similar(&a, &derivation);
+        assert_eq!(derivation.results[1].1, rm![2, 0; 0, 5]);
+        assert_eq!(
+            derivation.latex_steps()[2],
+            r"\ker(A - 2I) = \left[\begin{array}{c}-\frac{1}{2}\\1\end{array}\right]"
+        );
+
+        let error = rm![1, 1; 0, 1].diagonalize().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<Error>(),
+            Some(&Error::DefectiveEigenvalue {
+                eigenvalue: "1".to_string(),
+                algebraic: 2,
+                geometric: 1
+            })
+        );
+        let error = rm![0, -1; 1, 0].diagonalize().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<Error>(),
+            Some(&Error::IrrationalEigenvalues)
+        );
+    }
+
+    #[test]
+    fn test_jordan() {
+        let a = rm![5, 4, 2, 1; 0, 1, -1, -1; -1, -1, 3, 0; 1, 1, -1, 2];
+        let derivation = a.jordan().unwrap();
+        check_similar(&a, &derivation);
+        assert_eq!(
+            derivation.results[1].1,
+            rm![1, 0, 0, 0; 0, 2, 0, 0; 0, 0, 4, 1; 0, 0, 0, 4]
+        );
+
+        let b = rm![2, 1, 0; 0, 2, 1; 0, 0, 2];
+        let derivation = b.jordan().unwrap();
+        check_similar(&b, &derivation);
+        assert_eq!(derivation.results[1].1, b);
+        assert!(derivation
+            .latex_steps()
+            .iter()
+            .any(|step| step.starts_with(r"\ker\left((A - 2I)^{2}\right) = ")));
+
+        let identity: Matrix<Rational64> = Matrix::identity(2);
+        assert_eq!(identity.jordan().unwrap().results[1].1, identity);
+    }
+}
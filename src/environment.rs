@@ -1,10 +1,17 @@
 use std::collections::btree_map::IterMut;
 use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
 
-use anyhow::{bail, Context};
+use anyhow::bail;
 
+use crate::constants::{MAX_APPROXIMATION_DENOMINATOR, MAX_CONTINUED_FRACTION_TERMS};
+use crate::errors::Error;
+use crate::generators::Random;
+use crate::import::load_matrix;
+use crate::latex_reader::parse_latex;
 use crate::locale::Locale;
-use crate::traits::{GuiDisplayable, LaTeXable};
+use crate::rationals::{continued_fraction, rationalize};
+use crate::traits::{ExportFormat, Exportable, GuiDisplayable, LaTeXable};
 use crate::{matrices::Matrix, traits::MatrixNumber};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -19,7 +26,7 @@ impl Identifier {
         if Self::is_valid(&id) {
             Ok(Self { id })
         } else {
-            bail!("Invalid identifier.")
+            bail!(Error::InvalidIdentifier { id })
         }
     }
 
@@ -43,9 +50,9 @@ impl Identifier {
     }
 }
 
-impl ToString for Identifier {
-    fn to_string(&self) -> String {
-        self.id.clone()
+impl Display for Identifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
     }
 }
 
@@ -59,9 +66,7 @@ impl<T: MatrixNumber> Type<T> {
     pub fn from_scalar_option(opt: Option<T>) -> anyhow::Result<Self> {
         match opt {
             Some(val) => Ok(Self::Scalar(val)),
-            None => Err(anyhow::anyhow!(
-                "Arithmetic operation resulted in overflow!"
-            )),
+            None => bail!(T::checked_ops_error()),
         }
     }
 
@@ -72,23 +77,34 @@ impl<T: MatrixNumber> Type<T> {
     pub fn into_scalar(self) -> anyhow::Result<T> {
         match self {
             Type::Scalar(s) => Ok(s),
-            Type::Matrix(_) => bail!("Expected scalar, got matrix."),
+            Type::Matrix(_) => bail!(Error::ExpectedScalar),
         }
     }
 
+    /// Converts the value into another number type.
+    pub fn convert<U: MatrixNumber>(
+        &self,
+        convert: impl Fn(&T) -> Option<U>,
+    ) -> anyhow::Result<Type<U>> {
+        Ok(match self {
+            Type::Scalar(s) => Type::Scalar(convert(s).ok_or(Error::ConversionFailed)?),
+            Type::Matrix(m) => Type::Matrix(m.convert(convert).ok_or(Error::ConversionFailed)?),
+        })
+    }
+
     pub fn into_matrix(self) -> anyhow::Result<Matrix<T>> {
         match self {
             Type::Matrix(m) => Ok(m),
-            Type::Scalar(_) => bail!("Expected matrix, got scalar."),
+            Type::Scalar(_) => bail!(Error::ExpectedMatrix),
         }
     }
 }
 
-impl<T: MatrixNumber> ToString for Type<T> {
-    fn to_string(&self) -> String {
+impl<T: MatrixNumber> Display for Type<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Type::Scalar(s) => s.to_string(),
-            Type::Matrix(m) => m.to_string(),
+            Type::Scalar(s) => write!(f, "{}", s.to_string()),
+            Type::Matrix(m) => write!(f, "{m}"),
         }
     }
 }
@@ -124,29 +140,460 @@ impl<T: MatrixNumber> LaTeXable for Type<T> {
     }
 }
 
-pub type Callable<T> = dyn Fn(Type<T>) -> anyhow::Result<Type<T>>;
+impl<T: MatrixNumber> Exportable for Type<T> {
+    fn to_typst(&self) -> String {
+        match self {
+            Type::Scalar(s) => s.to_typst(),
+            Type::Matrix(m) => m.to_typst(),
+        }
+    }
+
+    fn to_mathml(&self) -> String {
+        match self {
+            Type::Scalar(s) => s.to_mathml(),
+            Type::Matrix(m) => m.to_mathml(),
+        }
+    }
+
+    fn to_plain(&self) -> String {
+        match self {
+            Type::Scalar(s) => s.to_plain(),
+            Type::Matrix(m) => m.to_plain(),
+        }
+    }
+
+    fn export(&self, format: ExportFormat) -> String {
+        match self {
+            Type::Scalar(s) => s.export(format),
+            Type::Matrix(m) => m.export(format),
+        }
+    }
+}
+
+/// Builtin function taking any number of comma-separated arguments.
+pub type Callable<T> = dyn Fn(Vec<Type<T>>) -> anyhow::Result<Type<T>>;
+
+/// Checks that exactly `N` arguments were passed.
+fn arguments<T: MatrixNumber, const N: usize>(args: Vec<Type<T>>) -> anyhow::Result<[Type<T>; N]> {
+    let given = args.len();
+    Ok(args
+        .try_into()
+        .map_err(|_| Error::ArgumentCount { expected: N, given })?)
+}
+
+fn unary<T: MatrixNumber>(
+    function: impl Fn(Type<T>) -> anyhow::Result<Type<T>> + 'static,
+) -> Box<Callable<T>> {
+    Box::new(move |args| {
+        let [t] = arguments(args)?;
+        function(t)
+    })
+}
+
+fn binary<T: MatrixNumber>(
+    function: impl Fn(Type<T>, Type<T>) -> anyhow::Result<Type<T>> + 'static,
+) -> Box<Callable<T>> {
+    Box::new(move |args| {
+        let [first, second] = arguments(args)?;
+        function(first, second)
+    })
+}
+
+fn ternary<T: MatrixNumber>(
+    function: impl Fn(Type<T>, Type<T>, Type<T>) -> anyhow::Result<Type<T>> + 'static,
+) -> Box<Callable<T>> {
+    Box::new(move |args| {
+        let [first, second, third] = arguments(args)?;
+        function(first, second, third)
+    })
+}
+
+/// Converts a non-negative integer to `usize`, `None` if the number is not
+/// an integer (`to_usize` alone would truncate it).
+pub fn integer_to_usize<T: MatrixNumber>(x: &T) -> Option<usize> {
+    let integer = x.to_usize()?;
+    (T::from_usize(integer)? == *x).then_some(integer)
+}
+
+/// Converts a 1-based index passed to `function` to a 0-based one.
+fn index<T: MatrixNumber>(t: Type<T>, function: &str) -> anyhow::Result<usize> {
+    Ok(integer_to_usize(&t.into_scalar()?)
+        .and_then(|index| index.checked_sub(1))
+        .ok_or_else(|| Error::InvalidFunctionArgument {
+            function: function.to_string(),
+        })?)
+}
+
+/// Converts a size of a matrix passed to `function`.
+fn size<T: MatrixNumber>(t: Type<T>, function: &str) -> anyhow::Result<usize> {
+    Ok(
+        integer_to_usize(&t.into_scalar()?).ok_or_else(|| Error::InvalidFunctionArgument {
+            function: function.to_string(),
+        })?,
+    )
+}
+
+/// Matrix of random numbers from `low` to `high` - integers in the exact mode.
+fn random_matrix<T: MatrixNumber>(args: Vec<Type<T>>) -> anyhow::Result<Type<T>> {
+    let [rows, cols, low, high] = arguments(args)?;
+    let shape = (size(rows, "rand")?, size(cols, "rand")?);
+    let bound = |t: Type<T>| {
+        t.into_scalar()?
+            .to_f64()
+            .ok_or_else(|| anyhow::Error::from(Error::ConversionFailed))
+    };
+    let (low, high) = (bound(low)?, bound(high)?);
+    Random::with_global(|random| match T::IS_EXACT {
+        true => Matrix::random_integers(shape, (low.ceil() as i64, high.floor() as i64), random),
+        false => Matrix::random_uniform(shape, (low, high), random),
+    })
+    .map(Type::Matrix)
+}
 
 fn builtin_functions<T: MatrixNumber>() -> BTreeMap<Identifier, Box<Callable<T>>> {
-    BTreeMap::from([
+    let mut functions = BTreeMap::from([
         (
             Identifier::new_unsafe("transpose".to_string()),
-            Box::new(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.transpose())))
-                as Box<Callable<T>>,
+            unary(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.transpose()))),
         ),
         (
             Identifier::new_unsafe("identity".to_string()),
-            Box::new(|t: Type<T>| {
-                Ok(Type::Matrix(Matrix::identity(
-                    t.into_scalar()?
-                        .to_usize()
-                        .context("Invalid identity argument")?,
-                )))
-            }) as Box<Callable<T>>,
+            unary(|t: Type<T>| Ok(Type::Matrix(Matrix::identity(size(t, "identity")?)))),
         ),
         (
             Identifier::new_unsafe("inverse".to_string()),
-            Box::new(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.inverse()?.result)))
-                as Box<Callable<T>>,
+            unary(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.inverse()?.result))),
+        ),
+        (
+            Identifier::new_unsafe("approx".to_string()),
+            unary(|t: Type<T>| t.convert(T::approx)),
+        ),
+        (
+            Identifier::new_unsafe("exact".to_string()),
+            unary(|t: Type<T>| t.convert(T::exact)),
+        ),
+        (
+            Identifier::new_unsafe("sqrt".to_string()),
+            unary(|t: Type<T>| {
+                let value = t.into_scalar()?;
+                Ok(Type::Scalar(value.checked_sqrt().ok_or_else(|| {
+                    Error::UnsupportedSquareRoot {
+                        value: value.to_string(),
+                    }
+                })?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("cfrac".to_string()),
+            unary(|t: Type<T>| {
+                // a truncated expansion has exactly MAX_CONTINUED_FRACTION_TERMS entries
+                let expansion = continued_fraction(&t.into_scalar()?, MAX_CONTINUED_FRACTION_TERMS)
+                    .ok_or_else(|| Error::InvalidFunctionArgument {
+                        function: "cfrac".to_string(),
+                    })?;
+                expansion
+                    .terms
+                    .into_iter()
+                    .map(T::from_i64)
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| T::checked_ops_error().into())
+                    .and_then(|row| Ok(Type::Matrix(Matrix::new(vec![row])?)))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("dot".to_string()),
+            binary(|u: Type<T>, v: Type<T>| {
+                Ok(Type::Scalar(u.into_matrix()?.dot(&v.into_matrix()?)?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("norm".to_string()),
+            unary(|v: Type<T>| Ok(Type::Scalar(v.into_matrix()?.norm()?))),
+        ),
+        (
+            Identifier::new_unsafe("proj".to_string()),
+            binary(|v: Type<T>, u: Type<T>| {
+                Ok(Type::Matrix(
+                    v.into_matrix()?.project_onto(&u.into_matrix()?)?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("cholesky".to_string()),
+            unary(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.cholesky()?))),
+        ),
+        (
+            Identifier::new_unsafe("definiteness".to_string()),
+            // Encoded as in `Definiteness::value`, see the table in GUIDE.md.
+            unary(|t: Type<T>| {
+                let definiteness = t.into_matrix()?.definiteness()?.definiteness;
+                Ok(Type::Scalar(
+                    T::from_i64(definiteness.value()).ok_or_else(T::checked_ops_error)?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("trace".to_string()),
+            unary(|t: Type<T>| Ok(Type::Scalar(t.into_matrix()?.trace()?))),
+        ),
+        (
+            Identifier::new_unsafe("sum".to_string()),
+            unary(|t: Type<T>| Ok(Type::Scalar(t.into_matrix()?.sum()?))),
+        ),
+        (
+            Identifier::new_unsafe("rowsum".to_string()),
+            unary(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.row_sums()?))),
+        ),
+        (
+            Identifier::new_unsafe("colsum".to_string()),
+            unary(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.column_sums()?))),
+        ),
+        (
+            Identifier::new_unsafe("min".to_string()),
+            unary(|t: Type<T>| Ok(Type::Scalar(t.into_matrix()?.min()?))),
+        ),
+        (
+            Identifier::new_unsafe("max".to_string()),
+            unary(|t: Type<T>| Ok(Type::Scalar(t.into_matrix()?.max()?))),
+        ),
+        (
+            Identifier::new_unsafe("norm1".to_string()),
+            unary(|t: Type<T>| Ok(Type::Scalar(t.into_matrix()?.norm_1()?))),
+        ),
+        (
+            Identifier::new_unsafe("norminf".to_string()),
+            unary(|t: Type<T>| Ok(Type::Scalar(t.into_matrix()?.norm_inf()?))),
+        ),
+        (
+            Identifier::new_unsafe("fnorm".to_string()),
+            unary(|t: Type<T>| {
+                let matrix = t.into_matrix()?;
+                let square = matrix.sum_of_squares()?;
+                if let Some(root) = square.checked_sqrt() {
+                    return Ok(Type::Scalar(root));
+                }
+                // The root is irrational, so the approximate norm is replaced
+                // with the nearest simple fraction.
+                let approximation =
+                    rationalize(matrix.frobenius_norm()?, MAX_APPROXIMATION_DENOMINATOR)
+                        .and_then(T::from_rational)
+                        .ok_or_else(|| Error::UnsupportedSquareRoot {
+                            value: square.to_string(),
+                        })?;
+                Ok(Type::Scalar(approximation))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("zeros".to_string()),
+            binary(|rows: Type<T>, cols: Type<T>| {
+                let shape = (size(rows, "zeros")?, size(cols, "zeros")?);
+                Ok(Type::Matrix(Matrix::zeros(shape)))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("ones".to_string()),
+            binary(|rows: Type<T>, cols: Type<T>| {
+                let shape = (size(rows, "ones")?, size(cols, "ones")?);
+                Ok(Type::Matrix(Matrix::ones(shape)))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("rand".to_string()),
+            Box::new(|args| random_matrix(args)),
+        ),
+        (
+            Identifier::new_unsafe("randint_invertible".to_string()),
+            unary(|n: Type<T>| {
+                let n = size(n, "randint_invertible")?;
+                Ok(Type::Matrix(Random::with_global(|random| {
+                    Matrix::random_invertible(n, random)
+                })?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("seed".to_string()),
+            unary(|t: Type<T>| {
+                let seed = t.clone().into_scalar()?.to_u64().ok_or_else(|| {
+                    Error::InvalidFunctionArgument {
+                        function: "seed".to_string(),
+                    }
+                })?;
+                Random::seed(seed);
+                Ok(t)
+            }),
+        ),
+        (
+            Identifier::new_unsafe("hilbert".to_string()),
+            unary(|n: Type<T>| Ok(Type::Matrix(Matrix::hilbert(size(n, "hilbert")?)?))),
+        ),
+        (
+            Identifier::new_unsafe("vandermonde".to_string()),
+            unary(|v: Type<T>| Ok(Type::Matrix(Matrix::vandermonde(&v.into_matrix()?)?))),
+        ),
+        (
+            Identifier::new_unsafe("toeplitz".to_string()),
+            unary(|v: Type<T>| Ok(Type::Matrix(Matrix::toeplitz(&v.into_matrix()?)?))),
+        ),
+        (
+            Identifier::new_unsafe("diag".to_string()),
+            unary(|t: Type<T>| {
+                let m = t.into_matrix()?;
+                Ok(Type::Matrix(match m.get_shape() {
+                    (1, _) | (_, 1) => Matrix::from_diagonal(&m)?,
+                    _ => m.diagonal()?,
+                }))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("kron".to_string()),
+            binary(|a: Type<T>, b: Type<T>| {
+                Ok(Type::Matrix(a.into_matrix()?.kronecker(&b.into_matrix()?)?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("hadamard".to_string()),
+            binary(|a: Type<T>, b: Type<T>| {
+                Ok(Type::Matrix(a.into_matrix()?.hadamard(&b.into_matrix()?)?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("dsum".to_string()),
+            binary(|a: Type<T>, b: Type<T>| {
+                Ok(Type::Matrix(a.into_matrix()?.direct_sum(&b.into_matrix()?)))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("comm".to_string()),
+            binary(|a: Type<T>, b: Type<T>| {
+                Ok(Type::Matrix(
+                    a.into_matrix()?.commutator(&b.into_matrix()?)?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("outer".to_string()),
+            binary(|u: Type<T>, v: Type<T>| {
+                Ok(Type::Matrix(u.into_matrix()?.outer(&v.into_matrix()?)?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("det".to_string()),
+            unary(|t: Type<T>| Ok(Type::Scalar(t.into_matrix()?.determinant()?))),
+        ),
+        (
+            Identifier::new_unsafe("minor".to_string()),
+            ternary(|m: Type<T>, i: Type<T>, j: Type<T>| {
+                let (i, j) = (index(i, "minor")?, index(j, "minor")?);
+                Ok(Type::Scalar(m.into_matrix()?.minor(i, j)?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("cofactors".to_string()),
+            unary(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.cofactor_matrix()?))),
+        ),
+        (
+            Identifier::new_unsafe("adjugate".to_string()),
+            unary(|t: Type<T>| Ok(Type::Matrix(t.into_matrix()?.adjugate()?))),
+        ),
+        (
+            Identifier::new_unsafe("cramer".to_string()),
+            // Only the solution, the derivation is copied by the GUI button.
+            binary(|a: Type<T>, b: Type<T>| {
+                let mut derivation = a.into_matrix()?.cramer(&b.into_matrix()?)?;
+                let (_, x) = derivation.results.remove(0);
+                Ok(Type::Matrix(x))
+            }),
+        ),
+    ]);
+    if !T::IS_EXACT {
+        functions.extend(approximate_functions());
+    }
+    functions
+}
+
+/// Functions available only in the approximate mode, computed over floats.
+fn approximate_functions<T: MatrixNumber>() -> [(Identifier, Box<Callable<T>>); 7] {
+    [
+        (
+            Identifier::new_unsafe("pinv".to_string()),
+            unary(|t: Type<T>| {
+                Ok(Type::Matrix(
+                    t.into_matrix()?.to_float()?.pinv()?.to_field()?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("cond".to_string()),
+            unary(|t: Type<T>| {
+                let cond = t.into_matrix()?.to_float()?.cond()?;
+                Ok(Type::Scalar(
+                    T::from_f64(cond.into()).ok_or(Error::ConversionFailed)?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("rank".to_string()),
+            unary(|t: Type<T>| {
+                let rank = t.into_matrix()?.to_float()?.rank()?;
+                Ok(Type::Scalar(
+                    T::from_usize(rank).ok_or(Error::ConversionFailed)?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("lstsq".to_string()),
+            binary(|a: Type<T>, b: Type<T>| {
+                let b = b.into_matrix()?.to_float()?;
+                Ok(Type::Matrix(
+                    a.into_matrix()?.to_float()?.lstsq(&b)?.to_field()?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("expm".to_string()),
+            unary(|t: Type<T>| {
+                Ok(Type::Matrix(
+                    t.into_matrix()?.to_float()?.expm()?.to_field()?,
+                ))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("rotation".to_string()),
+            unary(|t: Type<T>| {
+                let angle = t.into_scalar()?.to_f64().ok_or(Error::ConversionFailed)?;
+                let (sin, cos) = angle.sin_cos();
+                let entry = |x: f64| T::from_f64(x).ok_or(Error::ConversionFailed);
+                Ok(Type::Matrix(Matrix::new(vec![
+                    vec![entry(cos)?, entry(-sin)?],
+                    vec![entry(sin)?, entry(cos)?],
+                ])?))
+            }),
+        ),
+        (
+            Identifier::new_unsafe("sqrtm".to_string()),
+            unary(|t: Type<T>| {
+                Ok(Type::Matrix(
+                    t.into_matrix()?.to_float()?.sqrtm()?.to_field()?,
+                ))
+            }),
+        ),
+    ]
+}
+
+/// Function taking a string literal instead of a value, e.g. `load("A.csv")`.
+pub type TextCallable<T> = dyn Fn(&str, &Environment<T>) -> anyhow::Result<Type<T>>;
+
+fn builtin_text_functions<T: MatrixNumber>() -> BTreeMap<Identifier, Box<TextCallable<T>>> {
+    BTreeMap::from([
+        (
+            Identifier::new_unsafe("load".to_string()),
+            Box::new(|path: &str, env: &Environment<T>| Ok(Type::Matrix(load_matrix(path, env)?)))
+                as Box<TextCallable<T>>,
+        ),
+        (
+            Identifier::new_unsafe("latex".to_string()),
+            Box::new(|raw: &str, env: &Environment<T>| parse_latex(raw, env))
+                as Box<TextCallable<T>>,
         ),
     ])
 }
@@ -154,6 +601,7 @@ fn builtin_functions<T: MatrixNumber>() -> BTreeMap<Identifier, Box<Callable<T>>
 pub struct Environment<T: MatrixNumber> {
     env: BTreeMap<Identifier, Type<T>>,
     fun: BTreeMap<Identifier, Box<Callable<T>>>,
+    text_fun: BTreeMap<Identifier, Box<TextCallable<T>>>,
 }
 
 impl<T: MatrixNumber> Environment<T> {
@@ -161,6 +609,7 @@ impl<T: MatrixNumber> Environment<T> {
         Self {
             env: BTreeMap::new(),
             fun: builtin_functions(),
+            text_fun: builtin_text_functions(),
         }
     }
 
@@ -176,9 +625,26 @@ impl<T: MatrixNumber> Environment<T> {
         self.fun.get(id).map(|f| f.as_ref())
     }
 
+    pub fn get_text_function(&self, id: &Identifier) -> Option<&TextCallable<T>> {
+        self.text_fun.get(id).map(|f| f.as_ref())
+    }
+
     pub fn iter_mut(&mut self) -> IterMut<'_, Identifier, Type<T>> {
         self.env.iter_mut()
     }
+
+    /// Converts all values into another number type, e.g. when switching
+    /// between the exact and the approximate field.
+    pub fn convert<U: MatrixNumber>(
+        &self,
+        convert: impl Fn(&T) -> Option<U>,
+    ) -> anyhow::Result<Environment<U>> {
+        let mut env = Environment::new();
+        for (id, value) in &self.env {
+            env.insert(id.clone(), value.convert(&convert)?);
+        }
+        Ok(env)
+    }
 }
 
 impl<T: MatrixNumber> Default for Environment<T> {
@@ -190,6 +656,12 @@ impl<T: MatrixNumber> Default for Environment<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::float::Float64;
+    use crate::parser::parse_expression;
+    use crate::quadratic::Quadratic;
+    use crate::{fm, ri, rm, rv};
+    use num_rational::Rational64;
+    use num_traits::{Signed, ToPrimitive};
 
     #[test]
     fn test_identifier_validation() {
@@ -202,9 +674,9 @@ mod tests {
 
     #[test]
     fn test_identifier_new() {
-        assert!(matches!(Identifier::new("pociąg".to_string()), Ok(_)));
-        assert!(matches!(Identifier::new("32".to_string()), Err(_)));
-        assert!(matches!(Identifier::new("".to_string()), Err(_)));
+        assert!(Identifier::new("pociąg".to_string()).is_ok());
+        assert!(Identifier::new("32".to_string()).is_err());
+        assert!(Identifier::new("".to_string()).is_err());
     }
 
     #[test]
@@ -220,5 +692,365 @@ mod tests {
         assert!(env
             .get_function(&Identifier::new_unsafe("inverse".to_string()))
             .is_some());
+        assert!(env
+            .get_function(&Identifier::new_unsafe("approx".to_string()))
+            .is_some());
+        assert!(env
+            .get_function(&Identifier::new_unsafe("exact".to_string()))
+            .is_some());
+        assert!(env
+            .get_text_function(&Identifier::new_unsafe("load".to_string()))
+            .is_some());
+        assert!(env
+            .get_text_function(&Identifier::new_unsafe("latex".to_string()))
+            .is_some());
+    }
+
+    #[test]
+    fn test_env_convert() {
+        let mut env = Environment::<Rational64>::new();
+        let a = Identifier::new("A".to_string()).unwrap();
+        let x = Identifier::new("x".to_string()).unwrap();
+        env.insert(
+            a.clone(),
+            Type::Matrix(rm![1, 2; 3, 4].with_separator(Some(1))),
+        );
+        env.insert(x.clone(), Type::Scalar(Rational64::new(-1, 3)));
+
+        let approximate = env.convert(|x| Some(Float64::from(*x))).unwrap();
+        assert_eq!(
+            approximate.get_value(&x),
+            Some(&Type::Scalar(Float64::from(-1. / 3.)))
+        );
+        let exact = approximate.convert(|x| rationalize(*x, 1000)).unwrap();
+        assert_eq!(exact.get_value(&a), env.get_value(&a));
+        assert_eq!(exact.get_value(&x), env.get_value(&x));
+
+        let mut huge = Environment::<Float64>::new();
+        huge.insert(x, Type::Scalar(Float64::from(1e30)));
+        assert_eq!(
+            huge.convert(|x| rationalize(*x, 1000))
+                .err()
+                .and_then(|error| error.downcast::<Error>().ok()),
+            Some(Error::ConversionFailed)
+        );
+    }
+
+    #[test]
+    fn test_approx_and_exact_functions() {
+        let env = Environment::<Rational64>::new();
+        assert_eq!(
+            parse_expression("approx(2/3)", &env).unwrap(),
+            Type::Scalar(Rational64::new(667, 1000))
+        );
+        assert_eq!(
+            parse_expression("exact(2/3)", &env).unwrap(),
+            Type::Scalar(Rational64::new(2, 3))
+        );
+
+        let env = Environment::<Float64>::new();
+        let sum = parse_expression("exact(0.1 + 0.2)", &env)
+            .unwrap()
+            .into_scalar()
+            .unwrap();
+        assert_eq!(f64::from(sum), 0.3);
+    }
+
+    #[test]
+    fn test_sqrt_function() {
+        let env = Environment::<Rational64>::new();
+        assert_eq!(
+            parse_expression("sqrt(9/4)", &env).unwrap(),
+            Type::Scalar(Rational64::new(3, 2))
+        );
+        assert_eq!(
+            parse_expression("sqrt(2)", &env)
+                .unwrap_err()
+                .downcast::<Error>()
+                .unwrap(),
+            Error::UnsupportedSquareRoot {
+                value: "2".to_string()
+            }
+        );
+
+        let env = Environment::<Quadratic>::new();
+        let half_sqrt2 = parse_expression("sqrt(2)/2", &env).unwrap();
+        assert_eq!(parse_expression("1/sqrt(2)", &env).unwrap(), half_sqrt2);
+        assert_eq!(
+            parse_expression("sqrt(1/2) * sqrt(8)", &env).unwrap(),
+            Type::Scalar(Quadratic::from(Rational64::from_integer(2)))
+        );
+        assert!(parse_expression("sqrt(2) + sqrt(3)", &env).is_err());
+    }
+
+    #[test]
+    fn test_cfrac_function() {
+        let env = Environment::<Rational64>::new();
+        assert_eq!(
+            parse_expression("cfrac(415/93)", &env).unwrap(),
+            Type::Matrix(rm![4, 2, 6, 7])
+        );
+        assert!(parse_expression("cfrac(identity(2))", &env).is_err());
+
+        let env = Environment::<Quadratic>::new();
+        let mut sqrt2 = vec![Quadratic::from(Rational64::from_integer(1))];
+        sqrt2.resize(
+            MAX_CONTINUED_FRACTION_TERMS,
+            Quadratic::from(Rational64::from_integer(2)),
+        );
+        assert_eq!(
+            parse_expression("cfrac(sqrt(2))", &env).unwrap(),
+            Type::Matrix(Matrix::new(vec![sqrt2]).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_vector_functions() {
+        let mut env = Environment::<Rational64>::new();
+        let mut insert = |id: &str, value| {
+            env.insert(
+                Identifier::new(id.to_string()).unwrap(),
+                Type::Matrix(value),
+            )
+        };
+        insert("u", rm![1; 2; 2]);
+        insert("v", rm![2; 0; -1]);
+        insert("w", rm![1; 1; 0]);
+        insert("e", rm![1; 0; 0]);
+
+        assert_eq!(
+            parse_expression("dot(u, v)", &env).unwrap(),
+            Type::Scalar(ri!(0))
+        );
+        assert_eq!(
+            parse_expression("norm(u) * 2", &env).unwrap(),
+            Type::Scalar(ri!(6))
+        );
+        assert_eq!(
+            parse_expression("proj(u, 2 * e)", &env).unwrap(),
+            Type::Matrix(rm![1; 0; 0])
+        );
+        assert!(parse_expression("dot(u)", &env).is_err());
+        assert!(parse_expression("dot(u, transpose(u))", &env).is_err());
+        assert!(parse_expression("norm(w)", &env).is_err());
+    }
+
+    #[test]
+    fn test_definiteness_functions() {
+        let mut env = Environment::<Rational64>::new();
+        env.insert(
+            Identifier::new("A".to_string()).unwrap(),
+            Type::Matrix(rm![4, 2; 2, 5]),
+        );
+        assert_eq!(
+            parse_expression("cholesky(A)", &env).unwrap(),
+            Type::Matrix(rm![2, 0; 1, 2])
+        );
+        assert_eq!(
+            parse_expression("definiteness(A)", &env).unwrap(),
+            Type::Scalar(ri!(2))
+        );
+        assert_eq!(
+            parse_expression("definiteness(-1 * A)", &env).unwrap(),
+            Type::Scalar(ri!(-2))
+        );
+        assert!(parse_expression("cholesky(-1 * A)", &env).is_err());
+    }
+
+    #[test]
+    fn test_reduction_functions() {
+        let mut env = Environment::<Rational64>::new();
+        env.insert(
+            Identifier::new("A".to_string()).unwrap(),
+            Type::Matrix(rm![1, -2; -3, 4]),
+        );
+        let scalar = |value: i64| Type::Scalar(ri!(value));
+        assert_eq!(parse_expression("trace(A)", &env).unwrap(), scalar(5));
+        assert_eq!(parse_expression("sum(A)", &env).unwrap(), scalar(0));
+        assert_eq!(
+            parse_expression("rowsum(A)", &env).unwrap(),
+            Type::Matrix(rm![-1; 1])
+        );
+        assert_eq!(
+            parse_expression("colsum(A)", &env).unwrap(),
+            Type::Matrix(rm![-2, 2])
+        );
+        assert_eq!(parse_expression("min(A)", &env).unwrap(), scalar(-3));
+        assert_eq!(parse_expression("max(A)", &env).unwrap(), scalar(4));
+        assert_eq!(parse_expression("norm1(A)", &env).unwrap(), scalar(6));
+        assert_eq!(parse_expression("norminf(A)", &env).unwrap(), scalar(7));
+        assert_eq!(
+            parse_expression("fnorm(identity(4))", &env).unwrap(),
+            scalar(2)
+        );
+        let norm = parse_expression("fnorm(A)", &env)
+            .unwrap()
+            .into_scalar()
+            .unwrap();
+        assert!((norm.to_f64().unwrap() - 30_f64.sqrt()).abs() < 1e-9);
+        assert!(parse_expression("trace(transpose(rowsum(A)))", &env).is_err());
+    }
+
+    #[test]
+    fn test_generator_functions() {
+        let env = Environment::<Rational64>::new();
+        let matrix = |raw: &str| match parse_expression(raw, &env).unwrap() {
+            Type::Matrix(m) => m,
+            Type::Scalar(_) => panic!("{raw} is not a matrix"),
+        };
+        assert_eq!(matrix("zeros(2, 3)"), Matrix::zeros((2, 3)));
+        assert_eq!(matrix("ones(1, 2)"), rm![1, 1]);
+        assert_eq!(matrix("hilbert(3)"), Matrix::hilbert(3).unwrap());
+        assert_eq!(matrix("vandermonde(ones(1, 2) * 2)"), rm![1, 2; 1, 2]);
+        assert_eq!(matrix("toeplitz(ones(2, 1))"), rm![1, 1; 1, 1]);
+        assert_eq!(matrix("diag(ones(1, 2))"), rm![1, 0; 0, 1]);
+        assert_eq!(matrix("diag(identity(2) * 3)"), rm![3; 3]);
+        for raw in [
+            "identity(0.5)",
+            "zeros(1.5, 2)",
+            "minor(identity(2), 1.5, 1)",
+        ] {
+            let error = parse_expression(raw, &env).unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<Error>(),
+                Some(Error::InvalidFunctionArgument { .. })
+            ));
+        }
+
+        parse_expression("seed(5)", &env).unwrap();
+        let first = matrix("rand(3, 3, -2, 2)");
+        assert!(first
+            .get_data()
+            .iter()
+            .flatten()
+            .all(|x| x.is_integer() && x.abs() <= ri!(2)));
+        let invertible = matrix("randint_invertible(3)");
+        parse_expression("seed(5)", &env).unwrap();
+        assert_eq!(matrix("rand(3, 3, -2, 2)"), first);
+        assert_eq!(matrix("randint_invertible(3)"), invertible);
+        assert_eq!(invertible.determinant().unwrap().abs(), ri!(1));
+
+        assert!(parse_expression("rand(2, 2, 1/3, 1/2)", &env).is_err());
+        assert!(parse_expression("zeros(2)", &env).is_err());
+        assert!(parse_expression("diag(ones(2, 3))", &env).is_err());
+        assert!(parse_expression("rotation(1)", &env).is_err());
+    }
+
+    #[test]
+    fn test_product_functions() {
+        let mut env = Environment::<Rational64>::new();
+        env.insert(
+            Identifier::new("A".to_string()).unwrap(),
+            Type::Matrix(rm![0, 1; 0, 0]),
+        );
+        env.insert(
+            Identifier::new("B".to_string()).unwrap(),
+            Type::Matrix(rm![0, 0; 1, 0]),
+        );
+        env.insert(
+            Identifier::new("u".to_string()).unwrap(),
+            Type::Matrix(rm![1; 2]),
+        );
+        assert_eq!(
+            parse_expression("kron(identity(2), A)", &env).unwrap(),
+            parse_expression("dsum(A, A)", &env).unwrap()
+        );
+        assert_eq!(
+            parse_expression("hadamard(A + B, A)", &env).unwrap(),
+            Type::Matrix(rm![0, 1; 0, 0])
+        );
+        assert_eq!(
+            parse_expression("comm(A, B)", &env).unwrap(),
+            Type::Matrix(rm![1, 0; 0, -1])
+        );
+        assert_eq!(
+            parse_expression("outer(u, u)", &env).unwrap(),
+            Type::Matrix(rm![1, 2; 2, 4])
+        );
+        assert!(parse_expression("outer(A, u)", &env).is_err());
+        assert!(parse_expression("comm(A, u)", &env).is_err());
+    }
+
+    #[test]
+    fn test_cofactor_functions() {
+        let mut env = Environment::<Rational64>::new();
+        env.insert(
+            Identifier::new("A".to_string()).unwrap(),
+            Type::Matrix(rm![2, 1; 1, 3]),
+        );
+        env.insert(
+            Identifier::new("b".to_string()).unwrap(),
+            Type::Matrix(rm![3; 5]),
+        );
+        assert_eq!(
+            parse_expression("det(A)", &env).unwrap(),
+            Type::Scalar(ri!(5))
+        );
+        assert_eq!(
+            parse_expression("minor(A, 1, 2)", &env).unwrap(),
+            Type::Scalar(ri!(1))
+        );
+        assert_eq!(
+            parse_expression("cofactors(A)", &env).unwrap(),
+            Type::Matrix(rm![3, -1; -1, 2])
+        );
+        assert_eq!(
+            parse_expression("adjugate(A) * A", &env).unwrap(),
+            Type::Matrix(rm![5, 0; 0, 5])
+        );
+        assert_eq!(
+            parse_expression("A * cramer(A, b)", &env).unwrap(),
+            Type::Matrix(rm![3; 5])
+        );
+        assert!(parse_expression("minor(A, 0, 1)", &env).is_err());
+        assert!(parse_expression("minor(A, 3, 1)", &env).is_err());
+        assert!(parse_expression("minor(A, 1)", &env).is_err());
+    }
+
+    #[test]
+    fn test_approximate_functions() {
+        let mut env = Environment::<Float64>::new();
+        env.insert(
+            Identifier::new("A".to_string()).unwrap(),
+            Type::Matrix(fm![0., 1.; 1., 1.; 2., 1.]),
+        );
+        env.insert(
+            Identifier::new("b".to_string()).unwrap(),
+            Type::Matrix(fm![6.; 0.; 0.]),
+        );
+
+        assert_eq!(
+            parse_expression("lstsq(A, b)", &env).unwrap(),
+            Type::Matrix(fm![-3.; 5.])
+        );
+        assert_eq!(
+            parse_expression("rank(A * transpose(A))", &env).unwrap(),
+            Type::Scalar(Float64::from(2.))
+        );
+        assert_eq!(
+            parse_expression("pinv(A) * A", &env).unwrap(),
+            Type::Matrix(Matrix::identity(2))
+        );
+        assert!(parse_expression("cond(A * transpose(A))", &env).is_err());
+        assert!(parse_expression("cond(A)", &env).is_ok());
+        assert_eq!(
+            parse_expression("sqrtm(transpose(A) * A) * sqrtm(transpose(A) * A)", &env).unwrap(),
+            parse_expression("transpose(A) * A", &env).unwrap()
+        );
+        assert_eq!(
+            parse_expression("expm(identity(2) * 0)", &env).unwrap(),
+            Type::Matrix(Matrix::identity(2))
+        );
+        assert_eq!(
+            parse_expression("rotation(1) * transpose(rotation(1))", &env).unwrap(),
+            Type::Matrix(Matrix::identity(2))
+        );
+        assert_eq!(
+            parse_expression("rotation(0)", &env).unwrap(),
+            Type::Matrix(Matrix::identity(2))
+        );
+
+        let exact = Environment::<Rational64>::new();
+        assert!(parse_expression("rank(identity(2))", &exact).is_err());
     }
 }
@@ -0,0 +1,440 @@
+use crate::locale::{fill_placeholders, Locale};
+use std::fmt::{Display, Formatter};
+
+/// Errors of the calculator which can be presented to the user in any
+/// language. Every error has a stable code and an English message template
+/// (used as the translation key) with `{placeholders}` for its arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    // parser
+    UnterminatedString,
+    UndefinedIdentifier {
+        id: String,
+    },
+    UnexpectedToken {
+        token: String,
+        previous: String,
+    },
+    UnexpectedFirstToken {
+        token: String,
+    },
+    TextFunctionArgument {
+        function: String,
+    },
+    MismatchedBrackets,
+    InvalidUnaryOperator {
+        operator: char,
+    },
+    AssignmentInExpression,
+    InvalidExpression,
+    NumberConversion {
+        number: String,
+        type_name: String,
+    },
+    // operations
+    ScalarMatrixAddition,
+    ScalarMatrixSubtraction,
+    DivisionByZero,
+    DivisionByMatrix,
+    MatrixByScalarDivision,
+    InvalidExponent,
+    MatrixExponent,
+    Overflow,
+    NotFinite,
+    ConversionFailed,
+    NotRepresentable,
+    UnsupportedSquareRoot {
+        value: String,
+    },
+    NotRational,
+    // environment
+    InvalidIdentifier {
+        id: String,
+    },
+    ExpectedScalar,
+    ExpectedMatrix,
+    InvalidFunctionArgument {
+        function: String,
+    },
+    ArgumentCount {
+        expected: usize,
+        given: usize,
+    },
+    ExpectedColumnVector,
+    ExpectedVector,
+    EmptyRange,
+    // matrices
+    DifferentShapes,
+    MultiplicationShapes {
+        left: String,
+        right: String,
+    },
+    NonSquarePower,
+    // algorithms
+    CalculationFailed,
+    EmptyInverse,
+    NotSquare,
+    NotInvertible,
+    NotConverged,
+    NotSymmetric,
+    NotPositiveDefinite,
+    MatrixTooLarge {
+        limit: usize,
+    },
+    IrrationalEigenvalues,
+    DefectiveEigenvalue {
+        eigenvalue: String,
+        algebraic: usize,
+        geometric: usize,
+    },
+    IndexOutOfRange {
+        index: usize,
+        size: usize,
+    },
+    EmptyMatrix,
+    RankOutOfRange {
+        rank: usize,
+        max: usize,
+    },
+}
+
+impl Error {
+    /// Stable identifier of the error, e.g. `E101`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::UnterminatedString => "E101",
+            Error::UndefinedIdentifier { .. } => "E102",
+            Error::UnexpectedToken { .. } => "E103",
+            Error::UnexpectedFirstToken { .. } => "E104",
+            Error::TextFunctionArgument { .. } => "E105",
+            Error::MismatchedBrackets => "E106",
+            Error::InvalidUnaryOperator { .. } => "E107",
+            Error::AssignmentInExpression => "E108",
+            Error::InvalidExpression => "E109",
+            Error::NumberConversion { .. } => "E110",
+            Error::ScalarMatrixAddition => "E201",
+            Error::ScalarMatrixSubtraction => "E202",
+            Error::DivisionByZero => "E203",
+            Error::DivisionByMatrix => "E204",
+            Error::MatrixByScalarDivision => "E205",
+            Error::InvalidExponent => "E206",
+            Error::MatrixExponent => "E207",
+            Error::Overflow => "E208",
+            Error::NotFinite => "E209",
+            Error::ConversionFailed => "E210",
+            Error::NotRepresentable => "E211",
+            Error::UnsupportedSquareRoot { .. } => "E212",
+            Error::NotRational => "E213",
+            Error::InvalidIdentifier { .. } => "E301",
+            Error::ExpectedScalar => "E302",
+            Error::ExpectedMatrix => "E303",
+            Error::InvalidFunctionArgument { .. } => "E304",
+            Error::ArgumentCount { .. } => "E305",
+            Error::ExpectedColumnVector => "E306",
+            Error::ExpectedVector => "E307",
+            Error::EmptyRange => "E308",
+            Error::DifferentShapes => "E401",
+            Error::MultiplicationShapes { .. } => "E402",
+            Error::NonSquarePower => "E403",
+            Error::CalculationFailed => "E501",
+            Error::EmptyInverse => "E502",
+            Error::NotSquare => "E503",
+            Error::NotInvertible => "E504",
+            Error::NotConverged => "E505",
+            Error::NotSymmetric => "E506",
+            Error::NotPositiveDefinite => "E507",
+            Error::MatrixTooLarge { .. } => "E508",
+            Error::IrrationalEigenvalues => "E509",
+            Error::DefectiveEigenvalue { .. } => "E510",
+            Error::IndexOutOfRange { .. } => "E511",
+            Error::EmptyMatrix => "E512",
+            Error::RankOutOfRange { .. } => "E513",
+        }
+    }
+
+    /// English message with `{placeholders}`, also used as the translation key.
+    pub fn template(&self) -> &'static str {
+        match self {
+            Error::UnterminatedString => "Unterminated string!",
+            Error::UndefinedIdentifier { .. } => {
+                "Undefined identifier! Object \"{id}\" is unknown."
+            }
+            Error::UnexpectedToken { .. } => {
+                "Invalid expression! The {token} cannot follow {previous}"
+            }
+            Error::UnexpectedFirstToken { .. } => {
+                "Invalid expression! The {token} cannot be the first token!"
+            }
+            Error::TextFunctionArgument { .. } => {
+                "Function \"{function}\" expects a single string argument!"
+            }
+            Error::MismatchedBrackets => "Mismatched brackets!",
+            Error::InvalidUnaryOperator { .. } => {
+                "Operator {operator} cannot be used as a unary operator."
+            }
+            Error::AssignmentInExpression => "Assignment is not allowed in expressions!",
+            Error::InvalidExpression => "Invalid expression!",
+            Error::NumberConversion { .. } => {
+                "Number conversion failed! {number} cannot be parsed into {type_name}"
+            }
+            Error::ScalarMatrixAddition => "Adding scalar to matrix is not supported!",
+            Error::ScalarMatrixSubtraction => "Subtraction of scalar and matrix is not supported!",
+            Error::DivisionByZero => "Division by zero!",
+            Error::DivisionByMatrix => {
+                "Dividing by a matrix is not supported! Use the `inverse` function instead."
+            }
+            Error::MatrixByScalarDivision => "Dividing matrix by scalar is not supported yet!",
+            Error::InvalidExponent => "Exponent should be a nonnegative integer.",
+            Error::MatrixExponent => "Exponent cannot be a matrix!",
+            Error::Overflow => "Arithmetic operation resulted in overflow!",
+            Error::NotFinite => "Result is not finite!",
+            Error::ConversionFailed => "Value cannot be converted to the other field!",
+            Error::NotRepresentable => {
+                "Result cannot be represented exactly! Only one kind of square root can be used at once."
+            }
+            Error::UnsupportedSquareRoot { .. } => "Square root of {value} cannot be represented!",
+            Error::NotRational => "Only rational numbers are supported!",
+            Error::InvalidIdentifier { .. } => "Invalid identifier \"{id}\".",
+            Error::ExpectedScalar => "Expected scalar, got matrix.",
+            Error::ExpectedMatrix => "Expected matrix, got scalar.",
+            Error::InvalidFunctionArgument { .. } => "Invalid {function} argument!",
+            Error::ArgumentCount { .. } => {
+                "Invalid number of arguments! Expected {expected}, got {given}."
+            }
+            Error::ExpectedColumnVector => "Expected column vector!",
+            Error::ExpectedVector => "Expected row or column vector!",
+            Error::EmptyRange => "Range is empty! The lower bound is larger than the upper one.",
+            Error::DifferentShapes => "Matrices have different shapes!",
+            Error::MultiplicationShapes { .. } => {
+                "Cannot multiply matrices of shapes {left} and {right}!"
+            }
+            Error::NonSquarePower => "Only square matrices can be used in exponentiation!",
+            Error::CalculationFailed => "Calculations error!",
+            Error::EmptyInverse => "Cannot calculate inverse of an empty matrix!",
+            Error::NotSquare => "Matrix is not square!",
+            Error::NotInvertible => "Matrix is not invertible!",
+            Error::NotConverged => "Iterative algorithm did not converge!",
+            Error::NotSymmetric => "Matrix is not symmetric!",
+            Error::NotPositiveDefinite => "Matrix is not positive definite!",
+            Error::MatrixTooLarge { .. } => "Matrix is too large! At most {limit} rows are supported.",
+            Error::IrrationalEigenvalues => "Not all eigenvalues of the matrix are rational!",
+            Error::DefectiveEigenvalue { .. } => {
+                "Matrix is not diagonalizable! Eigenvalue {eigenvalue} has algebraic multiplicity {algebraic}, but only {geometric} linearly independent eigenvectors."
+            }
+            Error::IndexOutOfRange { .. } => "Index {index} is out of range! Expected a number from 1 to {size}.",
+            Error::EmptyMatrix => "Matrix is empty!",
+            Error::RankOutOfRange { .. } => {
+                "Rank {rank} is out of range! Expected a number from 0 to {max}."
+            }
+        }
+    }
+
+    /// Values of the placeholders of the template.
+    pub fn args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Error::UndefinedIdentifier { id } | Error::InvalidIdentifier { id } => {
+                vec![("id", id.clone())]
+            }
+            Error::UnsupportedSquareRoot { value } => vec![("value", value.clone())],
+            Error::MatrixTooLarge { limit } => vec![("limit", limit.to_string())],
+            Error::DefectiveEigenvalue {
+                eigenvalue,
+                algebraic,
+                geometric,
+            } => vec![
+                ("eigenvalue", eigenvalue.clone()),
+                ("algebraic", algebraic.to_string()),
+                ("geometric", geometric.to_string()),
+            ],
+            Error::IndexOutOfRange { index, size } => {
+                vec![("index", index.to_string()), ("size", size.to_string())]
+            }
+            Error::RankOutOfRange { rank, max } => {
+                vec![("rank", rank.to_string()), ("max", max.to_string())]
+            }
+            Error::ArgumentCount { expected, given } => {
+                vec![
+                    ("expected", expected.to_string()),
+                    ("given", given.to_string()),
+                ]
+            }
+            Error::UnexpectedToken { token, previous } => {
+                vec![("token", token.clone()), ("previous", previous.clone())]
+            }
+            Error::UnexpectedFirstToken { token } => vec![("token", token.clone())],
+            Error::TextFunctionArgument { function }
+            | Error::InvalidFunctionArgument { function } => {
+                vec![("function", function.clone())]
+            }
+            Error::InvalidUnaryOperator { operator } => vec![("operator", operator.to_string())],
+            Error::NumberConversion { number, type_name } => {
+                vec![("number", number.clone()), ("type_name", type_name.clone())]
+            }
+            Error::MultiplicationShapes { left, right } => {
+                vec![("left", left.clone()), ("right", right.clone())]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Message in the language of the locale, prefixed with the error code.
+    pub fn localize(&self, locale: &Locale) -> String {
+        format!(
+            "[{}] {}",
+            self.code(),
+            locale.get_translated_with(self.template(), &self.args())
+        )
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", fill_placeholders(self.template(), &self.args()))
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Locale {
+    /// Renders all causes of the error in the language of the locale, one
+    /// per line. Errors other than `Error` are left untranslated.
+    pub fn get_translated_error(&self, error: &anyhow::Error) -> String {
+        error
+            .chain()
+            .map(|cause| match cause.downcast_ref::<Error>() {
+                Some(error) => error.localize(self),
+                None => cause.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::Environment;
+    use crate::locale::Language;
+    use crate::parser::parse_expression;
+    use anyhow::Context;
+
+    fn all_errors() -> Vec<Error> {
+        let s = || "x".to_string();
+        vec![
+            Error::UnterminatedString,
+            Error::UndefinedIdentifier { id: s() },
+            Error::UnexpectedToken {
+                token: s(),
+                previous: s(),
+            },
+            Error::UnexpectedFirstToken { token: s() },
+            Error::TextFunctionArgument { function: s() },
+            Error::MismatchedBrackets,
+            Error::InvalidUnaryOperator { operator: '*' },
+            Error::AssignmentInExpression,
+            Error::InvalidExpression,
+            Error::NumberConversion {
+                number: s(),
+                type_name: s(),
+            },
+            Error::ScalarMatrixAddition,
+            Error::ScalarMatrixSubtraction,
+            Error::DivisionByZero,
+            Error::DivisionByMatrix,
+            Error::MatrixByScalarDivision,
+            Error::InvalidExponent,
+            Error::MatrixExponent,
+            Error::Overflow,
+            Error::NotFinite,
+            Error::ConversionFailed,
+            Error::NotRepresentable,
+            Error::UnsupportedSquareRoot { value: s() },
+            Error::NotRational,
+            Error::InvalidIdentifier { id: s() },
+            Error::ExpectedScalar,
+            Error::ExpectedMatrix,
+            Error::InvalidFunctionArgument { function: s() },
+            Error::ArgumentCount {
+                expected: 1,
+                given: 2,
+            },
+            Error::ExpectedColumnVector,
+            Error::ExpectedVector,
+            Error::EmptyRange,
+            Error::DifferentShapes,
+            Error::MultiplicationShapes {
+                left: s(),
+                right: s(),
+            },
+            Error::NonSquarePower,
+            Error::CalculationFailed,
+            Error::EmptyInverse,
+            Error::NotSquare,
+            Error::NotInvertible,
+            Error::NotConverged,
+            Error::NotSymmetric,
+            Error::NotPositiveDefinite,
+            Error::MatrixTooLarge { limit: 12 },
+            Error::IrrationalEigenvalues,
+            Error::DefectiveEigenvalue {
+                eigenvalue: s(),
+                algebraic: 2,
+                geometric: 1,
+            },
+            Error::IndexOutOfRange { index: 3, size: 2 },
+            Error::EmptyMatrix,
+            Error::RankOutOfRange { rank: 3, max: 2 },
+        ]
+    }
+
+    #[test]
+    fn test_errors_are_translated() {
+        for language in Language::available() {
+            let locale = Locale::new(language.clone());
+            for error in all_errors() {
+                assert!(
+                    locale.is_translated(error.template()),
+                    "{}: \"{}\" is not translated",
+                    language.code(),
+                    error.template()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_codes_are_unique() {
+        let mut codes: Vec<_> = all_errors().iter().map(Error::code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), all_errors().len());
+    }
+
+    #[test]
+    fn test_localized_error() {
+        let env = Environment::<i64>::new();
+        let error = parse_expression("2 * x", &env).unwrap_err();
+        let polish = Locale::new(Language::parse("pl").unwrap());
+
+        assert_eq!(
+            error.to_string(),
+            "Undefined identifier! Object \"x\" is unknown."
+        );
+        assert_eq!(
+            error.downcast_ref::<Error>(),
+            Some(&Error::UndefinedIdentifier { id: "x".into() })
+        );
+        assert_eq!(
+            polish.get_translated_error(&error),
+            "[E102] Niezdefiniowany identyfikator! Obiekt \"x\" jest nieznany."
+        );
+
+        let wrapped = Err::<(), _>(Error::DivisionByZero)
+            .context("Invalid cell")
+            .unwrap_err();
+        assert_eq!(
+            polish.get_translated_error(&wrapped),
+            "Invalid cell\n[E203] Dzielenie przez zero!"
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use crate::constants::EXERCISE_MAX_DETERMINANT;
+use crate::errors::Error;
+use crate::generators::Random;
+use crate::locale::Locale;
+use crate::matrices::Matrix;
+use crate::steps::Notation;
+use crate::traits::{LaTeXable, MatrixNumber};
+use anyhow::bail;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Options of the `exercises` subcommand.
+#[derive(Debug, Clone, Args)]
+pub struct ExerciseOptions {
+    /// Path of the generated LaTeX file.
+    #[arg(short, long, default_value = "exercises.tex")]
+    pub output: PathBuf,
+
+    /// Number of rows of the matrices (the echelon form exercises have one more column).
+    #[arg(long, default_value_t = 3)]
+    pub size: usize,
+
+    /// Number of echelon form exercises.
+    #[arg(long, default_value_t = 2)]
+    pub echelon: usize,
+
+    /// Rank of the matrices of the echelon form exercises, random if not given.
+    #[arg(long)]
+    pub rank: Option<usize>,
+
+    /// Number of determinant exercises.
+    #[arg(long, default_value_t = 2)]
+    pub determinant: usize,
+
+    /// Determinant of the matrices of the determinant exercises, random if not given.
+    #[arg(long, allow_negative_numbers = true)]
+    pub det: Option<i64>,
+
+    /// Number of inverse exercises.
+    #[arg(long, default_value_t = 2)]
+    pub inverse: usize,
+
+    /// Largest denominator of the entries of the inverses.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i64).range(1..))]
+    pub denominator: i64,
+}
+
+/// Single exercise along with the matrix it is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exercise<T: MatrixNumber> {
+    /// Reduce the matrix to the reduced row echelon form and find its rank.
+    Echelon(Matrix<T>),
+    Determinant(Matrix<T>),
+    Inverse(Matrix<T>),
+}
+
+impl<T: MatrixNumber> Exercise<T> {
+    pub fn matrix(&self) -> &Matrix<T> {
+        match self {
+            Exercise::Echelon(m) | Exercise::Determinant(m) | Exercise::Inverse(m) => m,
+        }
+    }
+
+    /// Untranslated statement of the exercise.
+    fn task(&self) -> &'static str {
+        match self {
+            Exercise::Echelon(_) => {
+                "Reduce the matrix to the reduced row echelon form and find its rank."
+            }
+            Exercise::Determinant(_) => "Calculate the determinant of the matrix.",
+            Exercise::Inverse(_) => "Calculate the inverse of the matrix.",
+        }
+    }
+
+    /// Returns the lines of the solution in LaTeX - the steps of the
+    /// elimination (if any) followed by the answer, e.g. `\det A = 2`.
+    pub fn solution_latex(&self, notation: &Notation) -> anyhow::Result<Vec<String>> {
+        let mut lines = vec![];
+        match self {
+            Exercise::Echelon(m) => {
+                let aftermath = m.echelon()?;
+                let rank = aftermath
+                    .result
+                    .get_data()
+                    .iter()
+                    .filter(|row| row.iter().any(|x| !x.is_zero()))
+                    .count();
+                lines.extend(aftermath.latex_steps_with(notation));
+                lines.push(format!(r"\operatorname{{rank}} A = {rank}"));
+            }
+            Exercise::Determinant(m) => {
+                lines.push(format!(r"\det A = {}", m.determinant()?.to_latex()));
+            }
+            Exercise::Inverse(m) => {
+                let aftermath = m.inverse()?;
+                lines.extend(aftermath.latex_steps_with(notation));
+                lines.push(format!("A^{{-1}} = {}", aftermath.result.to_latex()));
+            }
+        }
+        Ok(lines)
+    }
+}
+
+/// Randomly generated exercises, which can be reproduced from the seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExerciseSheet<T: MatrixNumber> {
+    pub seed: u64,
+    pub exercises: Vec<Exercise<T>>,
+}
+
+impl<T: MatrixNumber> ExerciseSheet<T> {
+    /// Generates integer matrices with "nice" solutions: the echelon form
+    /// exercises have the prescribed (or random) rank, the determinant
+    /// exercises the prescribed (or random) determinant and the inverses have
+    /// entries with denominators not larger than `options.denominator`.
+    pub fn generate(options: &ExerciseOptions, seed: u64) -> anyhow::Result<Self> {
+        let n = options.size;
+        if n == 0 {
+            bail!(Error::EmptyMatrix);
+        }
+        let mut random = Random::new(seed);
+        let mut exercises = vec![];
+        for _ in 0..options.echelon {
+            let rank = match options.rank {
+                Some(rank) => rank,
+                None => random.integer(1, n as i64) as usize,
+            };
+            let m = Matrix::random_with_rank((n, n + 1), rank, &mut random)?;
+            exercises.push(Exercise::Echelon(m));
+        }
+        for _ in 0..options.determinant {
+            let determinant = match options.det {
+                Some(determinant) => determinant,
+                None => random.nonzero(EXERCISE_MAX_DETERMINANT),
+            };
+            let m = Matrix::random_with_determinant(n, determinant, &mut random)?;
+            exercises.push(Exercise::Determinant(m));
+        }
+        for _ in 0..options.inverse {
+            // The denominators of the inverse divide the determinant.
+            let determinant = random.nonzero(options.denominator);
+            let m = Matrix::random_with_determinant(n, determinant, &mut random)?;
+            exercises.push(Exercise::Inverse(m));
+        }
+        Ok(Self { seed, exercises })
+    }
+
+    /// Returns the LaTeX document with the problem sheet followed by the
+    /// answer key on a new page.
+    pub fn to_latex(&self, locale: &Locale) -> anyhow::Result<String> {
+        let notation = Notation::new(locale, false);
+        let mut problems = vec![];
+        let mut answers = vec![];
+        for exercise in &self.exercises {
+            problems.push(format!(
+                "\\item {}\n\\[A = {}\\]",
+                locale.get_translated(exercise.task()),
+                exercise.matrix().to_latex()
+            ));
+            let lines: Vec<String> = exercise
+                .solution_latex(&notation)?
+                .iter()
+                .map(|line| format!("& {line}"))
+                .collect();
+            answers.push(format!(
+                "\\item\n\\begin{{align*}}\n{}\n\\end{{align*}}",
+                lines.join("\\\\\n")
+            ));
+        }
+
+        Ok(format!(
+            r"% Generated with the seed {seed}
+\documentclass{{article}}
+\usepackage[T1]{{fontenc}}
+\usepackage[utf8]{{inputenc}}
+\usepackage{{amsmath}}
+\begin{{document}}
+\section*{{{exercises}}}
+\begin{{enumerate}}
+{problems}
+\end{{enumerate}}
+\newpage
+\section*{{{answers}}}
+\begin{{enumerate}}
+{answer_key}
+\end{{enumerate}}
+\end{{document}}
+",
+            seed = self.seed,
+            exercises = locale.get_translated("Exercises"),
+            answers = locale.get_translated("Answers"),
+            problems = problems.join("\n"),
+            answer_key = answers.join("\n"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::Language;
+    use clap::Parser;
+    use num_rational::Rational64;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        options: ExerciseOptions,
+    }
+
+    fn parse_options(args: &[&str]) -> ExerciseOptions {
+        Cli::parse_from(std::iter::once("exercises").chain(args.iter().copied())).options
+    }
+
+    #[test]
+    fn test_generate() {
+        let options = parse_options(&["--size", "4", "--rank", "2", "--det", "-3"]);
+        let sheet = ExerciseSheet::<Rational64>::generate(&options, 42).unwrap();
+        assert_eq!(sheet, ExerciseSheet::generate(&options, 42).unwrap());
+        assert_ne!(sheet, ExerciseSheet::generate(&options, 43).unwrap());
+        assert_eq!(sheet.exercises.len(), 6);
+
+        let notation = Notation::default();
+        for exercise in &sheet.exercises {
+            let last = exercise.solution_latex(&notation).unwrap().pop().unwrap();
+            match exercise {
+                Exercise::Echelon(m) => {
+                    assert_eq!(m.get_shape(), (4, 5));
+                    assert_eq!(last, r"\operatorname{rank} A = 2");
+                }
+                Exercise::Determinant(_) => assert_eq!(last, r"\det A = -3"),
+                Exercise::Inverse(m) => {
+                    let inverse = m.inverse().unwrap().result;
+                    assert!(inverse.get_data().iter().flatten().all(|x| x.is_integer()));
+                }
+            }
+        }
+
+        assert!(
+            ExerciseSheet::<Rational64>::generate(&parse_options(&["--size", "0"]), 1).is_err()
+        );
+        assert!(ExerciseSheet::<Rational64>::generate(
+            &parse_options(&["--size", "2", "--rank", "3"]),
+            1
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_to_latex() {
+        let options = parse_options(&["--echelon", "1", "--determinant", "0", "--inverse", "1"]);
+        let sheet = ExerciseSheet::<Rational64>::generate(&options, 7).unwrap();
+        let polish = Locale::new(Language::parse("pl").unwrap());
+        let latex = sheet.to_latex(&polish).unwrap();
+
+        assert!(latex.starts_with("% Generated with the seed 7\n"));
+        assert!(latex.contains(r"\section*{Zadania}"));
+        assert!(latex.contains(r"\section*{Odpowiedzi}"));
+        assert_eq!(latex.matches(r"\item").count(), 4);
+        assert!(latex.contains(r"\xrightarrow"));
+        assert!(latex.contains("w_{1}"));
+        assert!(latex.contains("A^{-1} = "));
+        assert!(latex.trim_end().ends_with(r"\end{document}"));
+    }
+
+    #[test]
+    fn test_tasks_are_translated() {
+        let m = Matrix::<Rational64>::identity(1);
+        let exercises = [
+            Exercise::Echelon(m.clone()),
+            Exercise::Determinant(m.clone()),
+            Exercise::Inverse(m),
+        ];
+        for language in Language::available() {
+            let locale = Locale::new(language);
+            for exercise in &exercises {
+                assert!(locale.is_translated(exercise.task()));
+            }
+        }
+    }
+}
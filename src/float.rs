@@ -1,14 +1,27 @@
-use crate::constants::FLOAT_STRING_PRECISION;
+use crate::constants::{
+    FLOAT_ABSOLUTE_EPSILON, FLOAT_RELATIVE_EPSILON, FLOAT_STRING_PRECISION,
+    MAX_APPROXIMATION_DENOMINATOR, SCIENTIFIC_NOTATION_DIGITS,
+};
+use crate::errors::Error;
 use crate::locale::Locale;
-use crate::traits::{GuiDisplayable, LaTeXable};
+use crate::rationals::rationalize;
+use crate::traits::{
+    number_to_mathml, CheckedOpsError, CheckedSqrt, Exportable, FieldConversion, GuiDisplayable,
+    LaTeXable,
+};
 use eframe::epaint::{Color32, FontId, Shape, TextShape};
 use egui::{pos2, Context};
+use lazy_static::lazy_static;
+use num_rational::Rational64;
 use num_traits::{
     CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, FromPrimitive, Num, One, Signed, ToPrimitive,
     Zero,
 };
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
 use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
 use std::str::FromStr;
+use std::sync::RwLock;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Float64 {
@@ -23,9 +36,61 @@ impl Num for Float64 {
     }
 }
 
+/// Floats are compared with the tolerance set by [`Tolerance::set`].
 impl PartialEq for Float64 {
     fn eq(&self, other: &Self) -> bool {
-        <f64 as PartialEq>::eq(&self.value, &other.value)
+        Tolerance::current().equal(self.value, other.value)
+    }
+}
+
+lazy_static! {
+    static ref FLOAT_TOLERANCE: RwLock<Tolerance> = RwLock::new(Tolerance::default());
+}
+
+/// Tolerance of comparisons of floats, so rounding errors (e.g. `1e-17`
+/// left after elimination) do not matter. Two numbers are equal if they
+/// differ by at most `absolute` or by at most `relative` times the larger of
+/// their magnitudes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            absolute: FLOAT_ABSOLUTE_EPSILON,
+            relative: FLOAT_RELATIVE_EPSILON,
+        }
+    }
+}
+
+impl Tolerance {
+    /// Tolerance used by the comparisons of `Float64`.
+    pub fn current() -> Self {
+        *FLOAT_TOLERANCE
+            .read()
+            .expect("Float tolerance lock is poisoned!")
+    }
+
+    /// Changes the tolerance used by the comparisons of `Float64`.
+    pub fn set(tolerance: Self) {
+        *FLOAT_TOLERANCE
+            .write()
+            .expect("Float tolerance lock is poisoned!") = tolerance;
+    }
+
+    pub fn equal(&self, a: f64, b: f64) -> bool {
+        if a == b {
+            return true;
+        }
+        let difference = (a - b).abs();
+        difference <= self.absolute || difference <= self.relative * a.abs().max(b.abs())
+    }
+
+    pub fn is_zero(&self, a: f64) -> bool {
+        a.abs() <= self.absolute
     }
 }
 
@@ -41,13 +106,19 @@ impl From<f64> for Float64 {
     }
 }
 
+impl From<Rational64> for Float64 {
+    fn from(value: Rational64) -> Self {
+        (*value.numer() as f64 / *value.denom() as f64).into()
+    }
+}
+
 impl Zero for Float64 {
     fn zero() -> Self {
         <f64 as Zero>::zero().into()
     }
 
     fn is_zero(&self) -> bool {
-        <f64 as Zero>::is_zero(&self.value)
+        Tolerance::current().is_zero(self.value)
     }
 }
 
@@ -63,6 +134,10 @@ impl One for Float64 {
     fn one() -> Self {
         <f64 as One>::one().into()
     }
+
+    fn is_one(&self) -> bool {
+        Tolerance::current().equal(self.value, 1.)
+    }
 }
 
 impl Mul<Self> for Float64 {
@@ -97,30 +172,73 @@ impl Rem<Self> for Float64 {
     }
 }
 
+/// Returns `None` if the result of an operation is infinite or NaN.
+fn finite(value: f64) -> Option<Float64> {
+    value.is_finite().then(|| value.into())
+}
+
 impl CheckedAdd for Float64 {
     fn checked_add(&self, v: &Self) -> Option<Self> {
-        Some(self.value.add(v.value).into())
+        finite(self.value.add(v.value))
     }
 }
 
 impl CheckedSub for Float64 {
     fn checked_sub(&self, v: &Self) -> Option<Self> {
-        Some(self.value.sub(v.value).into())
+        finite(self.value.sub(v.value))
     }
 }
 
 impl CheckedMul for Float64 {
     fn checked_mul(&self, v: &Self) -> Option<Self> {
-        Some(self.value.mul(v.value).into())
+        finite(self.value.mul(v.value))
+    }
+}
+
+impl Float64 {
+    /// Checked division which tells the division by zero apart from other
+    /// non-finite results.
+    pub fn try_div(&self, v: &Self) -> Result<Self, Error> {
+        if v.value == 0. {
+            return Err(Error::DivisionByZero);
+        }
+        finite(self.value.div(v.value)).ok_or(Error::NotFinite)
     }
 }
 
 impl CheckedDiv for Float64 {
     fn checked_div(&self, v: &Self) -> Option<Self> {
-        if v.is_zero() {
-            return None;
+        self.try_div(v).ok()
+    }
+}
+
+/// `exact` replaces the value with the nearest simple fraction, which removes
+/// rounding errors, e.g. `0.30000000000000004` becomes `0.3`.
+impl FieldConversion for Float64 {
+    const IS_EXACT: bool = false;
+
+    fn exact(&self) -> Option<Self> {
+        rationalize(self.value, MAX_APPROXIMATION_DENOMINATOR).map(Float64::from)
+    }
+
+    fn from_rational(value: Rational64) -> Option<Self> {
+        Some(value.into())
+    }
+}
+
+/// Negative numbers within the tolerance of zero have the square root zero.
+impl CheckedSqrt for Float64 {
+    fn checked_sqrt(&self) -> Option<Self> {
+        match self.is_zero() {
+            true => Some(Float64::zero()),
+            false => finite(self.value.sqrt()),
         }
-        Some(self.value.div(v.value).into())
+    }
+}
+
+impl CheckedOpsError for Float64 {
+    fn checked_ops_error() -> Error {
+        Error::NotFinite
     }
 }
 
@@ -132,6 +250,10 @@ impl FromPrimitive for Float64 {
     fn from_u64(n: u64) -> Option<Self> {
         Some((n as f64).into())
     }
+
+    fn from_f64(n: f64) -> Option<Self> {
+        finite(n)
+    }
 }
 
 impl ToPrimitive for Float64 {
@@ -142,6 +264,10 @@ impl ToPrimitive for Float64 {
     fn to_u64(&self) -> Option<u64> {
         Some(self.value as u64)
     }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.value)
+    }
 }
 
 impl Signed for Float64 {
@@ -182,9 +308,9 @@ impl FromStr for Float64 {
     }
 }
 
-impl ToString for Float64 {
-    fn to_string(&self) -> String {
-        self.value.to_string()
+impl Display for Float64 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
     }
 }
 
@@ -194,6 +320,24 @@ impl LaTeXable for Float64 {
     }
 }
 
+impl Exportable for Float64 {
+    fn to_typst(&self) -> String {
+        FloatFormat::current().format_typst(self.value)
+    }
+
+    fn to_mathml(&self) -> String {
+        number_to_mathml(&self.to_plain())
+    }
+
+    fn to_plain(&self) -> String {
+        FloatFormat::current().format_plain(self.value)
+    }
+
+    fn to_data(&self) -> String {
+        self.value.to_string()
+    }
+}
+
 impl GuiDisplayable for Float64 {
     fn display_string(&self, locale: &Locale) -> String {
         self.value.display_string(locale)
@@ -206,7 +350,134 @@ impl GuiDisplayable for Float64 {
 
 impl LaTeXable for f64 {
     fn to_latex(&self) -> String {
-        trim_trailing_zeros_float_str(&format!("{:.*}", FLOAT_STRING_PRECISION, self))
+        FloatFormat::current().format_latex(*self)
+    }
+}
+
+lazy_static! {
+    static ref FLOAT_FORMAT: RwLock<FloatFormat> = RwLock::new(FloatFormat::default());
+}
+
+thread_local! {
+    static FLOAT_FORMAT_OVERRIDE: Cell<Option<FloatFormat>> = const { Cell::new(None) };
+}
+
+/// How floating point numbers are written - the number of fractional digits
+/// and the decimal separator. Very large and very small numbers are written
+/// in scientific notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatFormat {
+    pub precision: usize,
+    pub decimal_separator: char,
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        Self {
+            precision: FLOAT_STRING_PRECISION,
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl FloatFormat {
+    /// Format with the decimal separator of the locale.
+    pub fn for_locale(locale: &Locale, precision: usize) -> Self {
+        Self {
+            precision,
+            decimal_separator: locale
+                .get_translated("Decimal separator")
+                .chars()
+                .next()
+                .unwrap_or('.'),
+        }
+    }
+
+    /// Format used by the exports and the GUI.
+    pub fn current() -> Self {
+        FLOAT_FORMAT_OVERRIDE
+            .with(Cell::get)
+            .unwrap_or_else(|| *FLOAT_FORMAT.read().expect("Float format lock is poisoned!"))
+    }
+
+    /// Changes the format used by the exports and the GUI.
+    pub fn set(format: Self) {
+        *FLOAT_FORMAT
+            .write()
+            .expect("Float format lock is poisoned!") = format;
+    }
+
+    /// Calls `f` with numbers written in `format` instead of the global
+    /// format, without affecting other threads (e.g. tests running in
+    /// parallel).
+    #[cfg(test)]
+    pub fn scoped<R>(format: Self, f: impl FnOnce() -> R) -> R {
+        let previous = FLOAT_FORMAT_OVERRIDE.with(|cell| cell.replace(Some(format)));
+        let result = f();
+        FLOAT_FORMAT_OVERRIDE.with(|cell| cell.set(previous));
+        result
+    }
+
+    /// Splits the number into the (rounded) mantissa and the exponent, which
+    /// is `None` if scientific notation is not needed.
+    fn split(&self, value: f64) -> (String, Option<i32>) {
+        let magnitude = value.abs().log10().floor() as i32;
+        let scientific = value != 0.
+            && (magnitude >= SCIENTIFIC_NOTATION_DIGITS - 1
+                || magnitude < -(self.precision as i32));
+        if !scientific {
+            let fixed = trim_trailing_zeros_float_str(&format!("{:.*}", self.precision, value));
+            return (self.with_separator(&fixed), None);
+        }
+        let formatted = format!("{:.*e}", self.precision, value);
+        let (mantissa, exponent) = formatted.split_once('e').expect("Should contain exponent");
+        (
+            self.with_separator(&trim_trailing_zeros_float_str(mantissa)),
+            exponent.parse().ok(),
+        )
+    }
+
+    fn with_separator(&self, number: &str) -> String {
+        number.replace('.', &self.decimal_separator.to_string())
+    }
+
+    /// Plain text, e.g. `0.333` or `1.5e-7`.
+    pub fn format_plain(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        match self.split(value) {
+            (mantissa, Some(exponent)) => format!("{mantissa}e{exponent}"),
+            (mantissa, None) => mantissa,
+        }
+    }
+
+    /// LaTeX, e.g. `1.5 \cdot 10^{-7}`. A comma separator is wrapped in
+    /// braces, so LaTeX does not add a space after it.
+    pub fn format_latex(&self, value: f64) -> String {
+        if value.is_nan() {
+            return r"\mathrm{NaN}".to_string();
+        }
+        if value.is_infinite() {
+            return if value > 0. { r"\infty" } else { r"-\infty" }.to_string();
+        }
+        let (mantissa, exponent) = self.split(value);
+        let mantissa = mantissa.replace(',', "{,}");
+        match exponent {
+            Some(exponent) => format!(r"{mantissa} \cdot 10^{{{exponent}}}"),
+            None => mantissa,
+        }
+    }
+
+    /// Typst, e.g. `1.5 dot 10^(-7)`.
+    pub fn format_typst(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return self.format_plain(value);
+        }
+        match self.split(value) {
+            (mantissa, Some(exponent)) => format!("{mantissa} dot 10^({exponent})"),
+            (mantissa, None) => mantissa,
+        }
     }
 }
 
@@ -234,14 +505,15 @@ pub fn trim_trailing_zeros_float_str(s: &str) -> String {
 }
 
 impl GuiDisplayable for f64 {
-    fn display_string(&self, _locale: &Locale) -> String {
-        self.to_latex()
+    fn display_string(&self, locale: &Locale) -> String {
+        FloatFormat::for_locale(locale, FloatFormat::current().precision).format_plain(*self)
     }
 
     fn to_shape(&self, ctx: &Context, font_id: FontId, color: Color32) -> Shape {
+        let text = FloatFormat::current().format_plain(*self);
         let text_shape = TextShape::new(
             pos2(0., 0.),
-            ctx.fonts(|f| f.layout_no_wrap(self.to_latex(), font_id, color)),
+            ctx.fonts(|f| f.layout_no_wrap(text, font_id, color)),
         );
         Shape::Text(text_shape)
     }
@@ -250,7 +522,10 @@ impl GuiDisplayable for f64 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::environment::{Environment, Identifier, Type};
     use crate::locale::Language;
+    use crate::matrices::Matrix;
+    use crate::parser::parse_expression;
 
     #[test]
     fn test_zeros() {
@@ -282,6 +557,14 @@ mod test {
         assert_eq!(Float64::from_str("1.0").unwrap(), 1.0.into());
     }
 
+    #[test]
+    fn test_float64_primitive_conversions() {
+        assert_eq!(Float64::from_f64(0.25), Some(0.25.into()));
+        assert_eq!(Float64::from_f64(f64::NAN), None);
+        assert_eq!(Float64::from(-1.5).to_f64(), Some(-1.5));
+        assert_eq!(Float64::from(-1.5).to_i64(), Some(-1));
+    }
+
     #[test]
     fn test_float64_to_latex() {
         assert_eq!(Float64::from_str("10.0").unwrap().to_latex(), "10");
@@ -289,9 +572,17 @@ mod test {
         assert_eq!(Float64::from_str("1.0").unwrap().to_latex(), "1");
     }
 
+    #[test]
+    fn test_float64_export() {
+        let x = Float64::from(-0.12345);
+        assert_eq!(x.to_typst(), "-0.123");
+        assert_eq!(x.to_mathml(), "<mo>-</mo><mn>0.123</mn>");
+        assert_eq!(x.to_plain(), "-0.123");
+    }
+
     #[test]
     fn test_float64_display_string() {
-        let locale = Locale::new(Language::English);
+        let locale = Locale::new(Language::default());
         assert_eq!(
             Float64::from_str("10.0").unwrap().display_string(&locale),
             "10"
@@ -307,4 +598,110 @@ mod test {
             "1"
         );
     }
+
+    #[test]
+    fn test_float_format_locale() {
+        let polish = Locale::new(Language::parse("pl").unwrap());
+        let format = FloatFormat::for_locale(&polish, 2);
+
+        assert_eq!(format.decimal_separator, ',');
+        assert_eq!(format.format_plain(-1.0 / 3.0), "-0,33");
+        assert_eq!(format.format_latex(2.5), "2{,}5");
+        assert_eq!(format.format_plain(10.0), "10");
+        assert_eq!(
+            FloatFormat::for_locale(&Locale::new(Language::default()), 5).format_plain(1.0 / 3.0),
+            "0.33333"
+        );
+    }
+
+    #[test]
+    fn test_float_format_scientific() {
+        let format = FloatFormat::default();
+
+        assert_eq!(format.format_plain(123456.0), "123456");
+        assert_eq!(format.format_plain(1234567.0), "1.235e6");
+        assert_eq!(format.format_latex(-1234567.0), r"-1.235 \cdot 10^{6}");
+        assert_eq!(format.format_plain(0.001), "0.001");
+        assert_eq!(format.format_plain(0.00015), "1.5e-4");
+        assert_eq!(format.format_typst(0.00015), "1.5 dot 10^(-4)");
+        assert_eq!(format.format_plain(0.0), "0");
+        assert_eq!(format.format_latex(f64::INFINITY), r"\infty");
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let tolerance = Tolerance::default();
+
+        assert!(tolerance.equal(1., 1. + 1e-12));
+        assert!(tolerance.equal(1e8, 1e8 + 1e-3));
+        assert!(!tolerance.equal(1., 1.001));
+        assert!(!tolerance.equal(1e-11, 1e-11 + 1.));
+        assert!(tolerance.is_zero(1e-17));
+        assert!(!tolerance.is_zero(1e-3));
+
+        let strict = Tolerance {
+            absolute: 0.,
+            relative: 0.,
+        };
+        assert!(!strict.equal(1., 1. + 1e-12));
+        assert!(!strict.is_zero(1e-17));
+    }
+
+    #[test]
+    fn test_float64_tolerant_comparisons() {
+        let residue = Float64::from(0.1 + 0.2 - 0.3);
+
+        assert!(residue.is_zero());
+        assert!(Float64::from(0.1 + 0.2) == Float64::from(0.3));
+        assert!(Float64::from(3.0 * (1.0 / 3.0)).is_one());
+        assert_eq!(
+            Float64::one().checked_div(&residue).is_some(),
+            residue.value != 0.
+        );
+    }
+
+    #[test]
+    fn test_float64_checked_ops() {
+        let max = Float64::from(f64::MAX);
+        let two = Float64::from(2.);
+        let zero = Float64::zero();
+
+        assert_eq!(two.checked_add(&two), Some(4.0.into()));
+        assert_eq!(max.checked_add(&max), None);
+        assert_eq!(two.checked_sub(&two), Some(zero));
+        assert_eq!((-max).checked_sub(&max), None);
+        assert_eq!(two.checked_mul(&two), Some(4.0.into()));
+        assert_eq!(max.checked_mul(&two), None);
+        assert_eq!(two.checked_div(&two), Some(Float64::one()));
+        assert_eq!(two.checked_div(&zero), None);
+        assert_eq!(zero.checked_div(&zero), None);
+        assert_eq!(max.checked_div(&Float64::from(0.5)), None);
+        assert_eq!(two.try_div(&zero), Err(Error::DivisionByZero));
+        assert_eq!(two.try_div(&-zero), Err(Error::DivisionByZero));
+        assert_eq!(max.try_div(&Float64::from(0.5)), Err(Error::NotFinite));
+        assert_eq!(two.try_div(&two), Ok(Float64::one()));
+        assert_eq!(Float64::from(f64::NAN).checked_add(&two), None);
+        assert_eq!(Float64::from(f64::INFINITY).checked_sub(&two), None);
+    }
+
+    #[test]
+    fn test_float64_not_finite_error() {
+        let mut env = Environment::<Float64>::new();
+        env.insert(
+            Identifier::new("A".to_string()).unwrap(),
+            Type::Matrix(Matrix::new(vec![vec![1.0.into(), 1e200.into()]]).unwrap()),
+        );
+
+        let error = parse_expression("10^400", &env).unwrap_err();
+        assert_eq!(error.downcast_ref::<Error>(), Some(&Error::NotFinite));
+        assert_eq!(error.to_string(), "Result is not finite!");
+        assert!(parse_expression("A * 10^200", &env)
+            .unwrap_err()
+            .downcast_ref::<Error>()
+            .is_some_and(|error| *error == Error::NotFinite));
+        let error = parse_expression("1 / 0", &env).unwrap_err();
+        assert_eq!(error.downcast_ref::<Error>(), Some(&Error::DivisionByZero));
+        let error = parse_expression("10^307 / 0.01", &env).unwrap_err();
+        assert_eq!(error.downcast_ref::<Error>(), Some(&Error::NotFinite));
+    }
 }
@@ -0,0 +1,356 @@
+use crate::constants::RANDOM_INVERTIBLE_MAX_ENTRY;
+use crate::errors::Error;
+use crate::matrices::Matrix;
+use crate::traits::MatrixNumber;
+use anyhow::bail;
+use lazy_static::lazy_static;
+use num_traits::checked_pow;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+lazy_static! {
+    static ref RANDOM: Mutex<Random> = Mutex::new(Random::new(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64)
+    ));
+}
+
+/// Seedable pseudorandom number generator (SplitMix64), so the same seed
+/// always generates the same matrices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Reseeds the generator used by the builtin functions.
+    pub fn seed(seed: u64) {
+        *RANDOM.lock().expect("Random generator lock is poisoned!") = Self::new(seed);
+    }
+
+    /// Runs `f` with the generator used by the builtin functions.
+    pub fn with_global<R>(f: impl FnOnce(&mut Self) -> R) -> R {
+        f(&mut RANDOM.lock().expect("Random generator lock is poisoned!"))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed integer from `low` to `high`, both inclusive.
+    pub fn integer(&mut self, low: i64, high: i64) -> i64 {
+        let span = (high as i128 - low as i128 + 1) as u128;
+        (low as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    /// Uniformly distributed nonzero integer from `-max` to `max`.
+    pub fn nonzero(&mut self, max: i64) -> i64 {
+        let value = self.integer(1, max);
+        match self.integer(0, 1) {
+            0 => value,
+            _ => -value,
+        }
+    }
+
+    /// Uniformly distributed number from `[0, 1)`.
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// Randomly shuffles the values with the Fisher-Yates algorithm.
+    pub fn shuffle<V>(&mut self, values: &mut [V]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.integer(0, i as i64) as usize);
+        }
+    }
+}
+
+/// Entries of a row or a column vector.
+fn vector_entries<T: MatrixNumber>(v: &Matrix<T>) -> anyhow::Result<Vec<T>> {
+    match v.get_shape() {
+        (1, _) => Ok(v.get_data()[0].clone()),
+        (_, 1) => Ok(v.get_data().iter().map(|row| row[0].clone()).collect()),
+        _ => bail!(Error::ExpectedVector),
+    }
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns a matrix of uniformly distributed integers from `low` to
+    /// `high`, both inclusive.
+    pub fn random_integers(
+        shape: (usize, usize),
+        (low, high): (i64, i64),
+        random: &mut Random,
+    ) -> anyhow::Result<Self> {
+        if low > high {
+            bail!(Error::EmptyRange);
+        }
+        Self::random_with(shape, || T::from_i64(random.integer(low, high)))
+    }
+
+    /// Returns a matrix of uniformly distributed numbers from `[low, high)`.
+    pub fn random_uniform(
+        shape: (usize, usize),
+        (low, high): (f64, f64),
+        random: &mut Random,
+    ) -> anyhow::Result<Self> {
+        if low > high {
+            bail!(Error::EmptyRange);
+        }
+        Self::random_with(shape, || T::from_f64(low + (high - low) * random.unit()))
+    }
+
+    fn random_with(
+        (rows, cols): (usize, usize),
+        mut entry: impl FnMut() -> Option<T>,
+    ) -> anyhow::Result<Self> {
+        let data = (0..rows)
+            .map(|_| (0..cols).map(|_| entry()).collect())
+            .collect::<Option<Vec<Vec<T>>>>()
+            .ok_or(Error::ConversionFailed)?;
+        Ok(Matrix::new_unsafe(data))
+    }
+
+    /// Returns a random invertible integer matrix whose inverse has integer
+    /// entries as well - the product of unit lower and upper triangular
+    /// matrices with small entries and a permutation matrix, so its
+    /// determinant is `1` or `-1`.
+    pub fn random_invertible(n: usize, random: &mut Random) -> anyhow::Result<Self> {
+        let max = RANDOM_INVERTIBLE_MAX_ENTRY;
+        let triangular = |random: &mut Random, lower: bool| {
+            Self::random_integers((n, n), (-max, max), random).map(|m| {
+                Matrix::filled((n, n), |i, j| match (i == j, (i > j) == lower) {
+                    (true, _) => T::one(),
+                    (false, true) => m.get_data()[i][j].clone(),
+                    (false, false) => T::zero(),
+                })
+            })
+        };
+        let product = triangular(random, true)?.checked_mul(&triangular(random, false)?)?;
+        let mut rows = product.consume();
+        random.shuffle(&mut rows);
+        Ok(Matrix::new_unsafe(rows))
+    }
+
+    /// Returns a random integer matrix of the given rank - the product of
+    /// the first `rank` columns and the first `rank` rows of random
+    /// invertible matrices.
+    pub fn random_with_rank(
+        (rows, cols): (usize, usize),
+        rank: usize,
+        random: &mut Random,
+    ) -> anyhow::Result<Self> {
+        let max = rows.min(cols);
+        if rank > max {
+            bail!(Error::RankOutOfRange { rank, max });
+        }
+        if rank == 0 {
+            return Ok(Matrix::zeros((rows, cols)));
+        }
+        let left = Self::random_invertible(rows, random)?;
+        let right = Self::random_invertible(cols, random)?;
+        Matrix::filled((rows, rank), |i, j| left.get_data()[i][j].clone()).checked_mul(
+            &Matrix::filled((rank, cols), |i, j| right.get_data()[i][j].clone()),
+        )
+    }
+
+    /// Returns a random integer matrix with the given determinant. Its
+    /// adjugate has integer entries, so the denominators of the entries of
+    /// the inverse divide the determinant.
+    pub fn random_with_determinant(
+        n: usize,
+        determinant: i64,
+        random: &mut Random,
+    ) -> anyhow::Result<Self> {
+        if n == 0 {
+            bail!(Error::EmptyMatrix);
+        }
+        let determinant = T::from_i64(determinant).ok_or(Error::ConversionFailed)?;
+        let scale = Matrix::filled((n, n), |i, j| match (i == j, i == 0) {
+            (true, true) => determinant.clone(),
+            (true, false) => T::one(),
+            (false, _) => T::zero(),
+        });
+        let product = Self::random_invertible(n, random)?
+            .checked_mul(&scale)?
+            .checked_mul(&Self::random_invertible(n, random)?)?;
+        if product.determinant()? == determinant {
+            return Ok(product);
+        }
+        // The determinants of the random invertible matrices are `1` or `-1`,
+        // so negating a row fixes the sign.
+        let mut rows = product.consume();
+        rows[0] = rows[0].iter().map(|x| -x.clone()).collect();
+        Ok(Matrix::new_unsafe(rows))
+    }
+
+    /// Returns the Hilbert matrix with the entries `1 / (i + j - 1)`.
+    pub fn hilbert(n: usize) -> anyhow::Result<Self> {
+        let data = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| T::one().checked_div(&T::from_usize(i + j + 1)?))
+                    .collect()
+            })
+            .collect::<Option<Vec<Vec<T>>>>()
+            .ok_or_else(T::checked_ops_error)?;
+        Ok(Matrix::new_unsafe(data))
+    }
+
+    /// Returns the square Vandermonde matrix with the rows
+    /// `1, v_i, v_i^2, ...` of the entries of the vector.
+    pub fn vandermonde(v: &Self) -> anyhow::Result<Self> {
+        let entries = vector_entries(v)?;
+        let data = entries
+            .iter()
+            .map(|x| {
+                (0..entries.len())
+                    .map(|k| checked_pow(x.clone(), k))
+                    .collect()
+            })
+            .collect::<Option<Vec<Vec<T>>>>()
+            .ok_or_else(T::checked_ops_error)?;
+        Ok(Matrix::new_unsafe(data))
+    }
+
+    /// Returns the symmetric Toeplitz matrix with the first column given by
+    /// the vector, i.e. with the entries `v_{|i - j|}`.
+    pub fn toeplitz(v: &Self) -> anyhow::Result<Self> {
+        let entries = vector_entries(v)?;
+        let n = entries.len();
+        Ok(Matrix::filled((n, n), |i, j| {
+            entries[i.abs_diff(j)].clone()
+        }))
+    }
+
+    /// Returns the diagonal matrix with the entries of the vector on the
+    /// diagonal.
+    pub fn from_diagonal(v: &Self) -> anyhow::Result<Self> {
+        let entries = vector_entries(v)?;
+        let n = entries.len();
+        Ok(Matrix::filled((n, n), |i, j| match i == j {
+            true => entries[i].clone(),
+            false => T::zero(),
+        }))
+    }
+
+    /// Returns the column vector of the diagonal entries of a square matrix.
+    pub fn diagonal(&self) -> anyhow::Result<Self> {
+        let (rows, cols) = self.get_shape();
+        if rows != cols {
+            bail!(Error::NotSquare);
+        }
+        Ok(Matrix::filled((rows, 1), |i, _| {
+            self.get_data()[i][i].clone()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{im, ri, rm, rv};
+    use num_rational::Rational64;
+    use num_traits::Signed;
+
+    #[test]
+    fn test_random() {
+        let (mut first, mut second) = (Random::new(7), Random::new(7));
+        let a: Matrix<i64> = Matrix::random_integers((3, 4), (-5, 5), &mut first).unwrap();
+        let b = Matrix::random_integers((3, 4), (-5, 5), &mut second).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.get_shape(), (3, 4));
+        assert!(a.get_data().iter().flatten().all(|x| (-5..=5).contains(x)));
+        assert_ne!(
+            a,
+            Matrix::random_integers((3, 4), (-5, 5), &mut first).unwrap()
+        );
+
+        let uniform: Matrix<Rational64> =
+            Matrix::random_uniform((2, 2), (1., 2.), &mut first).unwrap();
+        assert!(uniform
+            .get_data()
+            .iter()
+            .flatten()
+            .all(|x| *x >= ri!(1) && *x < ri!(2)));
+        assert!(Matrix::<i64>::random_integers((1, 1), (2, 1), &mut first).is_err());
+    }
+
+    #[test]
+    fn test_random_invertible() {
+        let mut random = Random::new(2024);
+        for n in 1..6 {
+            let m: Matrix<Rational64> = Matrix::random_invertible(n, &mut random).unwrap();
+            assert_eq!(m.determinant().unwrap().abs(), ri!(1));
+            let inverse = m.inverse().unwrap().result;
+            assert!(inverse.get_data().iter().flatten().all(|x| x.is_integer()));
+        }
+    }
+
+    #[test]
+    fn test_prescribed_rank_and_determinant() {
+        let mut random = Random::new(50);
+        for rank in 0..4 {
+            let m: Matrix<Rational64> =
+                Matrix::random_with_rank((3, 4), rank, &mut random).unwrap();
+            let rows = m.echelon().unwrap().result.consume();
+            let nonzero = rows.iter().filter(|row| row.iter().any(|x| *x != ri!(0)));
+            assert_eq!(nonzero.count(), rank);
+        }
+        assert!(matches!(
+            Matrix::<i64>::random_with_rank((2, 3), 3, &mut random)
+                .unwrap_err()
+                .downcast::<Error>()
+                .unwrap(),
+            Error::RankOutOfRange { rank: 3, max: 2 }
+        ));
+
+        for determinant in [-3, 0, 1, 4] {
+            let m: Matrix<Rational64> =
+                Matrix::random_with_determinant(3, determinant, &mut random).unwrap();
+            assert_eq!(m.determinant().unwrap(), ri!(determinant));
+            assert!(m.get_data().iter().flatten().all(|x| x.is_integer()));
+        }
+        let inverse = Matrix::<Rational64>::random_with_determinant(4, 2, &mut random)
+            .unwrap()
+            .inverse()
+            .unwrap()
+            .result;
+        assert!(inverse.get_data().iter().flatten().all(|x| *x.denom() <= 2));
+        assert!(Matrix::<i64>::random_with_determinant(0, 1, &mut random).is_err());
+    }
+
+    #[test]
+    fn test_structured_matrices() {
+        let third = Rational64::new(1, 3);
+        assert_eq!(
+            Matrix::hilbert(2).unwrap(),
+            Matrix::new(vec![
+                vec![ri!(1), Rational64::new(1, 2)],
+                vec![Rational64::new(1, 2), third]
+            ])
+            .unwrap()
+        );
+        assert_eq!(
+            Matrix::vandermonde(&im![1, 2, 3]).unwrap(),
+            im![1, 1, 1; 1, 2, 4; 1, 3, 9]
+        );
+        assert_eq!(
+            Matrix::toeplitz(&im![1; 2; 3]).unwrap(),
+            im![1, 2, 3; 2, 1, 2; 3, 2, 1]
+        );
+        assert_eq!(Matrix::from_diagonal(&rm![1, 2]).unwrap(), rm![1, 0; 0, 2]);
+        assert_eq!(im![1, 2; 3, 4].diagonal().unwrap(), im![1; 4]);
+        assert!(Matrix::vandermonde(&im![1, 2; 3, 4]).is_err());
+        assert!(im![1, 2].diagonal().is_err());
+    }
+}
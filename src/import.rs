@@ -0,0 +1,211 @@
+use crate::environment::{Environment, Type};
+use crate::latex_reader::{is_latex_matrix, parse_latex_matrix, read_latex_cells, LaTeXCells};
+use crate::matrices::Matrix;
+use crate::parser::parse_expression;
+use crate::traits::MatrixNumber;
+use anyhow::{bail, Context};
+
+/// Splits a textual representation of a matrix into its cells.
+/// Supported layouts are:
+/// * LaTeX matrices and bodies of their environments (`1 & 2 \\ 3 & 4`),
+/// * tab separated values,
+/// * comma separated values,
+/// * whitespace separated values.
+///
+/// In the last three layouts every non-empty line is a single row.
+/// Cells are not validated in any way, but all rows must have the same length.
+pub fn split_cells(raw: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    Ok(split_cells_with_separator(raw)?.0)
+}
+
+/// Same as [`split_cells`], but also returns the separator of LaTeX `array`
+/// environments (other layouts have none).
+pub fn split_cells_with_separator(raw: &str) -> anyhow::Result<(Vec<Vec<String>>, Option<usize>)> {
+    let (rows, separator): (Vec<Vec<String>>, _) = if is_latex_matrix(raw) {
+        let LaTeXCells { cells, separator } = read_latex_cells(raw)?;
+        (cells, separator)
+    } else {
+        let lines: Vec<&str> = raw
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let rows = if lines.iter().any(|line| line.contains('\t')) {
+            lines
+                .iter()
+                .map(|line| line.split('\t').map(clean_cell).collect())
+                .collect()
+        } else if lines.iter().any(|line| line.contains(',')) {
+            lines
+                .iter()
+                .map(|line| line.split(',').map(clean_cell).collect())
+                .collect()
+        } else {
+            lines
+                .iter()
+                .map(|line| line.split_whitespace().map(clean_cell).collect())
+                .collect()
+        };
+        (rows, None)
+    };
+
+    if rows.is_empty() {
+        bail!("There is no matrix to import!");
+    }
+    let cols = rows[0].len();
+    if let Some((i, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != cols) {
+        bail!(
+            "Row {} has {} cells, but the first one has {}!",
+            i + 1,
+            row.len(),
+            cols
+        );
+    }
+    if separator.is_some_and(|s| s >= cols) {
+        bail!("The separator does not match the number of columns!");
+    }
+    Ok((rows, separator))
+}
+
+/// Trims whitespace and surrounding quotes (used by some CSV writers).
+fn clean_cell(cell: &str) -> String {
+    let cell = cell.trim();
+    cell.strip_prefix('"')
+        .and_then(|c| c.strip_suffix('"'))
+        .unwrap_or(cell)
+        .trim()
+        .to_string()
+}
+
+/// Parses a textual representation of a matrix (see `split_cells` for the
+/// supported layouts). Every cell is parsed as an expression in the given
+/// environment and has to evaluate to a scalar. The separator of LaTeX
+/// `array` environments is restored.
+pub fn parse_matrix<T: MatrixNumber>(raw: &str, env: &Environment<T>) -> anyhow::Result<Matrix<T>> {
+    if is_latex_matrix(raw) {
+        return parse_latex_matrix(raw, env);
+    }
+    Matrix::new(parse_cells(&split_cells(raw)?, env)?)
+}
+
+/// Evaluates every cell as an expression in the given environment.
+pub fn parse_cells<T: MatrixNumber>(
+    cells: &[Vec<String>],
+    env: &Environment<T>,
+) -> anyhow::Result<Vec<Vec<T>>> {
+    cells
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(j, cell)| {
+                    parse_expression(cell, env)
+                        .and_then(Type::into_scalar)
+                        .with_context(|| {
+                            format!("Invalid cell in row {}, column {}!", i + 1, j + 1)
+                        })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Reads a matrix from a text file (e.g. CSV, TSV or LaTeX).
+pub fn load_matrix<T: MatrixNumber>(path: &str, env: &Environment<T>) -> anyhow::Result<Matrix<T>> {
+    let raw =
+        std::fs::read_to_string(path).with_context(|| format!("Cannot read file {path:?}!"))?;
+    parse_matrix(&raw, env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::FLOAT_STRING_PRECISION;
+    use crate::float::{Float64, FloatFormat};
+    use crate::locale::{Language, Locale};
+    use crate::traits::{ExportFormat, Exportable};
+    use crate::{fm, ri, rm, rv};
+    use num_rational::Rational64;
+    use num_traits::ToPrimitive;
+
+    #[test]
+    fn test_split_layouts() {
+        let expected = vec![vec!["1", "-2", "3/4"], vec!["5", "6", "7"]];
+
+        assert_eq!(split_cells("1,-2,3/4\n5,6,7\n").unwrap(), expected);
+        assert_eq!(split_cells("1\t-2\t3/4\r\n5\t6\t7").unwrap(), expected);
+        assert_eq!(split_cells("  1 -2  3/4\n\n5 6 7").unwrap(), expected);
+        assert_eq!(
+            split_cells(r"1 & -2 & 3/4 \\ 5 & 6 & 7 \\").unwrap(),
+            expected
+        );
+        assert_eq!(
+            split_cells("\"1\",\"-2\",\"3/4\"\n5,6,7").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_split_separator() {
+        let (cells, separator) = split_cells_with_separator(
+            r"\left[\begin{array}{cc|c}1 & 2 & 3\\4 & 5 & 6\end{array}\right]",
+        )
+        .unwrap();
+        assert_eq!(cells, vec![vec!["1", "2", "3"], vec!["4", "5", "6"]]);
+        assert_eq!(separator, Some(2));
+        assert_eq!(split_cells_with_separator("1,2\n3,4").unwrap().1, None);
+        assert!(split_cells_with_separator(r"\begin{array}{c|c}1\end{array}").is_err());
+    }
+
+    #[test]
+    fn test_split_invalid() {
+        assert!(split_cells("").is_err());
+        assert!(split_cells(" \n\t\n").is_err());
+        assert!(split_cells("1 2\n3").is_err());
+    }
+
+    #[test]
+    fn test_parse_matrix() {
+        let env = Environment::new();
+
+        assert_eq!(
+            parse_matrix("1, 2/4, 0.25\n-1, 2^3, (1+2)*3", &env).unwrap(),
+            Matrix::new(vec![
+                vec![ri!(1), Rational64::new(1, 2), Rational64::new(1, 4)],
+                rv![-1, 8, 9],
+            ])
+            .unwrap()
+        );
+        assert_eq!(parse_matrix("1\t0\n0\t1", &env).unwrap(), rm![1, 0; 0, 1]);
+    }
+
+    #[test]
+    fn test_parse_matrix_invalid_cell() {
+        let env = Environment::<Rational64>::new();
+
+        let error = parse_matrix("1, 2\n3, x", &env).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid cell in row 2, column 2!");
+        assert!(parse_matrix("1, identity(2)", &env).is_err());
+    }
+
+    #[test]
+    fn test_float_csv_round_trip() {
+        let env = Environment::<Float64>::new();
+        let m = fm![1.5, 2.; -1234567.891, 1. / 3.];
+
+        let polish = Locale::new(Language::parse("pl").unwrap());
+        let polish_format = FloatFormat::for_locale(&polish, FLOAT_STRING_PRECISION);
+        let csv = FloatFormat::scoped(polish_format, || m.export(ExportFormat::Csv));
+        let markdown = FloatFormat::scoped(polish_format, || m.export(ExportFormat::Markdown));
+        assert!(markdown.contains("1,5"));
+
+        assert_eq!(csv.lines().next().unwrap(), "1.5,2");
+        let imported = parse_matrix(&csv, &env).unwrap();
+        assert_eq!(
+            imported.get_data()[1][1].to_f64().unwrap().to_bits(),
+            (1. / 3_f64).to_bits()
+        );
+        assert_eq!(imported, m);
+    }
+}
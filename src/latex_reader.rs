@@ -0,0 +1,495 @@
+use crate::environment::{Environment, Type};
+use crate::import::parse_cells;
+use crate::matrices::Matrix;
+use crate::parser::parse_expression;
+use crate::traits::MatrixNumber;
+use anyhow::{bail, Context};
+use num_rational::Ratio;
+
+/// Matrix environments that can be read.
+const MATRIX_ENVIRONMENTS: [&str; 7] = [
+    "array", "matrix", "bmatrix", "Bmatrix", "pmatrix", "vmatrix", "Vmatrix",
+];
+
+/// Cells of a matrix read from LaTeX, already translated to the syntax of
+/// the shell expressions, along with the position of the vertical separator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaTeXCells {
+    pub cells: Vec<Vec<String>>,
+    pub separator: Option<usize>,
+}
+
+/// Checks whether the text looks like a LaTeX matrix (or its body).
+pub fn is_latex_matrix(raw: &str) -> bool {
+    raw.contains(r"\begin{") || raw.contains('&') || raw.contains(r"\\")
+}
+
+/// Reads the cells of a matrix written in LaTeX. Accepts whole environments
+/// like `\begin{bmatrix}...\end{bmatrix}` or
+/// `\left[\begin{array}{cc|c}...\end{array}\right]` (in which case the
+/// separator is restored), as well as bare environment bodies like
+/// `1 & 2 \\ 3 & 4`.
+pub fn read_latex_cells(raw: &str) -> anyhow::Result<LaTeXCells> {
+    let raw = strip_math_delimiters(raw.trim());
+    let (body, separator) = match raw.find(r"\begin{") {
+        Some(begin) => {
+            if !strip_left_right(&raw[..begin]).is_empty() {
+                bail!("Unexpected LaTeX before \\begin!");
+            }
+            read_environment(&raw[begin..])?
+        }
+        None => (raw, None),
+    };
+
+    let cells = body
+        .split(r"\\")
+        .map(|row| row.replace(r"\hline", ""))
+        .filter(|row| !row.trim().is_empty())
+        .map(|row| {
+            row.split('&')
+                .map(latex_to_expression)
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(LaTeXCells { cells, separator })
+}
+
+/// Splits `\begin{env}{spec}body\end{env}` (possibly surrounded with
+/// `\left[` and `\right]`) into the body and the separator position.
+fn read_environment(raw: &str) -> anyhow::Result<(&str, Option<usize>)> {
+    let rest = raw.strip_prefix(r"\begin").context("Expected \\begin!")?;
+    let (name, mut rest) = read_argument(rest)?;
+    if !MATRIX_ENVIRONMENTS.contains(&name) {
+        bail!("Unsupported LaTeX environment \"{name}\"!");
+    }
+
+    let mut separator = None;
+    if name == "array" {
+        let (spec, after_spec) = read_argument(rest)?;
+        separator = read_column_spec(spec)?;
+        rest = after_spec;
+    }
+
+    let end = format!(r"\end{{{name}}}");
+    let end_index = rest.find(&end).with_context(|| format!("Missing {end}!"))?;
+    if !strip_left_right(&rest[end_index + end.len()..]).is_empty() {
+        bail!("Unexpected LaTeX after {end}!");
+    }
+    Ok((&rest[..end_index], separator))
+}
+
+/// Returns the number of columns preceding the first vertical line of an
+/// `array` column specification (e.g. `cc|c` gives `Some(2)`). Lines at the
+/// borders are ignored.
+fn read_column_spec(spec: &str) -> anyhow::Result<Option<usize>> {
+    let mut columns = 0;
+    let mut separator = None;
+    for c in spec.chars().filter(|c| !c.is_whitespace()) {
+        match c {
+            'l' | 'c' | 'r' => columns += 1,
+            '|' if separator.is_none() && columns > 0 => separator = Some(columns),
+            '|' => {}
+            _ => bail!("Unsupported array column specification \"{spec}\"!"),
+        }
+    }
+    Ok(separator.filter(|&s| s < columns))
+}
+
+/// Removes `$`, `$$`, `\[` and `\]` surrounding a formula.
+fn strip_math_delimiters(raw: &str) -> &str {
+    let raw = raw
+        .strip_prefix(r"\[")
+        .and_then(|r| r.strip_suffix(r"\]"))
+        .unwrap_or(raw);
+    raw.trim_matches('$').trim()
+}
+
+/// Removes `\left` and `\right` delimiters (e.g. `\left[`) and whitespace.
+fn strip_left_right(raw: &str) -> String {
+    let mut raw = raw.trim();
+    let mut result = String::new();
+    while !raw.is_empty() {
+        if let Some(rest) = raw
+            .strip_prefix(r"\left")
+            .or_else(|| raw.strip_prefix(r"\right"))
+        {
+            let rest = rest.trim_start();
+            let delimiter_len = if rest.starts_with('\\') {
+                rest.chars().take(2).map(char::len_utf8).sum()
+            } else {
+                rest.chars().next().map_or(0, char::len_utf8)
+            };
+            raw = rest[delimiter_len..].trim_start();
+        } else {
+            let c = raw.chars().next().unwrap();
+            result.push(c);
+            raw = raw[c.len_utf8()..].trim_start();
+        }
+    }
+    result
+}
+
+/// Reads a single macro argument - either a group in braces or a single
+/// character. Returns the argument and the rest of the text.
+fn read_argument(raw: &str) -> anyhow::Result<(&str, &str)> {
+    let raw = raw.trim_start();
+    if let Some(rest) = raw.strip_prefix('{') {
+        let mut depth = 0;
+        for (i, c) in rest.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok((&rest[..i], &rest[i + 1..])),
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        bail!("Mismatched braces!")
+    } else {
+        let c = raw.chars().next().context("Missing argument!")?;
+        Ok(raw.split_at(c.len_utf8()))
+    }
+}
+
+/// Reads a command name (letters following the backslash, or a single
+/// non-letter character). Returns the name and the rest of the text.
+fn read_command(raw: &str) -> anyhow::Result<(&str, &str)> {
+    let rest = &raw[1..];
+    let len = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    if len > 0 {
+        Ok(rest.split_at(len))
+    } else {
+        let c = rest.chars().next().context("Unfinished LaTeX command!")?;
+        Ok(rest.split_at(c.len_utf8()))
+    }
+}
+
+/// Splits the unsigned decimal number (e.g. `12` or `0.5`) at the end of a
+/// translated expression off the rest of it.
+fn split_trailing_number(expression: &str) -> (&str, &str) {
+    let start = expression
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+        .len();
+    expression.split_at(start)
+}
+
+/// Exact value of a decimal followed by repeating digits, e.g. `7/6` for
+/// `1.1` and `6`. `None` if the digits are invalid or too long.
+fn repeating_decimal(decimal: &str, repeating: &str) -> Option<Ratio<i128>> {
+    let (integer, fixed) = decimal.split_once('.').unwrap_or((decimal, ""));
+    let all_digits =
+        |digits: &str| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+    if !all_digits(integer) || !all_digits(repeating) {
+        return None;
+    }
+    let digits: i128 = format!("{integer}{fixed}").parse().ok()?;
+    let period = 10_i128.checked_pow(repeating.len() as u32)? - 1;
+    let denominator = 10_i128
+        .checked_pow(fixed.len() as u32)?
+        .checked_mul(period)?;
+    let numerator = digits
+        .checked_mul(period)?
+        .checked_add(repeating.parse().ok()?)?;
+    Some(Ratio::new(numerator, denominator))
+}
+
+/// Translates a LaTeX formula to the syntax of shell expressions, e.g.
+/// `-\frac{1}{2}` becomes `-(1)/(2)` and `2\sqrt{3}` becomes `2*sqrt(3)`.
+pub fn latex_to_expression(raw: &str) -> anyhow::Result<String> {
+    let mut raw = raw.trim();
+    let mut result = String::new();
+    while let Some(c) = raw.chars().next() {
+        if c == '\\' {
+            let (command, rest) = read_command(raw)?;
+            raw = rest;
+            match command {
+                "frac" | "dfrac" | "tfrac" => {
+                    let (numerator, rest) = read_argument(raw)?;
+                    let (denominator, rest) = read_argument(rest)?;
+                    raw = rest;
+                    let fraction = format!(
+                        "({})/({})",
+                        latex_to_expression(numerator)?,
+                        latex_to_expression(denominator)?
+                    );
+                    // An integer right before the fraction makes a mixed
+                    // number, e.g. `2\tfrac{1}{3}`.
+                    let (before, integer) = split_trailing_number(&result);
+                    if !integer.is_empty() && !integer.contains('.') {
+                        result = format!("{before}({integer} + {fraction})");
+                    } else {
+                        result.push_str(&fraction);
+                    }
+                }
+                "overline" => {
+                    // Repeating digits of a decimal, e.g. `0.1\overline{6}`.
+                    let (repeating, rest) = read_argument(raw)?;
+                    raw = rest;
+                    let (before, decimal) = split_trailing_number(&result);
+                    let fraction = repeating_decimal(decimal, repeating)
+                        .context("Unsupported repeating decimal!")?;
+                    result = format!("{before}({}/{})", fraction.numer(), fraction.denom());
+                }
+                "left" | "right" => {
+                    let rest = raw.trim_start();
+                    let (delimiter, rest) = if rest.starts_with('\\') {
+                        read_command(rest)?
+                    } else {
+                        read_argument(rest)?
+                    };
+                    raw = rest;
+                    match delimiter {
+                        "(" | "[" => result.push('('),
+                        ")" | "]" => result.push(')'),
+                        "." => {}
+                        _ => bail!("Unsupported delimiter \"{delimiter}\"!"),
+                    }
+                }
+                "sqrt" => {
+                    if raw.trim_start().starts_with('[') {
+                        bail!("Unsupported LaTeX command \\sqrt[n]!");
+                    }
+                    let (radicand, rest) = read_argument(raw)?;
+                    raw = rest;
+                    // A coefficient is written right before the root, e.g. `2\sqrt{3}`.
+                    if result.ends_with(|c: char| c.is_ascii_alphanumeric() || c == ')') {
+                        result.push('*');
+                    }
+                    result.push_str(&format!("sqrt({})", latex_to_expression(radicand)?));
+                }
+                "cdot" | "times" => result.push('*'),
+                "," | ";" | ":" | "!" | " " => {}
+                _ => bail!("Unsupported LaTeX command \\{command}!"),
+            }
+        } else if let Some(rest) = raw.strip_prefix("{,}") {
+            // Decimal comma, e.g. `0{,}5`.
+            raw = rest;
+            result.push('.');
+        } else {
+            raw = &raw[c.len_utf8()..];
+            match c {
+                '{' => result.push('('),
+                '}' => result.push(')'),
+                _ => result.push(c),
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Parses a matrix written in LaTeX (see `read_latex_cells`), every cell
+/// is evaluated in the given environment.
+pub fn parse_latex_matrix<T: MatrixNumber>(
+    raw: &str,
+    env: &Environment<T>,
+) -> anyhow::Result<Matrix<T>> {
+    let LaTeXCells { cells, separator } = read_latex_cells(raw)?;
+    let matrix = Matrix::new(parse_cells(&cells, env)?)?;
+    if separator.is_some_and(|s| s >= matrix.get_shape().1) {
+        bail!("The separator does not match the number of columns!");
+    }
+    Ok(matrix.with_separator(separator))
+}
+
+/// Parses a value written in LaTeX - a matrix if it looks like one
+/// (see `is_latex_matrix`), a scalar otherwise.
+pub fn parse_latex<T: MatrixNumber>(raw: &str, env: &Environment<T>) -> anyhow::Result<Type<T>> {
+    if is_latex_matrix(raw) {
+        Ok(Type::Matrix(parse_latex_matrix(raw, env)?))
+    } else {
+        parse_expression(
+            &latex_to_expression(strip_math_delimiters(raw.trim()))?,
+            env,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::float::FloatFormat;
+    use crate::quadratic::Quadratic;
+    use crate::rationals::RationalDisplay;
+    use crate::traits::LaTeXable;
+    use crate::{ri, rm, rv};
+    use num_rational::Rational64;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_latex_to_expression() {
+        assert_eq!(latex_to_expression(r"-\frac{1}{2}").unwrap(), "-(1)/(2)");
+        assert_eq!(
+            latex_to_expression(r"\left(-\frac{3}{4}\right) \cdot 2^{10}").unwrap(),
+            "(-(3)/(4)) * 2^(10)"
+        );
+        assert_eq!(latex_to_expression(r"\dfrac12").unwrap(), "(1)/(2)");
+        assert_eq!(latex_to_expression(r"\sqrt{2}").unwrap(), "sqrt(2)");
+        assert_eq!(
+            latex_to_expression(r"1 - \frac{3\sqrt{2}}{2}").unwrap(),
+            "1 - (3*sqrt(2))/(2)"
+        );
+        assert!(latex_to_expression(r"\sqrt[3]{2}").is_err());
+        assert_eq!(
+            latex_to_expression(r"-2\tfrac{1}{3}").unwrap(),
+            "-(2 + (1)/(3))"
+        );
+        assert_eq!(
+            latex_to_expression(r"-1{,}1\overline{6}").unwrap(),
+            "-(7/6)"
+        );
+        assert!(latex_to_expression(r"\overline{3}").is_err());
+        assert!(latex_to_expression(r"0.\overline{x}").is_err());
+        assert!(latex_to_expression(r"\frac{1}{2").is_err());
+    }
+
+    #[test]
+    fn test_read_bmatrix() {
+        let cells = read_latex_cells(r"\begin{bmatrix} 1 & -\frac{1}{2} \\ 3 & 4 \end{bmatrix}");
+        assert_eq!(
+            cells.unwrap(),
+            LaTeXCells {
+                cells: vec![
+                    vec!["1".into(), "-(1)/(2)".into()],
+                    vec!["3".into(), "4".into()]
+                ],
+                separator: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_array_separator() {
+        let env = Environment::new();
+        let m = parse_latex_matrix(
+            r"$\left[\begin{array}{cc|c}1 & 2 & 3\\4 & 5 & 6\\\hline\end{array}\right]$",
+            &env,
+        )
+        .unwrap();
+
+        assert_eq!(m, rm![1, 2, 3; 4, 5, 6]);
+        assert_eq!(m.get_separator(), Some(2));
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let env = Environment::<Rational64>::new();
+        let parse = |raw| parse_latex_matrix(raw, &env);
+
+        assert!(parse(r"\begin{bmatrix}1 & 2\end{pmatrix}").is_err());
+        assert!(parse(r"\begin{tabular}{cc}1 & 2\end{tabular}").is_err());
+        assert!(parse(r"\begin{bmatrix}1 & 2 \\ 3\end{bmatrix}").is_err());
+        assert!(parse(r"\begin{bmatrix}1 & 2\end{bmatrix} + 1").is_err());
+        assert!(parse(r"\begin{array}{c|c|}1 & 2\\3 & 4\end{array}").is_ok());
+    }
+
+    #[test]
+    fn test_parse_latex_scalar() {
+        let env = Environment::new();
+        assert_eq!(
+            parse_latex(r"-\frac{5}{10}", &env).unwrap(),
+            Type::Scalar(Rational64::new(-1, 2))
+        );
+        assert_eq!(
+            parse_latex(r"\begin{pmatrix}1\\2\end{pmatrix}", &env).unwrap(),
+            Type::Matrix(Matrix::new(vec![rv![1], rv![2]]).unwrap())
+        );
+        assert_eq!(parse_latex("$7$", &env).unwrap(), Type::Scalar(ri!(7)));
+    }
+
+    #[test]
+    fn test_parse_latex_quadratic() {
+        let env = Environment::<Quadratic>::new();
+        let q = |raw| parse_expression(raw, &env).unwrap().into_scalar().unwrap();
+        let m = Matrix::new(vec![vec![q("sqrt(2)/2"), q("1 - 2*sqrt(3)")]]).unwrap();
+        assert_eq!(
+            m.to_latex(),
+            r"\left[\begin{array}{cc}\frac{\sqrt{2}}{2} & 1 - 2\sqrt{3}\end{array}\right]"
+        );
+        assert_eq!(parse_latex_matrix(&m.to_latex(), &env).unwrap(), m);
+    }
+
+    #[test]
+    fn test_parse_latex_display_modes() {
+        let env = Environment::new();
+        let m = Matrix::new(vec![
+            vec![Rational64::new(7, 3), Rational64::new(-7, 6)],
+            vec![Rational64::new(1, 7), Rational64::new(-3, 8)],
+        ])
+        .unwrap();
+        for display in [RationalDisplay::Mixed, RationalDisplay::Repeating] {
+            let latex = RationalDisplay::scoped(Some(display), || m.to_latex());
+            assert_eq!(parse_latex_matrix(&latex, &env).unwrap(), m, "{latex}");
+        }
+        let comma = FloatFormat {
+            decimal_separator: ',',
+            ..FloatFormat::default()
+        };
+        let latex = RationalDisplay::Repeating.format_latex(&Rational64::new(-7, 6), comma);
+        assert_eq!(
+            parse_latex(&latex, &env).unwrap(),
+            Type::Scalar(Rational64::new(-7, 6))
+        );
+        let latex = RationalDisplay::scoped(Some(RationalDisplay::Fixed), || m.to_latex());
+        assert_eq!(parse_latex_matrix(&latex, &env).unwrap(), m);
+    }
+
+    fn rational() -> impl Strategy<Value = Rational64> {
+        (-1000_i64..1000, 1_i64..100).prop_map(|(n, d)| Rational64::new(n, d))
+    }
+
+    fn rational_matrix() -> impl Strategy<Value = Matrix<Rational64>> {
+        (1_usize..6, 1_usize..6)
+            .prop_flat_map(|(rows, cols)| {
+                (
+                    prop::collection::vec(prop::collection::vec(rational(), cols), rows),
+                    prop::option::of(0..cols),
+                )
+            })
+            .prop_map(|(data, separator)| {
+                Matrix::new(data)
+                    .unwrap()
+                    .with_separator(separator.filter(|&s| s > 0))
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_matrix_round_trip(m in rational_matrix()) {
+            let env = Environment::new();
+            let read = parse_latex_matrix(&m.to_latex(), &env).unwrap();
+
+            prop_assert_eq!(&read, &m);
+            prop_assert_eq!(read.get_separator(), m.get_separator());
+            prop_assert_eq!(read.to_latex(), m.to_latex());
+        }
+
+        #[test]
+        fn test_scalar_round_trip(r in rational()) {
+            let env = Environment::new();
+
+            prop_assert_eq!(parse_latex(&r.to_latex(), &env).unwrap(), Type::Scalar(r));
+            prop_assert_eq!(parse_latex(&r.to_latex_single(), &env).unwrap(), Type::Scalar(r));
+            for display in [RationalDisplay::Mixed, RationalDisplay::Repeating] {
+                let (latex, single) =
+                    RationalDisplay::scoped(Some(display), || (r.to_latex(), r.to_latex_single()));
+                prop_assert_eq!(parse_latex(&latex, &env).unwrap(), Type::Scalar(r));
+                prop_assert_eq!(parse_latex(&single, &env).unwrap(), Type::Scalar(r));
+            }
+        }
+
+        #[test]
+        fn test_quadratic_round_trip(
+            rational in rational(),
+            coefficient in rational(),
+            radicand in prop::sample::select(vec![2_i64, 3, 5, 6, 7, 12]),
+        ) {
+            let env = Environment::new();
+            let q = Quadratic::new(rational, coefficient, radicand).unwrap();
+
+            prop_assert_eq!(parse_latex(&q.to_latex(), &env).unwrap(), Type::Scalar(q));
+            prop_assert_eq!(parse_latex(&q.to_latex_single(), &env).unwrap(), Type::Scalar(q));
+        }
+    }
+}
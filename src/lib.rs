@@ -1,33 +1,59 @@
+mod cofactors;
 mod constants;
+mod decompositions;
+mod definiteness;
 mod editor_gui;
+mod eigenvalues;
 mod env_gui;
 mod environment;
+mod errors;
+mod exercises;
 mod float;
 #[cfg(feature = "fft")]
 mod fourier;
 #[cfg(feature = "clock")]
 mod fractal_clock;
+mod generators;
+mod import;
+mod latex_reader;
 mod locale;
 mod matrices;
 mod matrix_algorithms;
+mod matrix_functions;
 mod parser;
+mod products;
+mod quadratic;
 mod rationals;
+mod reductions;
+mod replay_gui;
+mod steps;
+mod svd;
 mod traits;
 
 #[cfg(feature = "fft")]
 use crate::constants::DFT_PATH;
 use crate::constants::{
-    APP_NAME, DEFAULT_HEIGHT, DEFAULT_LEFT_PANEL_WIDTH, DEFAULT_WIDTH, ICON_PATH,
+    APP_NAME, DEFAULT_HEIGHT, DEFAULT_LEFT_PANEL_WIDTH, DEFAULT_WIDTH, FLOAT_ABSOLUTE_EPSILON,
+    FLOAT_RELATIVE_EPSILON, FLOAT_STRING_PRECISION, ICON_PATH, MAX_APPROXIMATION_DENOMINATOR,
+    MAX_CONTINUED_FRACTION_TERMS,
 };
+use crate::decompositions::Derivation;
 use crate::editor_gui::{
     display_editor, set_editor_to_existing_matrix, set_editor_to_existing_scalar,
     set_editor_to_matrix, set_editor_to_scalar, EditorState,
 };
 use crate::environment::{Environment, Identifier, Type};
+use crate::errors::Error;
+use crate::exercises::{ExerciseOptions, ExerciseSheet};
+use crate::generators::Random;
 use crate::locale::{Language, Locale};
 use crate::matrix_algorithms::Aftermath;
+use crate::matrix_functions::PowerMethod;
 use crate::parser::parse_instruction;
-use crate::traits::{GuiDisplayable, LaTeXable, MatrixNumber};
+use crate::rationals::{continued_fraction, rationalize, RationalDisplay};
+use crate::replay_gui::{display_replay, set_replay, ReplayState};
+use crate::steps::Notation;
+use crate::traits::{ExportFormat, Exportable, GuiDisplayable, LaTeXable, MatrixNumber};
 use arboard::Clipboard;
 use constants::{FONT_ID, TEXT_COLOR, VALUE_PADDING};
 use eframe::{egui, IconData};
@@ -43,18 +69,17 @@ use traits::BoxedShape;
 use crate::fourier::Fourier;
 #[cfg(feature = "clock")]
 use crate::fractal_clock::FractalClock;
-use clap::builder::TypedValueParser;
-use clap::{arg, Parser};
+use clap::{Parser, Subcommand};
 use egui_toast::{Toast, ToastKind, ToastOptions, Toasts};
-use num_rational::Rational64;
 
 pub use float::trim_trailing_zeros_float_str;
 pub use matrices::*;
 
-use crate::float::Float64;
+use crate::float::{Float64, FloatFormat, Tolerance};
+use crate::quadratic::Quadratic;
 
-/// Field for matrices.
-type F = Rational64;
+/// Field for matrices - rationals extended with square roots.
+type F = Quadratic;
 
 /// Approximate field for matrices.
 type R = Float64;
@@ -67,22 +92,26 @@ pub fn run_application() -> Result<(), eframe::Error> {
     };
     let args = MatrixAppArgs::parse();
     let locale = Locale::new(args.language);
-
-    match args.approx {
-        true => exec_app::<R>(locale, options),
-        false => exec_app::<F>(locale, options),
+    FloatFormat::set(FloatFormat::for_locale(&locale, args.precision));
+    Tolerance::set(Tolerance {
+        absolute: args.epsilon,
+        relative: args.relative_epsilon,
+    });
+    if let Some(seed) = args.seed {
+        Random::seed(seed);
+    }
+    if let Some(Command::Exercises(options)) = &args.command {
+        generate_exercises(options, args.seed, &locale);
+        return Ok(());
     }
-}
 
-fn exec_app<T: MatrixNumber + 'static>(
-    locale: Locale,
-    options: eframe::NativeOptions,
-) -> Result<(), eframe::Error> {
-    eframe::run_native(
-        &locale.get_translated(APP_NAME),
-        options,
-        Box::new(|_cc| Box::<MatrixApp<T>>::new(MatrixApp::new(locale))),
-    )
+    let title = locale.get_translated(APP_NAME);
+    let app = match args.approx {
+        true => FieldApp::Approximate(MatrixApp::new(locale)),
+        false => FieldApp::Exact(MatrixApp::new(locale)),
+    };
+
+    eframe::run_native(&title, options, Box::new(|_cc| Box::new(app)))
 }
 
 fn load_icon(path: &str) -> Option<IconData> {
@@ -103,20 +132,81 @@ fn load_icon(path: &str) -> Option<IconData> {
     long_about = "**Just Pure 2D Graphics Matrix Display** is a powerful matrix calculator written in Rust using egui."
 )]
 struct MatrixAppArgs {
-    #[arg(
-    long,
-    default_value_t = Language::English,
-    value_parser = clap::builder::PossibleValuesParser::new(["English", "Polish", "Spanish"])
-    .map(| s | Language::of(Some(s))),
-    )]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long, global = true, default_value_t = Language::default(), value_parser = Language::parse)]
     language: Language,
 
     #[arg(long, default_value = "false")]
     approx: bool,
+
+    /// Number of fractional digits of approximate numbers.
+    #[arg(long, default_value_t = FLOAT_STRING_PRECISION)]
+    precision: usize,
+
+    /// Approximate numbers closer than this are considered equal.
+    #[arg(long, default_value_t = FLOAT_ABSOLUTE_EPSILON)]
+    epsilon: f64,
+
+    /// Approximate numbers whose relative difference is smaller than this are considered equal.
+    #[arg(long, default_value_t = FLOAT_RELATIVE_EPSILON)]
+    relative_epsilon: f64,
+
+    /// Seed of the random matrix generators, random if not given.
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generates random exercises with an answer key as a LaTeX document instead of running the calculator.
+    Exercises(ExerciseOptions),
+}
+
+/// Writes the exercise sheet to the output file, exits with an error message
+/// on failure.
+fn generate_exercises(options: &ExerciseOptions, seed: Option<u64>, locale: &Locale) {
+    let seed = seed.unwrap_or_else(|| Random::with_global(Random::next_u64));
+    let written = ExerciseSheet::<F>::generate(options, seed)
+        .and_then(|sheet| sheet.to_latex(locale))
+        .and_then(|latex| Ok(std::fs::write(&options.output, latex)?));
+    match written {
+        Ok(()) => println!(
+            "{}",
+            locale.get_translated_with(
+                "Exercises saved to {path} (seed {seed}).",
+                &[
+                    ("path", options.output.display().to_string()),
+                    ("seed", seed.to_string()),
+                ],
+            )
+        ),
+        Err(error) => {
+            eprintln!("{}", locale.get_translated_error(&error));
+            std::process::exit(1);
+        }
+    }
 }
 
 pub struct WindowState {
     is_open: bool,
+    export_format: ExportFormat,
+    /// Display mode of rationals in this window, the global one if `None`.
+    rational_display: Option<RationalDisplay>,
+    /// Exponent used by the `Power` button.
+    exponent: usize,
+}
+
+impl WindowState {
+    pub fn opened() -> Self {
+        Self {
+            is_open: true,
+            export_format: Default::default(),
+            rational_display: None,
+            exponent: 2,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -129,6 +219,8 @@ pub struct State<K: MatrixNumber> {
     windows: HashMap<Identifier, WindowState>,
     shell: ShellState,
     editor: EditorState,
+    replay: ReplayState<K>,
+    annotate_steps: bool,
     toasts: Toasts,
     clipboard: Clipboard,
     #[cfg(feature = "clock")]
@@ -144,7 +236,9 @@ impl<K: MatrixNumber> Default for State<K> {
             windows: Default::default(),
             shell: Default::default(),
             editor: Default::default(),
-            toasts: Default::default(),
+            replay: Default::default(),
+            annotate_steps: false,
+            toasts: new_toasts(),
             #[cfg(feature = "clock")]
             clock: Default::default(),
             clipboard: Clipboard::new().expect("Failed to create Clipboard context!"),
@@ -171,20 +265,53 @@ impl<K: MatrixNumber> MatrixApp<K> {
     fn gt(&self, str: &str) -> String {
         self.locale.get_translated(str)
     }
-}
 
-impl<K: MatrixNumber> eframe::App for MatrixApp<K> {
-    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
+    /// Converts the whole session (environment, open windows, shell and
+    /// editor) into another field.
+    fn convert<U: MatrixNumber>(
+        &mut self,
+        convert: impl Fn(&K) -> Option<U>,
+    ) -> anyhow::Result<MatrixApp<U>> {
+        // Everything fallible goes first, so the state is left intact on failure.
+        let env = self.state.env.convert(convert)?;
+        let clipboard = Clipboard::new()?;
+        let state = &mut self.state;
+        let converted = State {
+            env,
+            windows: std::mem::take(&mut state.windows),
+            shell: std::mem::take(&mut state.shell),
+            editor: std::mem::take(&mut state.editor),
+            replay: Default::default(),
+            annotate_steps: state.annotate_steps,
+            toasts: new_toasts(),
+            clipboard,
+            #[cfg(feature = "clock")]
+            clock: std::mem::take(&mut state.clock),
+            #[cfg(feature = "fft")]
+            fourier: state.fourier.take(),
+        };
+        Ok(MatrixApp {
+            state: converted,
+            locale: Locale::new(self.locale.get_language()),
+        })
+    }
+
+    /// Displays a single frame, returns whether switching to the other field
+    /// was requested.
+    fn update_frame(
+        &mut self,
+        ctx: &Context,
+        frame: &mut eframe::Frame,
+        approximate: bool,
+    ) -> bool {
         if !frame.is_web() {
             gui_zoom::zoom_with_keyboard_shortcuts(ctx, frame.info().native_pixels_per_point);
         }
 
-        self.state.toasts = Toasts::default()
-            .anchor(Align2::RIGHT_BOTTOM, (-10.0, -40.0))
-            .direction(egui::Direction::BottomUp);
-
-        let (_top_menu, new_locale) = display_menu_bar(ctx, &mut self.state, &self.locale);
+        let (_top_menu, new_locale, switch_field) =
+            display_menu_bar(ctx, &mut self.state, &self.locale, approximate);
         display_editor::<K>(ctx, &mut self.state, &self.locale);
+        display_replay::<K>(ctx, &mut self.state, &self.locale);
 
         let _left_panel = egui::SidePanel::left("objects")
             .resizable(true)
@@ -205,27 +332,30 @@ impl<K: MatrixNumber> eframe::App for MatrixApp<K> {
             })
             .response;
 
-        let mut windows_result = None;
+        let notation = Notation::new(&self.locale, self.state.annotate_steps);
+        let mut windows_result = vec![];
         for (id, window) in self.state.windows.iter_mut() {
             if window.is_open {
                 let element = self.state.env.get_value(id).unwrap();
                 let local_result = display_env_element_window(
                     ctx,
                     (id, element),
-                    &self.locale,
-                    &mut self.state.clipboard,
+                    (&self.locale, &notation),
+                    (&mut self.state.clipboard, &mut self.state.toasts),
                     &mut self.state.editor,
-                    &mut self.state.toasts,
-                    &mut window.is_open,
+                    &mut self.state.replay,
+                    window,
                 );
-                windows_result = windows_result.or(local_result);
+                if windows_result.is_empty() {
+                    windows_result = local_result;
+                }
             }
         }
 
-        if let Some(value) = windows_result {
+        for (identifier, value) in windows_result {
             insert_to_env(
                 &mut self.state.env,
-                Identifier::result(),
+                identifier,
                 value,
                 &mut self.state.windows,
             );
@@ -254,8 +384,65 @@ impl<K: MatrixNumber> eframe::App for MatrixApp<K> {
         self.state.toasts.show(ctx);
 
         if let Some(new_locale) = new_locale {
+            if new_locale.get_language() != self.locale.get_language() {
+                let precision = FloatFormat::current().precision;
+                FloatFormat::set(FloatFormat::for_locale(&new_locale, precision));
+            }
             self.locale = new_locale
         }
+
+        switch_field
+    }
+}
+
+/// Application working either in the exact or in the approximate field,
+/// which can be switched at runtime.
+enum FieldApp {
+    Exact(MatrixApp<F>),
+    Approximate(MatrixApp<R>),
+}
+
+impl FieldApp {
+    /// Converts the session into the other field. Approximate numbers are
+    /// replaced with their best rational approximations.
+    fn switch_field(&mut self) -> anyhow::Result<()> {
+        *self = match self {
+            FieldApp::Exact(app) => FieldApp::Approximate(app.convert(|x| Some(R::from(*x)))?),
+            FieldApp::Approximate(app) => FieldApp::Exact(
+                app.convert(|x| rationalize(*x, MAX_APPROXIMATION_DENOMINATOR).map(F::from))?,
+            ),
+        };
+        Ok(())
+    }
+
+    /// Reports the error in the toasts of the running application, they are
+    /// shown in the next frame.
+    fn report_error(&mut self, ctx: &Context, error: &anyhow::Error) {
+        let (toasts, locale) = match self {
+            FieldApp::Exact(app) => (&mut app.state.toasts, &app.locale),
+            FieldApp::Approximate(app) => (&mut app.state.toasts, &app.locale),
+        };
+        toasts_error(
+            toasts,
+            locale.get_translated_error(error),
+            Duration::from_secs(5),
+        );
+        ctx.request_repaint();
+    }
+}
+
+impl eframe::App for FieldApp {
+    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
+        let switch_field = match self {
+            FieldApp::Exact(app) => app.update_frame(ctx, frame, false),
+            FieldApp::Approximate(app) => app.update_frame(ctx, frame, true),
+        };
+
+        if switch_field {
+            if let Err(error) = self.switch_field() {
+                self.report_error(ctx, &error);
+            }
+        }
     }
 }
 
@@ -266,18 +453,35 @@ fn seconds_since_midnight() -> f64 {
     time.num_seconds_from_midnight() as f64 + 1e-9 * (time.nanosecond() as f64)
 }
 
+/// Displays the menu bar, returns the chosen locale and whether switching
+/// to the other field was requested.
 fn display_menu_bar<K: MatrixNumber>(
     ctx: &Context,
     state: &mut State<K>,
     locale: &Locale,
-) -> (Response, Option<Locale>) {
+    approximate: bool,
+) -> (Response, Option<Locale>, bool) {
     let mut new_locale = None;
+    let mut switch_field = false;
     (
         egui::TopBottomPanel::top("menu_bar")
             .show(ctx, |ui| {
                 egui::menu::bar(ui, |ui| {
                     display_add_matrix_button(ui, state, locale);
                     display_add_scalar_button(ui, state, locale);
+                    ui.checkbox(
+                        &mut state.annotate_steps,
+                        locale.get_translated("Annotate steps"),
+                    );
+                    switch_field = ui
+                        .selectable_label(approximate, locale.get_translated("Approximate"))
+                        .on_hover_text(
+                            locale.get_translated("Switch between exact and approximate numbers"),
+                        )
+                        .clicked();
+                    if !approximate {
+                        display_rational_display_selector(ui, locale);
+                    }
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         display_zoom_panel(ui, ctx);
                         ui.separator();
@@ -288,6 +492,7 @@ fn display_menu_bar<K: MatrixNumber>(
             })
             .response,
         new_locale,
+        switch_field,
     )
 }
 
@@ -309,23 +514,12 @@ fn display_zoom_panel(ui: &mut Ui, ctx: &Context) {
 fn display_language_panel(ui: &mut Ui, locale: &Locale) -> Locale {
     let mut selected = locale.get_language();
     egui::ComboBox::from_label(locale.get_translated("Language"))
-        .selected_text(locale.get_translated_from(selected.to_string()))
+        .selected_text(selected.native_name())
         .show_ui(ui, |ui| {
-            ui.selectable_value(
-                &mut selected,
-                Language::English,
-                locale.get_translated("English"),
-            );
-            ui.selectable_value(
-                &mut selected,
-                Language::Polish,
-                locale.get_translated("Polish"),
-            );
-            ui.selectable_value(
-                &mut selected,
-                Language::Spanish,
-                locale.get_translated("Spanish"),
-            );
+            for language in Language::available() {
+                let name = language.native_name().to_string();
+                ui.selectable_value(&mut selected, language, name);
+            }
         });
     Locale::new(selected)
 }
@@ -348,105 +542,336 @@ fn display_env_element<K: MatrixNumber>(
     (identifier, value): (&Identifier, &mut Type<K>),
     locale: &Locale,
 ) {
-    let mut is_open = windows.get(identifier).unwrap().is_open;
+    let window = windows.get_mut(identifier).unwrap();
     ui.horizontal(|ui| {
-        ui.checkbox(&mut is_open, identifier.to_string());
+        ui.checkbox(&mut window.is_open, identifier.to_string());
         ui.label(value.display_string(locale));
     });
-    windows.insert(identifier.clone(), WindowState { is_open });
 }
 
 fn display_env_element_window<K: MatrixNumber>(
     ctx: &Context,
     (identifier, value): (&Identifier, &Type<K>),
-    locale: &Locale,
-    clipboard: &mut Clipboard,
+    (locale, notation): (&Locale, &Notation),
+    (clipboard, toasts): (&mut Clipboard, &mut Toasts),
     editor: &mut EditorState,
-    toasts: &mut Toasts,
-    is_open: &mut bool,
-) -> Option<Type<K>> {
+    replay: &mut ReplayState<K>,
+    WindowState {
+        is_open,
+        export_format,
+        rational_display,
+        exponent,
+    }: &mut WindowState,
+) -> Vec<(Identifier, Type<K>)> {
     let mut window_result = None;
+    let mut named_results = vec![];
+    let display = *rational_display;
 
-    egui::Window::new(identifier.to_string())
-        .open(is_open)
-        .resizable(false)
-        .show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                if ui.button("LaTeX").clicked() {
-                    let latex = value.to_latex();
-                    set_clipboard(Ok(latex), clipboard, toasts, locale);
-                }
-                let mut update_by_result = |matrix_op_res| match matrix_op_res {
-                    Ok(Aftermath { result, steps }) => {
-                        window_result = Some(Type::Matrix(result));
-                        Ok(steps.join("\n"))
+    RationalDisplay::scoped(display, || {
+        egui::Window::new(identifier.to_string())
+            .open(is_open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(export_format.to_string()).clicked() {
+                        let exported = value.export(*export_format);
+                        set_clipboard(Ok(exported), clipboard, toasts, locale);
                     }
-                    Err(err) => Err(err),
-                };
-                if let Type::Matrix(m) = value {
-                    if ui.button(locale.get_translated("Echelon")).clicked() {
-                        let echelon = update_by_result(m.echelon());
-                        set_clipboard(echelon, clipboard, toasts, locale);
+                    display_export_format_selector(ui, identifier, export_format);
+                    if K::IS_EXACT {
+                        display_window_rational_display_selector(
+                            ui,
+                            identifier,
+                            rational_display,
+                            locale,
+                        );
                     }
-                }
-                if ui.button(locale.get_translated("Inverse")).clicked() {
-                    let inverse = match value {
-                        Type::Scalar(s) => match K::one().checked_div(s) {
-                            Some(inv) => {
-                                window_result = Some(Type::Scalar(inv.clone()));
-                                Ok(inv.to_latex())
+                    let format = *export_format;
+                    let mut update_by_result =
+                        |matrix_op_res: anyhow::Result<Aftermath<K>>| match matrix_op_res {
+                            Ok(aftermath) => {
+                                let exported = aftermath.export_with(format, notation);
+                                window_result = Some(Type::Matrix(aftermath.result));
+                                Ok(exported)
                             }
-                            None => Err(anyhow::Error::msg(
-                                locale.get_translated("Failed to calculate inverse"),
-                            )),
-                        },
-                        Type::Matrix(m) => update_by_result(m.inverse()),
-                    };
-                    set_clipboard(inverse, clipboard, toasts, locale);
-                }
-                if let Type::Matrix(m) = value {
-                    if ui.button(locale.get_translated("Transpose")).clicked() {
-                        let transpose = m.transpose();
-                        window_result = Some(Type::Matrix(transpose));
+                            Err(err) => Err(err),
+                        };
+                    if let Type::Matrix(m) = value {
+                        if ui.button(locale.get_translated("Echelon")).clicked() {
+                            let echelon = update_by_result(m.echelon());
+                            set_clipboard(echelon, clipboard, toasts, locale);
+                        }
                     }
-                }
-            });
-            let mut value_shape = value.to_shape(ctx, FONT_ID, TEXT_COLOR);
-            let value_rect = value_shape.get_rect();
-
-            ui.set_min_width(value_rect.width() + 2. * VALUE_PADDING);
-            ui.set_max_width(ui.min_size().x);
-            ui.separator();
+                    if ui.button(locale.get_translated("Inverse")).clicked() {
+                        let inverse = match value {
+                            Type::Scalar(s) => match K::one().checked_div(s) {
+                                Some(inv) => {
+                                    window_result = Some(Type::Scalar(inv.clone()));
+                                    Ok(inv.export(format))
+                                }
+                                None => Err(anyhow::Error::msg(
+                                    locale.get_translated("Failed to calculate inverse"),
+                                )),
+                            },
+                            Type::Matrix(m) => update_by_result(m.inverse()),
+                        };
+                        set_clipboard(inverse, clipboard, toasts, locale);
+                    }
+                    if let Type::Scalar(s) = value {
+                        if ui
+                            .button(locale.get_translated("Continued fraction"))
+                            .clicked()
+                        {
+                            let expansion = continued_fraction(s, MAX_CONTINUED_FRACTION_TERMS);
+                            if expansion.as_ref().is_some_and(|e| e.truncated) {
+                                toasts_info(
+                                    toasts,
+                                    locale.get_translated_with(
+                                        "The expansion was cut off after {count} terms",
+                                        &[("count", MAX_CONTINUED_FRACTION_TERMS.to_string())],
+                                    ),
+                                    Duration::from_secs(5),
+                                );
+                            }
+                            let expansion = expansion.map(|e| e.to_latex()).ok_or_else(|| {
+                                anyhow::Error::msg(
+                                    locale.get_translated("Failed to calculate continued fraction"),
+                                )
+                            });
+                            set_clipboard(expansion, clipboard, toasts, locale);
+                        }
+                    }
+                    if let Type::Matrix(m) = value {
+                        if ui.button(locale.get_translated("Replay")).clicked() {
+                            match m.echelon() {
+                                Ok(aftermath) => {
+                                    set_replay(replay, identifier.to_string(), aftermath)
+                                }
+                                Err(err) => set_clipboard(Err(err), clipboard, toasts, locale),
+                            }
+                        }
+                        if ui.button(locale.get_translated("Transpose")).clicked() {
+                            let transpose = m.transpose();
+                            window_result = Some(Type::Matrix(transpose));
+                        }
+                        if ui.button(locale.get_translated("Definiteness")).clicked() {
+                            let explanation = m.definiteness().map(|test| {
+                                toasts_info(
+                                    toasts,
+                                    locale.get_translated(test.definiteness.name()),
+                                    Duration::from_secs(5),
+                                );
+                                test.to_latex()
+                            });
+                            set_clipboard(explanation, clipboard, toasts, locale);
+                        }
+                        if ui.button(locale.get_translated("Gram-Schmidt")).clicked() {
+                            let orthogonalized = m.gram_schmidt().map(|mut derivation| {
+                                let exported = derivation.export(format);
+                                let (_, basis) = derivation.results.remove(0);
+                                window_result = Some(Type::Matrix(basis));
+                                exported
+                            });
+                            set_clipboard(orthogonalized, clipboard, toasts, locale);
+                        }
+                        if ui.button(locale.get_translated("Adjugate")).clicked() {
+                            let adjugate = m.adjugate_derivation().map(|mut derivation| {
+                                let exported = derivation.export(format);
+                                let (_, adjugate) = derivation.results.remove(1);
+                                window_result = Some(Type::Matrix(adjugate));
+                                exported
+                            });
+                            set_clipboard(adjugate, clipboard, toasts, locale);
+                        }
+                        if ui.button(locale.get_translated("Cramer's rule")).clicked() {
+                            // the system is given by the augmented matrix [A|b]
+                            let columns = m.get_shape().1;
+                            let solution = m
+                                .clone()
+                                .split(columns.saturating_sub(1))
+                                .and_then(|(a, b)| a.cramer(&b))
+                                .map(|mut derivation| {
+                                    let exported = derivation.export(format);
+                                    let (_, x) = derivation.results.remove(0);
+                                    window_result = Some(Type::Matrix(x));
+                                    exported
+                                });
+                            set_clipboard(solution, clipboard, toasts, locale);
+                        }
+                        if K::IS_EXACT {
+                            ui.add(egui::DragValue::new(exponent).clamp_range(0..=u16::MAX));
+                            if ui.button(locale.get_translated("Power")).clicked() {
+                                let power = m
+                                    .checked_pow_derivation(*exponent, PowerMethod::ClosedForm)
+                                    .or_else(|_| {
+                                        m.checked_pow_derivation(*exponent, PowerMethod::Squaring)
+                                    })
+                                    .map(|mut derivation| {
+                                        let exported = derivation.export(format);
+                                        let (_, power) = derivation.results.remove(0);
+                                        window_result = Some(Type::Matrix(power));
+                                        exported
+                                    });
+                                set_clipboard(power, clipboard, toasts, locale);
+                            }
+                            if ui.button(locale.get_translated("Diagonalize")).clicked() {
+                                let diagonalized = m.diagonalize().map(|derivation| {
+                                    named_results = derivation_results(&derivation, identifier);
+                                    derivation.export(format)
+                                });
+                                set_clipboard(diagonalized, clipboard, toasts, locale);
+                            }
+                            if ui.button(locale.get_translated("Jordan form")).clicked() {
+                                let jordan = m.jordan().map(|derivation| {
+                                    named_results = derivation_results(&derivation, identifier);
+                                    derivation.export(format)
+                                });
+                                set_clipboard(jordan, clipboard, toasts, locale);
+                            }
+                        }
+                        if !K::IS_EXACT {
+                            if ui.button(locale.get_translated("QR")).clicked() {
+                                let decomposition = approximate_derivation(m, Matrix::qr)
+                                    .map(|derivation| derivation.export(format));
+                                set_clipboard(decomposition, clipboard, toasts, locale);
+                            }
+                            if ui.button(locale.get_translated("SVD")).clicked() {
+                                let decomposition =
+                                    approximate_derivation(m, |a| Ok(a.svd()?.derivation(a)))
+                                        .map(|derivation| derivation.export(format));
+                                set_clipboard(decomposition, clipboard, toasts, locale);
+                            }
+                            if ui.button(locale.get_translated("Pseudo-inverse")).clicked() {
+                                let pseudo_inverse = m
+                                    .to_float()
+                                    .and_then(|a| a.pinv())
+                                    .and_then(|pinv| pinv.to_field::<K>())
+                                    .map(|pinv| {
+                                        let exported = pinv.export(format);
+                                        window_result = Some(Type::Matrix(pinv));
+                                        exported
+                                    });
+                                set_clipboard(pseudo_inverse, clipboard, toasts, locale);
+                            }
+                        }
+                    }
+                });
+                let mut value_shape = value.to_shape(ctx, FONT_ID, TEXT_COLOR);
+                let value_rect = value_shape.get_rect();
 
-            let bar_height = ui.min_size().y;
+                ui.set_min_width(value_rect.width() + 2. * VALUE_PADDING);
+                ui.set_max_width(ui.min_size().x);
+                ui.separator();
 
-            ui.add_space(value_rect.height() + VALUE_PADDING);
+                let bar_height = ui.min_size().y;
 
-            value_shape.translate(
-                ui.clip_rect().min.to_vec2()
-                    + vec2(
-                        (ui.min_size().x - value_rect.width()) / 2.,
-                        bar_height + VALUE_PADDING,
-                    ),
-            );
-            ui.painter().add(value_shape);
+                ui.add_space(value_rect.height() + VALUE_PADDING);
 
-            if !identifier.is_result() {
-                ui.separator();
-                if ui.button(locale.get_translated("Edit")).clicked() {
-                    match value {
-                        Type::Scalar(s) => {
-                            set_editor_to_existing_scalar(editor, s, identifier.to_string())
-                        }
-                        Type::Matrix(m) => {
-                            set_editor_to_existing_matrix(editor, m, identifier.to_string())
+                value_shape.translate(
+                    ui.clip_rect().min.to_vec2()
+                        + vec2(
+                            (ui.min_size().x - value_rect.width()) / 2.,
+                            bar_height + VALUE_PADDING,
+                        ),
+                );
+                ui.painter().add(value_shape);
+
+                if !identifier.is_result() {
+                    ui.separator();
+                    if ui.button(locale.get_translated("Edit")).clicked() {
+                        match value {
+                            Type::Scalar(s) => {
+                                set_editor_to_existing_scalar(editor, s, identifier.to_string())
+                            }
+                            Type::Matrix(m) => {
+                                set_editor_to_existing_matrix(editor, m, identifier.to_string())
+                            }
                         }
                     }
-                }
+                };
+            })
+    });
+
+    match window_result {
+        Some(value) => vec![(Identifier::result(), value)],
+        None => named_results,
+    }
+}
+
+/// Results of a derivation named after the decomposed matrix, e.g. `P_A` and
+/// `D_A` for the diagonalization of `A`, or just `P` and `D` for the result.
+fn derivation_results<K: MatrixNumber>(
+    derivation: &Derivation<K>,
+    identifier: &Identifier,
+) -> Vec<(Identifier, Type<K>)> {
+    derivation
+        .results
+        .iter()
+        .filter_map(|(symbol, matrix)| {
+            let name = match identifier.is_result() {
+                true => symbol.clone(),
+                false => format!("{symbol}_{identifier}"),
             };
+            Some((Identifier::new(name).ok()?, Type::Matrix(matrix.clone())))
+        })
+        .collect()
+}
+
+/// Decomposition of a matrix over an approximate field, computed over
+/// [`Float64`] and converted back.
+fn approximate_derivation<K: MatrixNumber>(
+    m: &Matrix<K>,
+    decompose: impl FnOnce(&Matrix<Float64>) -> anyhow::Result<Derivation<Float64>>,
+) -> anyhow::Result<Derivation<K>> {
+    Ok(decompose(&m.to_float()?)?
+        .convert(|x| K::from_f64(f64::from(*x)))
+        .ok_or(Error::ConversionFailed)?)
+}
+
+fn display_window_rational_display_selector(
+    ui: &mut Ui,
+    identifier: &Identifier,
+    display: &mut Option<RationalDisplay>,
+    locale: &Locale,
+) {
+    let name = |display: Option<RationalDisplay>| match display {
+        Some(display) => locale.get_translated(display.name()),
+        None => locale.get_translated("Global display"),
+    };
+    egui::ComboBox::from_id_source(("rational_display", identifier))
+        .selected_text(name(*display))
+        .width(0.)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(display, None, name(None));
+            for option in RationalDisplay::ALL {
+                ui.selectable_value(display, Some(option), name(Some(option)));
+            }
+        });
+}
+
+fn display_rational_display_selector(ui: &mut Ui, locale: &Locale) {
+    let mut selected = RationalDisplay::current();
+    egui::ComboBox::from_label(locale.get_translated("Rationals"))
+        .selected_text(locale.get_translated(selected.name()))
+        .show_ui(ui, |ui| {
+            for option in RationalDisplay::ALL {
+                ui.selectable_value(&mut selected, option, locale.get_translated(option.name()));
+            }
         });
+    if selected != RationalDisplay::current() {
+        RationalDisplay::set(selected);
+    }
+}
 
-    window_result
+fn display_export_format_selector(ui: &mut Ui, identifier: &Identifier, format: &mut ExportFormat) {
+    egui::ComboBox::from_id_source(("export_format", identifier))
+        .selected_text(format.to_string())
+        .width(0.)
+        .show_ui(ui, |ui| {
+            for option in ExportFormat::ALL {
+                ui.selectable_value(format, option, option.to_string());
+            }
+        });
 }
 
 fn set_clipboard(
@@ -457,18 +882,18 @@ fn set_clipboard(
 ) {
     const CLIPBOARD_TOAST_DURATION: Duration = Duration::from_secs(5);
     match message {
-        Ok(latex) => match clipboard.set_text(latex) {
+        Ok(text) => match clipboard.set_text(text) {
             Ok(_) => {
                 toasts_info(
                     toasts,
-                    locale.get_translated("LaTeX copied to clipboard"),
+                    locale.get_translated("Copied to clipboard"),
                     CLIPBOARD_TOAST_DURATION,
                 );
             }
             Err(e) => {
                 toasts_error(
                     toasts,
-                    locale.get_translated("Failed to copy LaTeX to clipboard")
+                    locale.get_translated("Failed to copy to clipboard")
                         + "\n"
                         + e.to_string().as_str(),
                     CLIPBOARD_TOAST_DURATION,
@@ -478,7 +903,7 @@ fn set_clipboard(
         Err(e) => {
             toasts_error(
                 toasts,
-                locale.get_translated("Failed to generate LaTeX") + "\n" + e.to_string().as_str(),
+                locale.get_translated("Failed to export") + "\n" + &locale.get_translated_error(&e),
                 CLIPBOARD_TOAST_DURATION,
             );
         }
@@ -499,11 +924,15 @@ fn display_shell<K: MatrixNumber>(
     let mut run_shell_command = |shell_text: &mut String| match parse_instruction(shell_text, env) {
         Ok(identifier) => {
             shell_text.clear();
-            windows.insert(identifier, WindowState { is_open: true });
+            windows.insert(identifier, WindowState::opened());
         }
         Err(error) => {
             println!("{error}");
-            toasts_error(toasts, error.to_string(), Duration::from_secs(5));
+            toasts_error(
+                toasts,
+                locale.get_translated_error(&error),
+                Duration::from_secs(5),
+            );
         }
     };
 
@@ -541,6 +970,12 @@ fn display_shell<K: MatrixNumber>(
         });
 }
 
+fn new_toasts() -> Toasts {
+    Toasts::default()
+        .anchor(Align2::RIGHT_BOTTOM, (-10.0, -40.0))
+        .direction(egui::Direction::BottomUp)
+}
+
 fn toasts_add_kind(toasts: &mut Toasts, text: String, duration: Duration, kind: ToastKind) {
     toasts.add(Toast {
         text: text.into(),
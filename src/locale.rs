@@ -1,174 +1,394 @@
+use crate::constants::LOCALES_PATH;
+use anyhow::Context;
 use lazy_static::lazy_static;
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
+use std::path::Path;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Language {
-    English,
-    Spanish,
-    Polish,
+/// Catalogs compiled into the binary. Catalog files found in `LOCALES_PATH`
+/// at runtime override them or add new languages.
+const EMBEDDED_CATALOGS: [(&str, &str); 3] = [
+    ("en", include_str!("../assets/locales/en.json")),
+    ("pl", include_str!("../assets/locales/pl.json")),
+    ("es", include_str!("../assets/locales/es.json")),
+];
+
+/// Code of the language used when the requested one is not available. Its
+/// catalog also provides the translations missing from other catalogs.
+const DEFAULT_LANGUAGE: &str = "en";
+
+/// Language identified by the name of its catalog file, e.g. `pl`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Language {
+    code: String,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self {
+            code: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
 }
 
 impl Display for Language {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.catalog().english_name)
+    }
+}
+
+impl Language {
+    /// Returns all languages with a catalog, sorted by their codes.
+    pub fn available() -> Vec<Language> {
+        CATALOGS
+            .keys()
+            .map(|code| Language { code: code.clone() })
+            .collect()
+    }
+
+    /// Finds the language by its code, English or native name (ignoring
+    /// case), e.g. `pl`, `Polish` or `polski`.
+    pub fn parse(str: &str) -> Result<Language, String> {
+        let str = str.to_lowercase();
+        CATALOGS
+            .iter()
+            .find(|(code, catalog)| {
+                [code.as_str(), &catalog.english_name, &catalog.name]
+                    .iter()
+                    .any(|name| name.to_lowercase() == str)
+            })
+            .map(|(code, _)| Language { code: code.clone() })
+            .ok_or_else(|| {
+                let available: Vec<String> = Language::available()
+                    .iter()
+                    .map(Language::to_string)
+                    .collect();
+                format!("available languages: {}", available.join(", "))
+            })
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Name of the language in the language itself, e.g. "Polski".
+    pub fn native_name(&self) -> &str {
+        &self.catalog().name
+    }
+
+    fn catalog(&self) -> &'static Catalog {
+        CATALOGS
+            .get(&self.code)
+            .unwrap_or_else(|| &CATALOGS[DEFAULT_LANGUAGE])
+    }
+}
+
+/// Rules choosing the plural form for a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluralRule {
+    /// `one` for 1, `other` for everything else (e.g. English, Spanish).
+    #[default]
+    OneOther,
+    /// `one` for 1, `few` for 2-4, 22-24, 32-34, ... (but not 12-14),
+    /// `many` for everything else.
+    Polish,
+    /// Always `other` (e.g. languages without plural forms).
+    None,
+}
+
+impl PluralRule {
+    pub fn category(&self, n: usize) -> &'static str {
         match self {
-            Language::English => write!(f, "English"),
-            Language::Spanish => write!(f, "Spanish"),
-            Language::Polish => write!(f, "Polish"),
+            PluralRule::OneOther if n == 1 => "one",
+            PluralRule::OneOther => "other",
+            PluralRule::Polish if n == 1 => "one",
+            PluralRule::Polish
+                if (2..=4).contains(&(n % 10)) && !(12..=14).contains(&(n % 100)) =>
+            {
+                "few"
+            }
+            PluralRule::Polish => "many",
+            PluralRule::None => "other",
         }
     }
 }
 
-impl Language {
-    pub fn of(str: Option<String>) -> Language {
-        str.map_or(Language::English, |str| match str.to_lowercase().as_str() {
-            "en" | "english" => Language::English,
-            "es" | "spanish" => Language::Spanish,
-            "pl" | "polish" => Language::Polish,
-            _ => Language::English,
+/// Translation of a single key - either a text or its plural forms
+/// indexed by the categories of `PluralRule`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Translation {
+    Text(String),
+    Plural(HashMap<String, String>),
+}
+
+/// Translations of a single language, read from a JSON file like:
+/// ```json
+/// {
+///   "name": "Polski",
+///   "english_name": "Polish",
+///   "plural_rule": "polish",
+///   "translations": {
+///     "Add": "Dodaj",
+///     "{count} steps": { "one": "{count} krok", "few": "{count} kroki", "many": "{count} kroków" }
+///   }
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+struct Catalog {
+    name: String,
+    english_name: String,
+    #[serde(default)]
+    plural_rule: PluralRule,
+    translations: HashMap<String, Translation>,
+}
+
+impl Catalog {
+    fn get(&self, key: &str) -> Option<&Translation> {
+        self.translations.get(key)
+    }
+
+    /// Returns the plural form of `key` suitable for `count`.
+    fn get_plural(&self, key: &str, count: usize) -> Option<&String> {
+        match self.get(key)? {
+            Translation::Text(text) => Some(text),
+            Translation::Plural(forms) => forms
+                .get(self.plural_rule.category(count))
+                .or_else(|| forms.get("other"))
+                .or_else(|| forms.values().next()),
+        }
+    }
+}
+
+lazy_static! {
+    static ref CATALOGS: BTreeMap<String, Catalog> = load_catalogs(Path::new(LOCALES_PATH));
+}
+
+/// Loads the embedded catalogs and every `<code>.json` file from `dir`.
+/// Invalid files are reported and skipped.
+fn load_catalogs(dir: &Path) -> BTreeMap<String, Catalog> {
+    let mut catalogs: BTreeMap<String, Catalog> = EMBEDDED_CATALOGS
+        .iter()
+        .map(|(code, raw)| {
+            let catalog = serde_json::from_str(raw).expect("Embedded catalog should be valid!");
+            (code.to_string(), catalog)
         })
+        .collect();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return catalogs;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(code) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        match read_catalog(&path) {
+            Ok(catalog) => {
+                catalogs.insert(code.to_string(), catalog);
+            }
+            Err(error) => eprintln!("{error:#}"),
+        }
     }
+    catalogs
+}
+
+fn read_catalog(path: &Path) -> anyhow::Result<Catalog> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read catalog {:?}!", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("Invalid catalog {:?}!", path.display()))
+}
+
+/// Replaces every `{name}` placeholder with the corresponding value.
+pub(crate) fn fill_placeholders(text: &str, args: &[(&str, String)]) -> String {
+    args.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value)
+    })
 }
 
 pub struct Locale {
     language: Language,
-    translation_map: &'static HashMap<String, String>,
+    catalog: &'static Catalog,
+    fallback: &'static Catalog,
 }
 
 impl Locale {
     pub fn new(language: Language) -> Self {
         Self {
+            catalog: language.catalog(),
+            fallback: &CATALOGS[DEFAULT_LANGUAGE],
             language,
-            translation_map: match language {
-                Language::English => &TRANS_EN_MAP,
-                Language::Polish => &TRANS_PL_MAP,
-                Language::Spanish => &TRANS_ES_MAP,
-            },
-        }
-    }
-
-    fn unwrap_or_default(str: Option<&String>, default: &str) -> String {
-        match str {
-            Some(str) => str.to_string(),
-            None => default.to_string(),
         }
     }
 
     pub fn get_language(&self) -> Language {
-        self.language
+        self.language.clone()
     }
 
+    /// Translates `s`. Falls back to the default language and then to `s`
+    /// itself if there is no translation.
     pub fn get_translated(&self, s: &str) -> String {
-        Self::unwrap_or_default(self.translation_map.get(s), s)
+        self.get_plural_or_text(s, 1)
+            .unwrap_or_else(|| s.to_string())
     }
 
-    pub fn get_translated_from(&self, s: String) -> String {
-        self.get_translated(&s)
+    /// Translates `s` and replaces every `{name}` placeholder with the
+    /// corresponding value.
+    pub fn get_translated_with(&self, s: &str, args: &[(&str, String)]) -> String {
+        fill_placeholders(&self.get_translated(s), args)
+    }
+
+    /// Translates `s` choosing the plural form suitable for `count`, which
+    /// replaces the `{count}` placeholder (as well as the other `args`).
+    pub fn get_plural(&self, s: &str, count: usize, args: &[(&str, String)]) -> String {
+        let text = self
+            .get_plural_or_text(s, count)
+            .unwrap_or_else(|| s.to_string());
+        fill_placeholders(
+            &fill_placeholders(&text, args),
+            &[("count", count.to_string())],
+        )
+    }
+
+    /// Whether the language of the locale has its own translation of `s`.
+    pub fn is_translated(&self, s: &str) -> bool {
+        self.catalog.get(s).is_some()
+    }
+
+    fn get_plural_or_text(&self, s: &str, count: usize) -> Option<String> {
+        self.catalog
+            .get_plural(s, count)
+            .or_else(|| self.fallback.get_plural(s, count))
+            .cloned()
     }
 }
 
-lazy_static! {
-    pub static ref TRANS_EN_RAW: Vec<(&'static str, &'static str)> = vec![
-        ("objects", "Objects"),
-        ("matrix", "Matrix"),
-        ("Add Matrix", "Add Matrix"),
-        ("Add Scalar", "Add Scalar"),
-        ("JP2GMD - Matrix Calculator", "JP2GMD - Matrix Calculator"),
-        ("Echelon", "Echelon"),
-        ("Inverse", "Inverse"),
-        ("Transpose", "Transpose"),
-        ("Run", "Run"),
-        ("Editor", "Editor"),
-        ("Identifier:", "Identifier:"),
-        ("Matrix is invalid!", "Matrix is invalid!"),
-        ("Add", "Add"),
-        ("Error", "Error"),
-        ("Enter the matrix:", "Enter the matrix:"),
-        ("Enter the scalar:", "Enter the scalar:"),
-        ("Height", "Height"),
-        ("Width", "Width"),
-        ("Edit", "Edit"),
-        ("Error ", "Error "),
-        ("Identifier is invalid!", "Identifier is invalid!"),
-        ("LaTeX copied to clipboard", "LaTeX copied to clipboard"),
-        ("Failed to generate LaTeX", "Failed to generate LaTeX"),
-        ("Language", "Language"),
-        ("English", "English"),
-        ("Polish", "Polish"),
-        ("Spanish", "Spanish"),
-    ];
-    pub static ref TRANS_PL_RAW: Vec<(&'static str, &'static str)> =
-        vec![
-        ("objects", "Obiekty"),
-        ("matrix", "Macierz"),
-        ("Add Matrix", "Dodaj Macierz"),
-        ("Add Scalar", "Dodaj Skalar"),
-        (
-            "JP2GMD - Matrix Calculator",
-            "Jaki Potężny 2-wymiarowy Generator Macierzy Diagonalizowalnych - Kalkulator Macierzy"
-        ),
-        ("Echelon", "Schodkuj"),
-        ("Inverse", "Odwrotność"),
-        ("Transpose", "Transponuj"),
-        ("Run", "Uruchom"),
-        ("Editor", "Edytor"),
-        ("Identifier:", "Identyfikator:"),
-        ("Matrix is invalid!", "Macierz jest niepoprawna!"),
-        ("Add", "Dodaj"),
-        ("Error", "Błąd"),
-        ("Enter the matrix:", "Wprowadź macierz:"),
-        ("Enter the scalar:", "Wprowadź skalar:"),
-        ("Height", "Wysokość"),
-        ("Width", "Szerokość"),
-        ("Edit", "Edytuj"),
-        ("Error ", "Błąd "),
-        ("Identifier is invalid!", "Identyfikator jest niepoprawny!"),
-        ("LaTeX copied to clipboard", "Skopiowano LaTeX'a do schowka"),
-        ("Failed to generate LaTeX", "Nie udało się wygenerować LaTeX'a"),
-        ("Language", "Język"),
-        ("English", "Angielski"),
-        ("Polish", "Polski"),
-        ("Spanish", "Hiszpański"),
-    ];
-    pub static ref TRANS_ES_RAW: Vec<(&'static str, &'static str)> = vec![
-        ("objects", "Objetos"),
-        ("matrix", "Matriz"),
-        ("Add Matrix", "Añadir Matriz"),
-        ("Add Scalar", "Añadir Escalar"),
-        (
-            "JP2GMD - Matrix Calculator",
-            "JP2GMD - Calculadora de Matrices"
-        ),
-        ("Echelon", "Echelon"),
-        ("Inverse", "Inversa"),
-        ("Transpose", "Transpuesta"),
-        ("Run", "Ejecutar"),
-        ("Editor", "Editor"),
-        ("Identifier:", "Identificador:"),
-        ("Matrix is invalid!", "¡La matriz es inválida!"),
-        ("Add", "Añadir"),
-        ("Error", "Error"),
-        ("Enter the matrix:", "Introduzca la matriz:"),
-        ("Enter the scalar:", "Introduzca el escalar:"),
-        ("Height", "Altura"),
-        ("Width", "Anchura"),
-        ("Editor", "Editar"),
-        ("Error ", "Error "),
-        ("Identifier is invalid!", "¡El identificador es inválido!"),
-        ("LaTeX copied to clipboard", "LaTeX copiado al portapapeles"),
-        ("Failed to generate LaTeX", "No se pudo generar LaTeX"),
-        ("Language", "Idioma"),
-        ("English", "Inglés"),
-        ("Polish", "Polaco"),
-        ("Spanish", "Español"),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Functions whose first argument is a translation key.
+    const TRANSLATING_FUNCTIONS: [&str; 4] = [
+        "get_translated(",
+        "get_translated_with(",
+        "get_plural(",
+        ".gt(",
     ];
-    pub static ref TRANS_EN_MAP: HashMap<String, String> = gen_map(&TRANS_EN_RAW);
-    pub static ref TRANS_PL_MAP: HashMap<String, String> = gen_map(&TRANS_PL_RAW);
-    pub static ref TRANS_ES_MAP: HashMap<String, String> = gen_map(&TRANS_ES_RAW);
-}
 
-fn gen_map(vec: &[(&'static str, &'static str)]) -> HashMap<String, String> {
-    vec.iter()
-        .map(|(k, v)| (k.to_string(), v.to_string()))
-        .collect()
+    /// Returns string literals passed as keys to the translating functions in
+    /// `*.rs` files in `dir` (tests excluded), along with their file names.
+    fn used_keys(dir: &Path) -> Vec<(String, String)> {
+        let mut keys = vec![];
+        for path in std::fs::read_dir(dir).unwrap().flatten().map(|e| e.path()) {
+            if path.extension().and_then(|extension| extension.to_str()) != Some("rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            let source = source.split("#[cfg(test)]").next().unwrap();
+            for function in TRANSLATING_FUNCTIONS {
+                for (index, _) in source.match_indices(function) {
+                    let rest = source[index + function.len()..].trim_start();
+                    let Some(rest) = rest.strip_prefix('"') else {
+                        continue;
+                    };
+                    let key = rest.split('"').next().unwrap();
+                    let file = path.file_name().unwrap().to_string_lossy().to_string();
+                    keys.push((file, key.to_string()));
+                }
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn test_catalogs_contain_used_keys() {
+        let keys = used_keys(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"));
+        assert!(keys.iter().any(|(_, key)| key == "Echelon"));
+
+        let missing: Vec<String> = CATALOGS
+            .iter()
+            .flat_map(|(code, catalog)| {
+                keys.iter()
+                    .filter(|(_, key)| catalog.get(key).is_none())
+                    .map(move |(file, key)| format!("{code}: \"{key}\" (used in {file})"))
+            })
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "Keys missing from catalogs:\n{}",
+            missing.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_embedded_catalogs() {
+        let codes: Vec<String> = Language::available()
+            .iter()
+            .map(|language| language.code().to_string())
+            .collect();
+        assert!(codes.contains(&"en".to_string()));
+        assert!(codes.contains(&"pl".to_string()));
+        assert!(codes.contains(&"es".to_string()));
+
+        assert_eq!(Language::parse("Polish").unwrap().code(), "pl");
+        assert_eq!(Language::parse("español").unwrap().code(), "es");
+        assert!(Language::parse("Klingon").is_err());
+        assert_eq!(Language::default().to_string(), "English");
+    }
+
+    #[test]
+    fn test_translations() {
+        let polish = Locale::new(Language::parse("pl").unwrap());
+
+        assert_eq!(polish.get_translated("Add"), "Dodaj");
+        assert_eq!(polish.get_translated("Unknown key"), "Unknown key");
+        assert_eq!(
+            polish.get_translated_with(
+                "Swap rows {first} and {second}",
+                &[("first", "1".to_string()), ("second", "2".to_string())]
+            ),
+            "Zamień wiersze 1 i 2"
+        );
+    }
+
+    #[test]
+    fn test_plural_forms() {
+        let polish = Locale::new(Language::parse("pl").unwrap());
+        let english = Locale::new(Language::default());
+
+        let steps = |locale: &Locale, n| locale.get_plural("{count} steps", n, &[]);
+        assert_eq!(steps(&english, 1), "1 step");
+        assert_eq!(steps(&english, 0), "0 steps");
+        assert_eq!(steps(&polish, 1), "1 krok");
+        assert_eq!(steps(&polish, 3), "3 kroki");
+        assert_eq!(steps(&polish, 12), "12 kroków");
+        assert_eq!(steps(&polish, 22), "22 kroki");
+        assert_eq!(steps(&polish, 25), "25 kroków");
+    }
+
+    #[test]
+    fn test_load_catalogs_from_directory() {
+        let dir = std::env::temp_dir().join("jp2gmd_test_load_catalogs");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("de.json"),
+            r#"{"name": "Deutsch", "english_name": "German", "translations": {"Add": "Hinzufügen"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let catalogs = load_catalogs(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!catalogs.contains_key("broken"));
+        let german = &catalogs["de"];
+        assert_eq!(german.english_name, "German");
+        assert_eq!(german.plural_rule, PluralRule::OneOther);
+        assert!(matches!(german.get("Add"), Some(Translation::Text(text)) if text == "Hinzufügen"));
+        assert!(catalogs.contains_key("en"));
+    }
 }
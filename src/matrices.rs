@@ -1,9 +1,11 @@
 use crate::constants::{MATRIX_HPADDING, MATRIX_VPADDING};
+use crate::errors::Error;
 use crate::locale::Locale;
-use crate::traits::{BoxedShape, LaTeXable};
+use crate::traits::{wrap_mathml, BoxedShape, ExportFormat, Exportable, LaTeXable};
 use crate::traits::{GuiDisplayable, MatrixNumber};
 use anyhow::{bail, Context};
 use egui::{pos2, Color32, FontId, Rect};
+use std::fmt::{Display, Formatter};
 use std::ops::{Add, Mul, Neg, Sub};
 
 /// A matrix of type `T`.
@@ -319,7 +321,7 @@ impl<T: MatrixNumber> Matrix<T> {
     /// assert!(!m.is_valid());
     /// ```
     pub fn is_valid(&self) -> bool {
-        return if self.data.is_empty() {
+        if self.data.is_empty() {
             true
         } else {
             !self
@@ -330,7 +332,7 @@ impl<T: MatrixNumber> Matrix<T> {
                     (acc || row_len != next.len(), row_len)
                 })
                 .0 // does any row have different length?
-        };
+        }
     }
 
     /// Returns the raw data of the matrix.
@@ -400,9 +402,11 @@ impl<T: MatrixNumber> Matrix<T> {
         if self_w == other_h {
             Ok((h, w))
         } else {
-            Err(anyhow::anyhow!(
-                "Cannot multiply matrices of shapes ({h}, {self_w}) and ({other_h}, {w})"
-            ))
+            Err(Error::MultiplicationShapes {
+                left: format!("({h}, {self_w})"),
+                right: format!("({other_h}, {w})"),
+            }
+            .into())
         }
     }
 
@@ -424,9 +428,9 @@ impl<T: MatrixNumber> Matrix<T> {
     where
         F: Fn(&T, &T) -> Option<T>,
     {
-        self.same_shapes(other)
-            .then_some(())
-            .context("Matrices have different shapes!")?;
+        if !self.same_shapes(other) {
+            bail!(Error::DifferentShapes);
+        }
         let data = self
             .data
             .iter()
@@ -439,7 +443,7 @@ impl<T: MatrixNumber> Matrix<T> {
                     .collect::<Option<Vec<T>>>()
             })
             .collect::<Option<Vec<Vec<T>>>>()
-            .ok_or_else(|| anyhow::anyhow!("Operation failed!"))?;
+            .ok_or_else(T::checked_ops_error)?;
         Self::new(data)
     }
 
@@ -466,6 +470,29 @@ impl<T: MatrixNumber> Matrix<T> {
         self.checked_operation_on_two(self, |a, _| operation(a))
     }
 
+    /// Converts every element of the matrix into another number type.
+    /// # Arguments
+    /// * `convert` - The conversion of a single element.
+    /// # Returns
+    /// The converted matrix with the same separator or `None` if any element
+    /// cannot be converted.
+    /// # Examples
+    /// ```rust
+    /// # use jp2gmd_lib::Matrix;
+    /// let m = Matrix::new(vec![vec![1_i64, 2], vec![3, 4]]).unwrap();
+    /// let converted = m.convert(|x| i32::try_from(*x).ok()).unwrap();
+    /// assert_eq!(converted, Matrix::new(vec![vec![1_i32, 2], vec![3, 4]]).unwrap());
+    /// assert!(Matrix::new(vec![vec![i64::MAX]]).unwrap().convert(|x| i32::try_from(*x).ok()).is_none());
+    /// ```
+    pub fn convert<U: MatrixNumber>(&self, convert: impl Fn(&T) -> Option<U>) -> Option<Matrix<U>> {
+        let data = self
+            .data
+            .iter()
+            .map(|row| row.iter().map(&convert).collect::<Option<Vec<U>>>())
+            .collect::<Option<Vec<Vec<U>>>>()?;
+        Some(Matrix::new_unsafe(data).with_separator(self.separator))
+    }
+
     /// Performs matrix addition.
     /// # Arguments
     /// * `other` - The other matrix.
@@ -534,16 +561,17 @@ impl<T: MatrixNumber> Matrix<T> {
     /// assert_eq!(m3, Matrix::new(vec![vec![7, 10], vec![15, 22]]).unwrap());
     /// ```
     pub fn checked_mul(&self, v: &Self) -> anyhow::Result<Self> {
-        const OVERFLOW_MSG: &str = "Overflow during matrix multiplication!";
         let (h, w) = self.result_shape_for_mul(v)?;
 
         let mut res = Matrix::<T>::zeros((h, w)).data;
-        for (i, item) in self.data.iter().enumerate() {
-            for j in 0..w {
+        for (res_row, item) in res.iter_mut().zip(self.data.iter()) {
+            for (j, res_elem) in res_row.iter_mut().enumerate() {
                 for (k, item_item) in item.iter().enumerate() {
-                    res[i][j] = (item_item.checked_mul(&v.data[k][j]).context(OVERFLOW_MSG)?)
-                        .checked_add(&res[i][j])
-                        .context(OVERFLOW_MSG)?;
+                    *res_elem = (item_item
+                        .checked_mul(&v.data[k][j])
+                        .ok_or_else(T::checked_ops_error)?)
+                    .checked_add(res_elem)
+                    .ok_or_else(T::checked_ops_error)?;
                 }
             }
         }
@@ -576,7 +604,7 @@ impl<T: MatrixNumber> Matrix<T> {
     pub fn checked_pow(&self, mut exponent: usize) -> anyhow::Result<Self> {
         let (h, w) = self.get_shape();
         if h != w {
-            bail!("Only square matrices can be used in exponentiation!");
+            bail!(Error::NonSquarePower);
         }
 
         let mut pow2 = self.clone();
@@ -691,6 +719,155 @@ impl<T: MatrixNumber> LaTeXable for Matrix<T> {
     }
 }
 
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the representations of all elements.
+    fn cells(&self, cell: impl Fn(&T) -> String) -> Vec<Vec<String>> {
+        self.data
+            .iter()
+            .map(|row| row.iter().map(&cell).collect())
+            .collect()
+    }
+
+    /// Returns the widths (in characters) of all columns of `cells`.
+    fn column_widths(cells: &[Vec<String>]) -> Vec<usize> {
+        let mut widths = vec![0; cells.first().map_or(0, |row| row.len())];
+        for row in cells {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        widths
+    }
+
+    /// Exports the matrix as a GitHub-flavoured Markdown table. As tables
+    /// require a header, the header row is left empty.
+    fn to_markdown(&self) -> String {
+        let (_, cols) = self.get_shape();
+        let header = format!("|{}", "   |".repeat(cols));
+        let alignment = format!("|{}", "---:|".repeat(cols));
+        std::iter::once(header)
+            .chain(std::iter::once(alignment))
+            .chain(
+                self.cells(T::to_plain)
+                    .iter()
+                    .map(|row| format!("| {} |", row.join(" | "))),
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Exports the matrix as comma separated values, one row per line.
+    fn to_csv(&self) -> String {
+        self.cells(T::to_data)
+            .iter()
+            .map(|row| row.join(","))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Pretty prints the matrix using box-drawing characters, e.g.
+    /// ```text
+    /// ┌          ┐
+    /// │ 1  2 │ 3 │
+    /// │ 4  5 │ 6 │
+    /// └          ┘
+    /// ```
+    fn to_ascii(&self) -> String {
+        let cells = self.cells(T::to_data);
+        let widths = Self::column_widths(&cells);
+        let rows = cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&widths)
+                    .enumerate()
+                    .map(|(j, (cell, width))| {
+                        let gap = match (j, self.separator) {
+                            (0, _) => "",
+                            (j, Some(s)) if j == s => " │ ",
+                            _ => "  ",
+                        };
+                        format!("{gap}{cell:>width$}")
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        let inner_width = rows.first().map_or(0, |row| row.chars().count());
+        let padding = " ".repeat(inner_width + 2);
+
+        std::iter::once(format!("┌{padding}┐"))
+            .chain(rows.iter().map(|row| format!("│ {row} │")))
+            .chain(std::iter::once(format!("└{padding}┘")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<T: MatrixNumber> Exportable for Matrix<T> {
+    fn to_typst(&self) -> String {
+        let augment = self
+            .separator
+            .map(|s| format!("augment: #{s}, "))
+            .unwrap_or_default();
+        format!(
+            r#"mat(delim: "[", {augment}{})"#,
+            self.data
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|elem| elem.to_typst())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        )
+    }
+
+    fn to_mathml(&self) -> String {
+        let (_, cols) = self.get_shape();
+        let column_lines = match self.separator {
+            Some(s) if s > 0 && s < cols => format!(
+                r#" columnlines="{}""#,
+                (1..cols)
+                    .map(|j| if j == s { "solid" } else { "none" })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            _ => String::new(),
+        };
+        format!(
+            "<mrow><mo>[</mo><mtable{column_lines}>{}</mtable><mo>]</mo></mrow>",
+            self.data
+                .iter()
+                .map(|row| {
+                    format!(
+                        "<mtr>{}</mtr>",
+                        row.iter()
+                            .map(|elem| format!("<mtd>{}</mtd>", elem.to_mathml()))
+                            .collect::<String>()
+                    )
+                })
+                .collect::<String>()
+        )
+    }
+
+    fn to_plain(&self) -> String {
+        self.to_ascii()
+    }
+
+    fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::LaTeX => self.to_latex(),
+            ExportFormat::Typst => self.to_typst(),
+            ExportFormat::MathML => wrap_mathml(&self.to_mathml()),
+            ExportFormat::Markdown => self.to_markdown(),
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Ascii => self.to_ascii(),
+        }
+    }
+}
+
 impl<T: MatrixNumber> GuiDisplayable for Matrix<T> {
     fn display_string(&self, locale: &Locale) -> String {
         let (h, w) = self.get_shape();
@@ -699,13 +876,28 @@ impl<T: MatrixNumber> GuiDisplayable for Matrix<T> {
     }
 
     fn to_shape(&self, ctx: &egui::Context, font_id: FontId, color: Color32) -> egui::Shape {
+        self.to_shape_colored(ctx, font_id, |_, _| color)
+    }
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Same as `to_shape`, but the color of every cell is given by
+    /// `color(row, column)`. Used to highlight parts of the matrix.
+    pub fn to_shape_colored(
+        &self,
+        ctx: &egui::Context,
+        font_id: FontId,
+        color: impl Fn(usize, usize) -> Color32,
+    ) -> egui::Shape {
         let (rows, cols) = self.get_shape();
         let mut shapes: Vec<Vec<egui::Shape>> = self
             .get_data()
             .iter()
-            .map(|row| {
+            .enumerate()
+            .map(|(i, row)| {
                 row.iter()
-                    .map(|element| element.to_shape(ctx, font_id.clone(), color))
+                    .enumerate()
+                    .map(|(j, element)| element.to_shape(ctx, font_id.clone(), color(i, j)))
                     .collect()
             })
             .collect();
@@ -795,18 +987,22 @@ impl<T: MatrixNumber> Mul<T> for Matrix<T> {
     }
 }
 
-impl<T: MatrixNumber> ToString for Matrix<T> {
-    fn to_string(&self) -> String {
-        self.data
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|elem| elem.to_string())
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
+impl<T: MatrixNumber> Display for Matrix<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.data
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|elem| elem.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
     }
 }
 
@@ -879,6 +1075,27 @@ macro_rules! im {
     );
 }
 
+/// Create a matrix of Float64 numbers passed as f64 literals.
+/// fm stands for Float Matrix, `Float64` has to be in scope.
+/// Example:
+/// ```ignore
+/// // Creates a matrix
+/// // | 1.5  2  |
+/// // | -1   0.1 |
+/// // values of the matrix are Float64 numbers
+/// fm!(1.5, 2.; -1., 0.1);
+/// ```
+#[macro_export]
+macro_rules! fm {
+    ($($($x:expr),+ $(,)?);+ $(;)?) => (
+        Matrix::<Float64>::new_unsafe(vec![
+            $(vec![
+                $(Float64::from($x)),+
+            ]),+
+        ])
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ri;
@@ -895,6 +1112,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_matrix_export() {
+        let matrix = rm![1, 2; 3, 4]
+            .checked_mul_scl(&Rational64::new(1, 2))
+            .unwrap();
+
+        assert_eq!(
+            matrix.export(ExportFormat::Typst),
+            r#"mat(delim: "[", frac(1, 2), 1; frac(3, 2), 2)"#
+        );
+        assert_eq!(matrix.export(ExportFormat::Csv), "1/2,1\n3/2,2");
+        assert_eq!(
+            matrix.export(ExportFormat::Markdown),
+            "|   |   |\n|---:|---:|\n| 1/2 | 1 |\n| 3/2 | 2 |"
+        );
+    }
+
+    #[test]
+    fn test_matrix_export_separator() {
+        let matrix = im![1, 2, 3; 4, 50, 6].with_separator(Some(2));
+
+        assert_eq!(
+            matrix.export(ExportFormat::Typst),
+            r#"mat(delim: "[", augment: #2, 1, 2, 3; 4, 50, 6)"#
+        );
+        assert_eq!(
+            matrix.to_mathml(),
+            concat!(
+                r#"<mrow><mo>[</mo><mtable columnlines="none solid">"#,
+                "<mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd><mtd><mn>3</mn></mtd></mtr>",
+                "<mtr><mtd><mn>4</mn></mtd><mtd><mn>50</mn></mtd><mtd><mn>6</mn></mtd></mtr>",
+                "</mtable><mo>]</mo></mrow>"
+            )
+        );
+        assert_eq!(
+            matrix.export(ExportFormat::Ascii),
+            "┌           ┐\n│ 1   2 │ 3 │\n│ 4  50 │ 6 │\n└           ┘"
+        );
+    }
+
     #[test]
     fn test_new_unsafe() {
         let matrix = Matrix::new_unsafe(vec![vec![1, 2, 3], vec![4, 5, 6]]);
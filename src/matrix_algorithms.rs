@@ -1,31 +1,135 @@
+use crate::errors::Error;
+use crate::locale::Locale;
 use crate::matrices::Matrix;
-use crate::traits::{LaTeXable, MatrixNumber};
-use anyhow::Context;
+use crate::steps::{Notation, RowOperation, Step};
+use crate::traits::{wrap_mathml, ExportFormat, Exportable, LaTeXable, MatrixNumber};
 
 #[derive(Debug, Clone)]
 pub struct Aftermath<T: MatrixNumber> {
     pub result: Matrix<T>,
-    pub steps: Vec<String>,
+    /// Matrix the steps start from.
+    pub initial: Matrix<T>,
+    pub steps: Vec<Step<T>>,
+}
+
+impl<T: MatrixNumber> Aftermath<T> {
+    /// Inserts a single echelonization step with operation `operation`
+    /// based on pivot `pivot` and matrix containing `data` with separator
+    /// `separator`.
+    fn push_step(
+        &mut self,
+        operation: RowOperation<T>,
+        pivot: (usize, usize),
+        data: Vec<Vec<T>>,
+        separator: Option<usize>,
+    ) -> Vec<Vec<T>> {
+        let temp_matrix = Matrix::new_unsafe(data).with_separator(separator);
+        self.steps.push(Step {
+            operation,
+            pivot,
+            matrix: temp_matrix.clone(),
+        });
+        temp_matrix.consume()
+    }
+
+    /// Returns the initial matrix followed by all steps, each represented in
+    /// human-friendly LaTeX notation.
+    pub fn latex_steps(&self) -> Vec<String> {
+        self.latex_steps_with(&Notation::default())
+    }
+
+    /// Same as [`Aftermath::latex_steps`], but the row operations are written
+    /// in the given notation.
+    pub fn latex_steps_with(&self, notation: &Notation) -> Vec<String> {
+        self.frames(LaTeXable::to_latex, |step| step.to_latex_with(notation))
+    }
+
+    /// Returns the initial matrix followed by all steps in plain text.
+    pub fn plain_steps(&self) -> Vec<String> {
+        self.frames(|m| m.export(ExportFormat::Ascii), Step::to_plain)
+    }
+
+    /// Returns the descriptions of all steps in the given language.
+    pub fn describe_steps(&self, locale: &Locale) -> Vec<String> {
+        self.steps
+            .iter()
+            .map(|step| step.describe(locale))
+            .collect()
+    }
+
+    fn frames(
+        &self,
+        initial: impl Fn(&Matrix<T>) -> String,
+        step: impl Fn(&Step<T>) -> String,
+    ) -> Vec<String> {
+        if self.initial.is_empty() {
+            return vec![];
+        }
+        std::iter::once(initial(&self.initial))
+            .chain(self.steps.iter().map(step))
+            .collect()
+    }
+
+    /// Exports the derivation in the given format. The steps are available
+    /// in LaTeX, Typst, MathML, Markdown (as LaTeX math blocks) and ASCII,
+    /// CSV only contains the result.
+    pub fn export(&self, format: ExportFormat) -> String {
+        self.export_with(format, &Notation::default())
+    }
+
+    /// Same as [`Aftermath::export`], but the steps are written in the given
+    /// notation.
+    pub fn export_with(&self, format: ExportFormat, notation: &Notation) -> String {
+        match format {
+            ExportFormat::LaTeX => self.latex_steps_with(notation).join("\n"),
+            ExportFormat::Typst => self
+                .frames(Exportable::to_typst, |step| step.to_typst_with(notation))
+                .join("\n"),
+            ExportFormat::MathML => wrap_mathml(
+                &self
+                    .frames(Exportable::to_mathml, |step| step.to_mathml_with(notation))
+                    .concat(),
+            ),
+            ExportFormat::Markdown => self
+                .latex_steps_with(notation)
+                .iter()
+                .map(|step| format!("$$\n{step}\n$$"))
+                .chain(std::iter::once(self.result.export(format)))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            ExportFormat::Ascii => self
+                .plain_steps()
+                .into_iter()
+                .chain(std::iter::once(self.result.export(format)))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            ExportFormat::Csv => self.result.export(format),
+        }
+    }
 }
 
 impl<T: MatrixNumber> Matrix<T> {
     /// Returns a copy of the matrix which is in the row echelon form along
-    /// with all steps represented in human-friendly LaTeX notation.
+    /// with all steps (row operations and intermediate matrices).
     /// Uses Gaussian elimination combined with some heuristics aiming at
     /// making the number of steps as small as possible.
     pub fn echelon(&self) -> anyhow::Result<Aftermath<T>> {
-        const CONTEXT: &str = "Calculations error!";
-
         if self.is_empty() {
             return Ok(Aftermath {
                 result: self.clone(),
+                initial: self.clone(),
                 steps: vec![],
             });
         }
 
         let (rows, cols) = self.get_shape();
+        let separator = self.get_separator();
 
-        let mut steps = vec![self.to_latex()];
+        let mut aftermath = Aftermath {
+            result: self.clone(),
+            initial: self.clone(),
+            steps: vec![],
+        };
         let mut c = 0;
         let mut i = 0;
         let mut data = self.deep_matrix_data_clone();
@@ -33,8 +137,8 @@ impl<T: MatrixNumber> Matrix<T> {
         while c < cols && i < rows {
             let mut j = i;
             for k in i + 1..rows {
-                if Self::nice(&data[k][c]).context(CONTEXT)?
-                    < Self::nice(&data[j][c]).context(CONTEXT)?
+                if Self::nice(&data[k][c]).ok_or(Error::CalculationFailed)?
+                    < Self::nice(&data[j][c]).ok_or(Error::CalculationFailed)?
                 {
                     j = k;
                 }
@@ -43,54 +147,59 @@ impl<T: MatrixNumber> Matrix<T> {
             if !data[j][c].is_zero() {
                 if i != j {
                     data.swap(i, j);
-                    data = Self::push_step(
-                        &mut steps,
-                        format!(r"w_{{{}}} \leftrightarrow w_{{{}}}", i + 1, j + 1).as_str(),
-                        data,
-                        self.get_separator(),
-                    );
+                    data = aftermath.push_step(RowOperation::Swap(i, j), (i, c), data, separator);
                 }
 
                 if !data[i][c].is_one() {
                     let d = data[i][c].clone();
-                    for j in c..cols {
-                        data[i][j] = data[i][j].checked_div(&d).context(CONTEXT)?;
+                    for elem in data[i].iter_mut().skip(c) {
+                        *elem = elem.checked_div(&d).ok_or(Error::CalculationFailed)?;
                     }
 
-                    data = Self::push_step(
-                        &mut steps,
-                        format!(r"w_{{{}}} : {}", i + 1, d.to_latex_single()).as_str(),
+                    data = aftermath.push_step(
+                        RowOperation::Scale { row: i, divisor: d },
+                        (i, c),
                         data,
-                        self.get_separator(),
+                        separator,
                     );
                 }
 
-                let mut step_ops: Vec<String> = Vec::new();
+                let mut step_ops = Vec::new();
                 for j in 0..rows {
                     if j != i && !data[j][c].is_zero() {
-                        let p = data[j][c].checked_div(&data[i][c]).context(CONTEXT)?;
-                        for k in c..cols {
-                            data[j][k] = data[j][k]
-                                .checked_sub(&data[i][k].checked_mul(&p).context(CONTEXT)?)
-                                .context(CONTEXT)?;
+                        let p = data[j][c]
+                            .checked_div(&data[i][c])
+                            .ok_or(Error::CalculationFailed)?;
+                        let pivot_row = data[i].clone();
+                        for (elem, pivot_elem) in data[j].iter_mut().zip(pivot_row).skip(c) {
+                            *elem = elem
+                                .checked_sub(
+                                    &pivot_elem.checked_mul(&p).ok_or(Error::CalculationFailed)?,
+                                )
+                                .ok_or(Error::CalculationFailed)?;
+                            // rounding errors of approximate numbers are dropped
+                            if elem.is_zero() {
+                                *elem = T::zero();
+                            }
                         }
 
-                        step_ops.push(format!(
-                            "w_{{{}}} {}w_{{{}}}",
-                            j + 1,
-                            Self::sub_coefficient_to_latex(&p).context(CONTEXT)?,
-                            i + 1
-                        ));
+                        step_ops.push(RowOperation::AddMultiple {
+                            target: j,
+                            source: i,
+                            coefficient: T::zero()
+                                .checked_sub(&p)
+                                .ok_or(Error::CalculationFailed)?,
+                        });
                     }
                 }
 
-                if !step_ops.is_empty() {
-                    data = Self::push_step(
-                        &mut steps,
-                        format!(r"\substack{{{}}}", &step_ops.join(r"\\")).as_str(),
-                        data,
-                        self.get_separator(),
-                    );
+                let operation = match step_ops.len() {
+                    0 => None,
+                    1 => step_ops.pop(),
+                    _ => Some(RowOperation::Combined(step_ops)),
+                };
+                if let Some(operation) = operation {
+                    data = aftermath.push_step(operation, (i, c), data, separator);
                 }
 
                 i += 1;
@@ -99,34 +208,32 @@ impl<T: MatrixNumber> Matrix<T> {
             c += 1;
         }
 
-        Ok(Aftermath {
-            result: Self::new_unsafe(data),
-            steps,
-        })
+        aftermath.result = Self::new_unsafe(data);
+        Ok(aftermath)
     }
 
-    /// Returns the inverse of the matrix along with all steps represented in
-    /// human-friendly LaTeX notation.
+    /// Returns the inverse of the matrix along with all steps of the
+    /// elimination performed on the matrix extended with the identity.
     pub fn inverse(&self) -> anyhow::Result<Aftermath<T>> {
         if self.is_empty() {
-            anyhow::bail!("Cannot calculate inverse of an empty matrix!");
+            anyhow::bail!(Error::EmptyInverse);
         }
 
         let (rows, cols) = self.get_shape();
         if rows != cols {
-            anyhow::bail!("Matrix is not square!");
+            anyhow::bail!(Error::NotSquare);
         }
 
         let extended_matrix = self.clone().concat(Matrix::identity(rows))?;
         let echelon_aftermath = extended_matrix.with_separator(Some(rows)).echelon()?;
         let (left, right) = echelon_aftermath.result.split(rows)?;
         if left != Matrix::identity(rows) {
-            anyhow::bail!("Matrix is not invertible!");
+            anyhow::bail!(Error::NotInvertible);
         }
 
         Ok(Aftermath {
             result: right,
-            steps: echelon_aftermath.steps,
+            ..echelon_aftermath
         })
     }
 
@@ -145,29 +252,60 @@ impl<T: MatrixNumber> Matrix<T> {
         Self::new_unsafe(data)
     }
 
+    /// Returns the determinant of a square matrix, computed with the
+    /// fraction-free Bareiss algorithm, so every division is exact also for
+    /// integers.
+    pub fn determinant(&self) -> anyhow::Result<T> {
+        let (rows, cols) = self.get_shape();
+        if rows != cols {
+            anyhow::bail!(Error::NotSquare);
+        }
+
+        let mut data = self.deep_matrix_data_clone();
+        let mut negated = false;
+        let mut previous = T::one();
+        for k in 0..rows {
+            if data[k][k].is_zero() {
+                match (k + 1..rows).find(|&i| !data[i][k].is_zero()) {
+                    Some(i) => {
+                        data.swap(i, k);
+                        negated = !negated;
+                    }
+                    None => return Ok(T::zero()),
+                }
+            }
+            for i in k + 1..rows {
+                for j in k + 1..rows {
+                    data[i][j] = data[i][j]
+                        .checked_mul(&data[k][k])
+                        .zip(data[i][k].checked_mul(&data[k][j]))
+                        .and_then(|(a, b)| a.checked_sub(&b))
+                        .and_then(|x| x.checked_div(&previous))
+                        .ok_or_else(T::checked_ops_error)?;
+                }
+            }
+            previous = data[k][k].clone();
+        }
+
+        match negated {
+            true => Ok(T::zero()
+                .checked_sub(&previous)
+                .ok_or_else(T::checked_ops_error)?),
+            false => Ok(previous),
+        }
+    }
+
+    /// Checks whether the matrix is square and equal to its transpose.
+    pub fn is_symmetric(&self) -> bool {
+        let (rows, cols) = self.get_shape();
+        rows == cols && *self == self.transpose()
+    }
+
     /// Returns a deep copy of matrix data vector.
     fn deep_matrix_data_clone(&self) -> Vec<Vec<T>> {
         self.get_data().iter().map(|row| row.to_vec()).collect()
     }
 
-    /// Inserts the LaTeX representation of a single echelonization step with
-    /// transitions `transitions` and matrix containing `data` with separator
-    /// `separator` into `steps`.
-    fn push_step(
-        steps: &mut Vec<String>,
-        transitions: &str,
-        data: Vec<Vec<T>>,
-        separator: Option<usize>,
-    ) -> Vec<Vec<T>> {
-        let temp_matrix = Self::new_unsafe(data).with_separator(separator);
-        steps.push(format!(
-            r"\xrightarrow{{{}}} {}",
-            transitions,
-            temp_matrix.to_latex(),
-        ));
-        temp_matrix.consume()
-    }
-
     /// Returns an integer representing how nice a row starting with the given
     /// coefficient is to be used in a step of Gaussian elimination. The smaller
     /// value means the better choice.
@@ -186,38 +324,18 @@ impl<T: MatrixNumber> Matrix<T> {
             Some(2)
         }
     }
-
-    /// How the substraction of an identifier multiplied by the given coefficient
-    /// should be printed in LaTeX.
-    /// For example, `sub_coefficient_to_latex(1)` returns `- `, as it is not
-    /// necessary to write `- 1w`, but `- w` is sufficient and
-    /// `sub_coefficient_to_latex(-5)` returns `+ 5`, because `+ 5w` is easier
-    /// to read than `- (-5)w`.
-    /// Assumes that the coefficient is nonzero.
-    fn sub_coefficient_to_latex(coefficient: &T) -> Option<String> {
-        if coefficient.is_one() {
-            Some("- ".to_string())
-        } else if (T::zero().checked_sub(coefficient)?).is_one() {
-            Some("+ ".to_string())
-        } else if coefficient.is_positive() {
-            Some(format!("- {}", coefficient.to_latex()))
-        } else if coefficient.is_negative() {
-            Some(format!(
-                "+ {}",
-                (T::zero().checked_sub(coefficient)?.to_latex())
-            ))
-        } else {
-            unreachable!("Should not be used for zero coefficient!")
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::traits::LaTeXable;
-    use crate::{matrices::Matrix, ri, rm, rv};
+    use crate::errors::Error;
+    use crate::float::Float64;
+    use crate::locale::{Language, Locale};
+    use crate::steps::{Notation, RowOperation};
+    use crate::traits::{ExportFormat, LaTeXable};
+    use crate::{fm, im, matrices::Matrix, ri, rm, rv};
     use num_rational::Rational64;
-    use num_traits::One;
+    use num_traits::{One, Zero};
 
     #[test]
     fn test_echelon_rational1() {
@@ -228,7 +346,7 @@ mod tests {
 
         assert_eq!(aftermath.result.to_latex(), expected.to_latex());
         assert_eq!(
-            aftermath.steps,
+            aftermath.latex_steps(),
             vec![
                 r"\left[\begin{array}{cc}-2 & 1\\1 & 1\end{array}\right]",
                 r"\xrightarrow{w_{1} \leftrightarrow w_{2}} \left[\begin{array}{cc}1 & 1\\-2 & 1\end{array}\right]",
@@ -248,7 +366,7 @@ mod tests {
 
         assert_eq!(aftermath.result.to_latex(), expected.to_latex());
         assert_eq!(
-            aftermath.steps,
+            aftermath.latex_steps(),
             vec![
                 r"\left[\begin{array}{cc}4 & 3\\2 & 1\end{array}\right]",
                 r"\xrightarrow{w_{1} : 4} \left[\begin{array}{cc}1 & \frac{3}{4}\\2 & 1\end{array}\right]",
@@ -267,7 +385,7 @@ mod tests {
 
         assert_eq!(aftermath.result.to_latex(), id.to_latex());
         assert_eq!(
-            aftermath.steps,
+            aftermath.latex_steps(),
             vec![r"\left[\begin{array}{cc}1 & 0\\0 & 1\end{array}\right]"]
         );
     }
@@ -281,7 +399,7 @@ mod tests {
 
         assert_eq!(aftermath.result.to_latex(), expected.to_latex());
         assert_eq!(
-            aftermath.steps,
+            aftermath.latex_steps(),
             vec![
                 r"\left[\begin{array}{ccc}1 & -1 & 1\\1 & 1 & -1\\-1 & 1 & -1\end{array}\right]",
                 r"\xrightarrow{\substack{w_{2} - w_{1}\\w_{3} + w_{1}}} \left[\begin{array}{ccc}1 & -1 & 1\\0 & 2 & -2\\0 & 0 & 0\end{array}\right]",
@@ -304,7 +422,7 @@ mod tests {
 
         assert_eq!(aftermath.result.to_latex(), expected.to_latex());
         assert_eq!(
-            aftermath.steps,
+            aftermath.latex_steps(),
             vec![
                 r"\left[\begin{array}{cc|cc}1 & 2 & 1 & 0\\3 & 4 & 0 & 1\end{array}\right]",
                 r"\xrightarrow{\substack{w_{2} - 3w_{1}}} \left[\begin{array}{cc|cc}1 & 2 & 1 & 0\\0 & -2 & -3 & 1\end{array}\right]",
@@ -314,6 +432,184 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_echelon_structured_steps() {
+        let m = rm![-2, 1; 1, 1];
+
+        let aftermath = m.echelon().unwrap();
+        let operations: Vec<_> = aftermath
+            .steps
+            .iter()
+            .map(|step| (step.operation.clone(), step.pivot))
+            .collect();
+
+        assert_eq!(
+            operations,
+            vec![
+                (RowOperation::Swap(0, 1), (0, 0)),
+                (
+                    RowOperation::AddMultiple {
+                        target: 1,
+                        source: 0,
+                        coefficient: ri!(2)
+                    },
+                    (0, 0)
+                ),
+                (
+                    RowOperation::Scale {
+                        row: 1,
+                        divisor: ri!(3)
+                    },
+                    (1, 1)
+                ),
+                (
+                    RowOperation::AddMultiple {
+                        target: 0,
+                        source: 1,
+                        coefficient: ri!(-1)
+                    },
+                    (1, 1)
+                ),
+            ]
+        );
+        assert_eq!(aftermath.initial, m);
+        assert_eq!(aftermath.steps[1].matrix, rm![1, 1; 0, 3]);
+        assert_eq!(aftermath.steps.last().unwrap().matrix, aftermath.result);
+    }
+
+    #[test]
+    fn test_echelon_combined_step() {
+        let aftermath = rm![1, -1, 1; 1, 1, -1; -1, 1, -1].echelon().unwrap();
+
+        assert_eq!(
+            aftermath.steps[0].operation,
+            RowOperation::Combined(vec![
+                RowOperation::AddMultiple {
+                    target: 1,
+                    source: 0,
+                    coefficient: ri!(-1)
+                },
+                RowOperation::AddMultiple {
+                    target: 2,
+                    source: 0,
+                    coefficient: ri!(1)
+                },
+            ])
+        );
+        assert_eq!(
+            aftermath.describe_steps(&Locale::new(Language::default())),
+            vec![
+                "Subtract row 1 from row 2; Add row 1 to row 3",
+                "Divide row 2 by 2",
+                "Add row 2 to row 1",
+            ]
+        );
+        assert_eq!(aftermath.plain_steps().len(), 4);
+        assert!(aftermath.plain_steps()[1].starts_with("w2 - w1, w3 + w1\n"));
+    }
+
+    #[test]
+    fn test_inverse_steps() {
+        let aftermath = rm![1, 2; 3, 4].inverse().unwrap();
+        let last = &aftermath.steps.last().unwrap().matrix;
+
+        assert_eq!(last.get_separator(), Some(2));
+        assert_eq!(last.clone().split(2).unwrap().1, aftermath.result);
+        assert_eq!(aftermath.steps[0].operation.modified_rows(), vec![1]);
+        assert_eq!(aftermath.steps[0].operation.to_plain(), "w2 - 3 w1");
+    }
+
+    #[test]
+    fn test_aftermath_export() {
+        let aftermath = rm![2, 4; 0, 1].echelon().unwrap();
+
+        assert_eq!(
+            aftermath.export(ExportFormat::LaTeX),
+            aftermath.latex_steps().join("\n")
+        );
+        assert_eq!(aftermath.export(ExportFormat::Csv), "1,0\n0,1");
+        let typst = aftermath.export(ExportFormat::Typst);
+        assert!(
+            typst.starts_with("mat(delim: \"[\", 2, 4; 0, 1)\nlimits(arrow.r.long)^(w_(1) : 2)")
+        );
+        assert_eq!(typst.lines().count(), aftermath.steps.len() + 1);
+        let mathml = aftermath.export(ExportFormat::MathML);
+        assert!(mathml.starts_with("<math"));
+        assert_eq!(mathml.matches("<mover>").count(), aftermath.steps.len());
+        assert!(aftermath
+            .export(ExportFormat::Markdown)
+            .starts_with(&format!("$$\n{}\n$$\n\n", aftermath.latex_steps()[0])));
+        assert!(aftermath
+            .export(ExportFormat::Ascii)
+            .contains("\n\nw1 : 2\n"));
+    }
+
+    #[test]
+    fn test_localized_steps() {
+        let aftermath = rm![0, 1; 2, 0].inverse().unwrap();
+        let polish = Locale::new(Language::parse("pl").unwrap());
+        let english = Locale::new(Language::default());
+
+        let steps = aftermath.latex_steps_with(&Notation::new(&polish, false));
+        assert!(steps[1].starts_with(r"\xrightarrow{w_{1} \leftrightarrow w_{2}}"));
+        let steps = aftermath.latex_steps_with(&Notation::new(&english, true));
+        assert!(
+            steps[1].starts_with(r"\xrightarrow[\text{swap rows}]{r_{1} \leftrightarrow r_{2}}")
+        );
+        assert!(steps[2].starts_with(r"\xrightarrow[\text{divide row by}]{r_{1} : 2}"));
+        assert_eq!(
+            aftermath.export_with(ExportFormat::LaTeX, &Notation::new(&polish, false)),
+            aftermath.export(ExportFormat::LaTeX)
+        );
+    }
+
+    #[test]
+    fn test_echelon_float_residues() {
+        let m = fm![0.1, 0.2, 0.3; 0.4, 0.5, 0.6; 0.7, 0.8, 0.9];
+
+        let echelon = m.echelon().unwrap().result;
+        assert_eq!(echelon, fm![1., 0., -1.; 0., 1., 2.; 0., 0., 0.]);
+        assert!(echelon.get_data()[2][2].is_zero());
+        assert_eq!(
+            m.inverse().unwrap_err().downcast_ref::<Error>(),
+            Some(&Error::NotInvertible)
+        );
+    }
+
+    #[test]
+    fn test_inverse_float_ill_conditioned() {
+        let n = 4;
+        let hilbert = Matrix::filled((n, n), |i, j| Float64::from(1. / (i + j + 1) as f64));
+
+        let inverse = hilbert.inverse().unwrap().result;
+        let product = hilbert.checked_mul(&inverse).unwrap();
+        for i in 0..n {
+            for j in 0..n {
+                let expected = if i == j { 1. } else { 0. };
+                assert!((f64::from(product.get_data()[i][j]) - expected).abs() < 1e-9);
+            }
+        }
+        assert_eq!(inverse.get_data()[0][0], Float64::from(16.));
+        assert_eq!(inverse.get_data()[3][3], Float64::from(2800.));
+    }
+
+    #[test]
+    fn test_determinant() {
+        assert_eq!(im![2, 1; 7, 4].determinant().unwrap(), 1);
+        assert_eq!(im![0, 1, 2; 1, 0, 3; 4, -3, 8].determinant().unwrap(), -2);
+        assert_eq!(im![1, 2, 3; 4, 5, 6; 7, 8, 9].determinant().unwrap(), 0);
+        assert_eq!(
+            rm![1, 2; 3, 4]
+                .checked_mul_scl(&Rational64::new(1, 2))
+                .unwrap()
+                .determinant()
+                .unwrap(),
+            Rational64::new(-1, 2)
+        );
+        assert_eq!(Matrix::<i64>::empty().determinant().unwrap(), 1);
+        assert!(im![1, 2].determinant().is_err());
+    }
+
     #[test]
     fn test_transpose_rational1() {
         let m = rm![1, 2; 3, 4];
@@ -0,0 +1,315 @@
+use crate::constants::MAX_DENMAN_BEAVERS_ITERATIONS;
+use crate::decompositions::{Derivation, Equation, Formula};
+use crate::errors::Error;
+use crate::float::Float64;
+use crate::matrices::Matrix;
+use crate::traits::MatrixNumber;
+use anyhow::bail;
+use num_traits::checked_pow;
+
+/// Degree of the diagonal Padé approximant of the exponential.
+const PADE_DEGREE: usize = 6;
+
+/// How [`Matrix::checked_pow_derivation`] derives the power of a matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerMethod {
+    /// Binary exponentiation - the repeated squares `A^{2}, A^{4}, ...`
+    /// multiplied according to the binary expansion of the exponent.
+    Squaring,
+    /// The closed form `A^{k} = PD^{k}P^{-1}` of a matrix diagonalizable
+    /// over the rationals.
+    ClosedForm,
+}
+
+/// `A^{k}` in LaTeX and in plain text, just `A` for the first power.
+fn power_text(exponent: usize) -> (String, String) {
+    match exponent {
+        1 => ("A".to_string(), "A".to_string()),
+        _ => (format!("A^{{{exponent}}}"), format!("A^{exponent}")),
+    }
+}
+
+fn power_equation<T: MatrixNumber>(
+    exponent: usize,
+    formula: (String, String),
+    value: Option<Matrix<T>>,
+) -> Equation<T> {
+    let (latex, plain) = formula;
+    Equation {
+        symbol: power_text(exponent).0,
+        formula: Some(Formula { latex, plain }),
+        value,
+    }
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Same as [`Matrix::checked_pow`], but also returns the derivation of
+    /// the power computed with the given method. The result is named
+    /// `A^{k}`.
+    pub fn checked_pow_derivation(
+        &self,
+        exponent: usize,
+        method: PowerMethod,
+    ) -> anyhow::Result<Derivation<T>> {
+        let (h, w) = self.get_shape();
+        if h != w {
+            bail!(Error::NonSquarePower);
+        }
+        let (equations, result) = match method {
+            PowerMethod::Squaring => self.squaring_equations(exponent)?,
+            PowerMethod::ClosedForm => self.closed_form_equations(exponent)?,
+        };
+        Ok(Derivation {
+            initial: self.clone(),
+            equations,
+            results: vec![(power_text(exponent).0, result)],
+        })
+    }
+
+    fn squaring_equations(&self, exponent: usize) -> anyhow::Result<(Vec<Equation<T>>, Self)> {
+        if exponent == 0 {
+            return Ok((vec![], Self::identity(self.get_shape().0)));
+        }
+        let mut equations = vec![];
+        let mut squares = vec![(1, self.clone())];
+        while let Some((power, square)) = squares.last().filter(|(power, _)| 2 * power <= exponent)
+        {
+            let (power, next) = (2 * power, square.checked_mul(square)?);
+            let (latex, plain) = power_text(power / 2);
+            equations.push(power_equation(
+                power,
+                (format!("{latex}{latex}"), format!("{plain} {plain}")),
+                Some(next.clone()),
+            ));
+            squares.push((power, next));
+        }
+
+        // the squares of the set bits of the exponent, from the largest
+        let factors: Vec<&(usize, Self)> = squares
+            .iter()
+            .rev()
+            .filter(|(power, _)| exponent & power != 0)
+            .collect();
+        let mut result = factors[0].1.clone();
+        for (_, factor) in &factors[1..] {
+            result = result.checked_mul(factor)?;
+        }
+        if factors.len() > 1 {
+            let (latex, plain): (Vec<_>, Vec<_>) =
+                factors.iter().map(|(power, _)| power_text(*power)).unzip();
+            equations.push(power_equation(
+                exponent,
+                (latex.concat(), plain.join(" ")),
+                None,
+            ));
+        }
+        Ok((equations, result))
+    }
+
+    fn closed_form_equations(&self, exponent: usize) -> anyhow::Result<(Vec<Equation<T>>, Self)> {
+        let mut diagonalization = self.diagonalize()?;
+        let (_, d) = diagonalization
+            .results
+            .pop()
+            .ok_or(Error::CalculationFailed)?;
+        let (_, p) = diagonalization
+            .results
+            .pop()
+            .ok_or(Error::CalculationFailed)?;
+        let p_inverse = p.inverse()?.result;
+        let n = d.get_shape().0;
+        let diagonal = (0..n)
+            .map(|i| {
+                checked_pow(d.get_data()[i][i].clone(), exponent).ok_or_else(T::checked_ops_error)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let d_power = Matrix::filled((n, n), |i, j| match i == j {
+            true => diagonal[i].clone(),
+            false => T::zero(),
+        });
+        let result = p.checked_mul(&d_power)?.checked_mul(&p_inverse)?;
+
+        let value = |symbol: String, value: &Self| Equation {
+            symbol,
+            formula: None,
+            value: Some(value.clone()),
+        };
+        let mut equations = diagonalization.equations;
+        equations.extend([
+            value("P".to_string(), &p),
+            value("D".to_string(), &d),
+            value("P^{-1}".to_string(), &p_inverse),
+            value(format!("D^{{{exponent}}}"), &d_power),
+            power_equation(
+                exponent,
+                (
+                    format!("PD^{{{exponent}}}P^{{-1}}"),
+                    format!("P D^{exponent} P^-1"),
+                ),
+                None,
+            ),
+        ]);
+        Ok((equations, result))
+    }
+}
+
+impl Matrix<Float64> {
+    fn ensure_square(&self) -> anyhow::Result<usize> {
+        match self.get_shape() {
+            (h, w) if h == w => Ok(h),
+            _ => bail!(Error::NotSquare),
+        }
+    }
+
+    /// Returns the matrix exponential `e^{A}`, computed with the diagonal
+    /// Padé approximant and scaling and squaring - the matrix is scaled by
+    /// `2^{-s}` so that its norm is at most `1/2` and the approximated
+    /// exponential is squared `s` times.
+    pub fn expm(&self) -> anyhow::Result<Self> {
+        let n = self.ensure_square()?;
+        let norm = match n {
+            0 => 0.,
+            _ => f64::from(self.norm_inf()?),
+        };
+        let squarings = match norm > 0. {
+            true => (norm.log2().floor() as i64 + 2).max(0) as i32,
+            false => 0,
+        };
+        let scaled = self.checked_mul_scl(&Float64::from(0.5_f64.powi(squarings)))?;
+
+        let (mut numerator, mut denominator) = (Self::identity(n), Self::identity(n));
+        let (mut power, mut coefficient) = (Self::identity(n), 1.);
+        let q = PADE_DEGREE as f64;
+        for k in 1..=PADE_DEGREE {
+            let k_f = k as f64;
+            coefficient *= (q - k_f + 1.) / ((2. * q - k_f + 1.) * k_f);
+            power = scaled.checked_mul(&power)?;
+            let term = power.checked_mul_scl(&Float64::from(coefficient))?;
+            numerator = numerator.checked_add(&term)?;
+            denominator = match k % 2 {
+                0 => denominator.checked_add(&term)?,
+                _ => denominator.checked_sub(&term)?,
+            };
+        }
+
+        let mut result = denominator.inverse()?.result.checked_mul(&numerator)?;
+        for _ in 0..squarings {
+            result = result.checked_mul(&result)?;
+        }
+        Ok(result.snapped())
+    }
+
+    /// Returns the principal square root `X` with `X^2 = A`, computed with
+    /// the Denman-Beavers iteration `Y_{k+1} = (Y_k + Z_k^{-1}) / 2`,
+    /// `Z_{k+1} = (Z_k + Y_k^{-1}) / 2` starting from `Y_0 = A` and
+    /// `Z_0 = I`. Fails if the iteration does not converge, e.g. for
+    /// matrices with negative eigenvalues.
+    pub fn sqrtm(&self) -> anyhow::Result<Self> {
+        let n = self.ensure_square()?;
+        let half = Float64::from(0.5);
+        let (mut y, mut z) = (self.clone(), Self::identity(n));
+        for _ in 0..MAX_DENMAN_BEAVERS_ITERATIONS {
+            let next_y = y
+                .checked_add(&z.inverse()?.result)?
+                .checked_mul_scl(&half)?;
+            let next_z = z
+                .checked_add(&y.inverse()?.result)?
+                .checked_mul_scl(&half)?;
+            if next_y == y {
+                return Ok(next_y.snapped());
+            }
+            (y, z) = (next_y, next_z);
+        }
+        bail!(Error::NotConverged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fm, ri, rm, rv};
+    use num_rational::Rational64;
+
+    #[test]
+    fn test_pow_by_squaring() {
+        let m = rm![1, 1; 1, 0];
+        let derivation = m.checked_pow_derivation(13, PowerMethod::Squaring).unwrap();
+        assert_eq!(
+            derivation.results,
+            vec![("A^{13}".to_string(), rm![377, 233; 233, 144])]
+        );
+        let steps = derivation.latex_steps();
+        assert_eq!(steps.len(), 6);
+        assert!(steps[1].starts_with("A^{2} = AA = "));
+        assert!(steps[3].starts_with("A^{8} = A^{4}A^{4} = "));
+        assert_eq!(steps[4], "A^{13} = A^{8}A^{4}A");
+        assert_eq!(derivation.plain_steps()[4], "A^13 = A^8 A^4 A");
+
+        let power_of_two = m.checked_pow_derivation(4, PowerMethod::Squaring).unwrap();
+        assert_eq!(power_of_two.latex_steps().len(), 4);
+        assert_eq!(power_of_two.results[0].1, m.checked_pow(4).unwrap());
+        let zeroth = m.checked_pow_derivation(0, PowerMethod::Squaring).unwrap();
+        assert_eq!(zeroth.results[0].1, Matrix::identity(2));
+        assert!(rm![1, 2]
+            .checked_pow_derivation(2, PowerMethod::Squaring)
+            .is_err());
+    }
+
+    #[test]
+    fn test_pow_closed_form() {
+        let m = rm![4, 1; 2, 3];
+        let derivation = m
+            .checked_pow_derivation(5, PowerMethod::ClosedForm)
+            .unwrap();
+        assert_eq!(derivation.results[0].1, m.checked_pow(5).unwrap());
+        let steps = derivation.latex_steps();
+        assert_eq!(steps[steps.len() - 2], "A^{5} = PD^{5}P^{-1}");
+        assert!(steps.iter().any(|step| step.starts_with("D^{5} = ")));
+
+        // a Jordan block is not diagonalizable
+        assert!(rm![1, 1; 0, 1]
+            .checked_pow_derivation(3, PowerMethod::ClosedForm)
+            .is_err());
+        let half = Rational64::new(1, 2);
+        let m = Matrix::new(vec![vec![half, ri!(0)], vec![ri!(0), ri!(3)]]).unwrap();
+        assert_eq!(
+            m.checked_pow_derivation(3, PowerMethod::ClosedForm)
+                .unwrap()
+                .results[0]
+                .1,
+            Matrix::new(vec![
+                vec![Rational64::new(1, 8), ri!(0)],
+                vec![ri!(0), ri!(27)]
+            ])
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_expm() {
+        let e = std::f64::consts::E;
+        assert_eq!(fm![0., 0.; 0., 0.].expm().unwrap(), Matrix::identity(2));
+        assert_eq!(
+            fm![1., 0.; 0., -2.].expm().unwrap(),
+            fm![e, 0.; 0., e.powi(-2)]
+        );
+        // nilpotent matrix, e^A = I + A
+        assert_eq!(fm![0., 3.; 0., 0.].expm().unwrap(), fm![1., 3.; 0., 1.]);
+        // rotation by the angle 1
+        assert_eq!(
+            fm![0., -1.; 1., 0.].expm().unwrap(),
+            fm![1_f64.cos(), -1_f64.sin(); 1_f64.sin(), 1_f64.cos()]
+        );
+        assert_eq!(fm![20.].expm().unwrap(), fm![20_f64.exp()]);
+        assert!(fm![1., 2.].expm().is_err());
+    }
+
+    #[test]
+    fn test_sqrtm() {
+        assert_eq!(fm![4., 0.; 0., 9.].sqrtm().unwrap(), fm![2., 0.; 0., 3.]);
+        let a = fm![33., 24.; 48., 57.];
+        assert_eq!(a.sqrtm().unwrap(), fm![5., 2.; 4., 7.]);
+        assert!(fm![-1., 0.; 0., 1.].sqrtm().is_err());
+        assert!(fm![1., 2.].sqrtm().is_err());
+    }
+}
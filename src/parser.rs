@@ -1,29 +1,39 @@
 use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
 
-use anyhow::{bail, Context};
+use anyhow::bail;
 use num_traits::checked_pow;
 
-use crate::environment::{Environment, Identifier, Type};
+use crate::environment::{integer_to_usize, Environment, Identifier, Type};
+use crate::errors::Error;
 use crate::traits::MatrixNumber;
 
+/// Operator of the Hadamard (element-wise) product, written as `.*`.
+const HADAMARD: char = '∘';
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Token {
     Integer(u64),
+    Decimal(String),
+    String(String),
     Identifier(Identifier),
     Operator(char),
     LeftBracket,
     RightBracket,
+    Comma,
 }
 
 impl Display for Token {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Token::Integer(i) => write!(f, "int {i}"),
+            Token::Decimal(d) => write!(f, "decimal {d}"),
+            Token::String(s) => write!(f, "string \"{s}\""),
             Token::Identifier(id) => write!(f, "id {}", id.to_string()),
             Token::Operator(op) => write!(f, "operator \"{op}\""),
             Token::LeftBracket => write!(f, "( bracket"),
             Token::RightBracket => write!(f, ") bracket"),
+            Token::Comma => write!(f, "comma"),
         }
     }
 }
@@ -47,6 +57,12 @@ impl<'a> Tokenizer<'a> {
         } else if self.raw.starts_with(')') {
             self.raw = &self.raw[1..];
             Ok(Some(Token::RightBracket))
+        } else if self.raw.starts_with(',') {
+            self.raw = &self.raw[1..];
+            Ok(Some(Token::Comma))
+        } else if let Some(rest) = self.raw.strip_prefix(".*") {
+            self.raw = rest;
+            Ok(Some(Token::Operator(HADAMARD)))
         } else if self.raw.starts_with(|c| "+-*/^=".contains(c)) {
             let op = self.raw.chars().next().unwrap();
             self.raw = &self.raw[1..];
@@ -56,9 +72,26 @@ impl<'a> Tokenizer<'a> {
                 .raw
                 .find(|c: char| !c.is_ascii_digit())
                 .unwrap_or(self.raw.len());
-            let num = &self.raw[..i];
-            self.raw = &self.raw[i..];
-            Ok(Some(Token::Integer(num.parse::<u64>()?)))
+            let fraction_len = self.raw[i..]
+                .strip_prefix('.')
+                .map(|rest| {
+                    rest.find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(rest.len())
+                })
+                .unwrap_or(0);
+            if fraction_len > 0 {
+                let num = &self.raw[..i + 1 + fraction_len];
+                self.raw = &self.raw[i + 1 + fraction_len..];
+                Ok(Some(Token::Decimal(num.to_string())))
+            } else {
+                let num = &self.raw[..i];
+                self.raw = &self.raw[i..];
+                Ok(Some(Token::Integer(num.parse::<u64>()?)))
+            }
+        } else if let Some(rest) = self.raw.strip_prefix('"') {
+            let i = rest.find('"').ok_or(Error::UnterminatedString)?;
+            self.raw = &rest[i + 1..];
+            Ok(Some(Token::String(rest[..i].to_string())))
         } else if let Some(rest) = self.raw.strip_prefix(Identifier::RESULT) {
             self.raw = rest;
             Ok(Some(Token::Identifier(Identifier::result())))
@@ -77,22 +110,25 @@ impl<'a> Tokenizer<'a> {
 #[derive(Clone, PartialEq, Eq)]
 enum WorkingToken<T: MatrixNumber> {
     Type(Type<T>),
-    Function(Identifier),
+    /// Function with the number of its arguments.
+    Function(Identifier, usize),
     UnaryOp(char),
     BinaryOp(char),
     LeftBracket,
     RightBracket,
+    Comma,
 }
 
 impl<T: MatrixNumber> Display for WorkingToken<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             WorkingToken::Type(_) => write!(f, "value token"),
-            WorkingToken::Function(_) => write!(f, "function token"),
+            WorkingToken::Function(..) => write!(f, "function token"),
             WorkingToken::UnaryOp(op) => write!(f, "unary operator \"{op}\""),
             WorkingToken::BinaryOp(op) => write!(f, "binary operator \"{op}\""),
             WorkingToken::LeftBracket => write!(f, "( bracket"),
             WorkingToken::RightBracket => write!(f, ") bracket"),
+            WorkingToken::Comma => write!(f, "comma"),
         }
     }
 }
@@ -102,12 +138,12 @@ fn binary_op<T: MatrixNumber>(left: Type<T>, right: Type<T>, op: char) -> anyhow
         '+' => match (left, right) {
             (Type::Matrix(l), Type::Matrix(r)) => Type::from_matrix_result(l.checked_add(&r)),
             (Type::Scalar(l), Type::Scalar(r)) => Type::from_scalar_option(l.checked_add(&r)),
-            _ => bail!("Adding scalar to matrix is not supported!"),
+            _ => bail!(Error::ScalarMatrixAddition),
         },
         '-' => match (left, right) {
             (Type::Matrix(l), Type::Matrix(r)) => Type::from_matrix_result(l.checked_sub(&r)),
             (Type::Scalar(l), Type::Scalar(r)) => Type::from_scalar_option(l.checked_sub(&r)),
-            _ => bail!("Substraction of scalar and matrix is not supported!"),
+            _ => bail!(Error::ScalarMatrixSubtraction),
         },
         '*' => match (left, right) {
             (Type::Matrix(l), Type::Matrix(r)) => Type::from_matrix_result(l.checked_mul(&r)),
@@ -115,35 +151,30 @@ fn binary_op<T: MatrixNumber>(left: Type<T>, right: Type<T>, op: char) -> anyhow
             (Type::Matrix(l), Type::Scalar(r)) => Type::from_matrix_result(l.checked_mul_scl(&r)),
             (Type::Scalar(l), Type::Matrix(r)) => Type::from_matrix_result(r.checked_mul_scl(&l)),
         },
+        HADAMARD => match (left, right) {
+            (Type::Matrix(l), Type::Matrix(r)) => Type::from_matrix_result(l.hadamard(&r)),
+            (left, right) => binary_op(left, right, '*'),
+        },
         '/' => match (left, right) {
             (Type::Scalar(l), Type::Scalar(r)) => {
                 if !r.is_zero() {
                     Type::from_scalar_option(l.checked_div(&r))
                 } else {
-                    bail!("Division by zero!")
+                    bail!(Error::DivisionByZero)
                 }
             }
-            (Type::Matrix(_), Type::Matrix(_)) => {
-                bail!("WTF dividing by matrix? You should use the `inverse` function instead!")
-            }
-            (Type::Matrix(_), Type::Scalar(_)) => {
-                bail!("Diving matrix by scalar is not supported yet...")
-            }
-            (Type::Scalar(_), Type::Matrix(_)) => {
-                bail!("Diving scalar by matrix does not make sense!")
-            }
+            (Type::Matrix(_), Type::Scalar(_)) => bail!(Error::MatrixByScalarDivision),
+            (_, Type::Matrix(_)) => bail!(Error::DivisionByMatrix),
         },
         '^' => {
             if let Type::Scalar(exp) = right {
-                let exp = exp
-                    .to_usize()
-                    .context("Exponent should be a nonnegative integer.")?;
+                let exp = integer_to_usize(&exp).ok_or(Error::InvalidExponent)?;
                 match left {
                     Type::Scalar(base) => Type::from_scalar_option(checked_pow(base, exp)),
                     Type::Matrix(base) => Type::from_matrix_result(base.checked_pow(exp)),
                 }
             } else {
-                bail!("Exponent cannot be a matrix!");
+                bail!(Error::MatrixExponent);
             }
         }
         _ => unimplemented!(),
@@ -161,15 +192,48 @@ fn unary_op<T: MatrixNumber>(arg: Type<T>, op: char) -> anyhow::Result<Type<T>>
     }
 }
 
+/// Converts a decimal literal (e.g. `12.345`) into `T`. If `T` cannot parse
+/// it directly, the value is computed as a fraction of integers, so it stays
+/// exact in exact fields.
+fn parse_decimal<T: MatrixNumber>(raw: &str) -> anyhow::Result<T> {
+    if let Ok(value) = T::from_str(raw) {
+        return Ok(value);
+    }
+
+    let error = || Error::NumberConversion {
+        number: format!("{raw:?}"),
+        type_name: format!("{:?}", std::any::type_name::<T>()),
+    };
+    let (integer, fraction) = raw.split_once('.').ok_or_else(error)?;
+    let numerator = format!("{integer}{fraction}")
+        .parse::<u64>()
+        .ok()
+        .and_then(T::from_u64)
+        .ok_or_else(error)?;
+    let denominator = 10_u64
+        .checked_pow(fraction.len() as u32)
+        .and_then(T::from_u64)
+        .ok_or_else(error)?;
+    let value = numerator.checked_div(&denominator).ok_or_else(error)?;
+    // Integer types would silently truncate the value.
+    if value.checked_mul(&denominator).ok_or_else(error)? != numerator {
+        bail!(error());
+    }
+    Ok(value)
+}
+
 /*
 <digit>      ::= "0" | "1" | ... | "9"
 <integer>    ::= <digit>+
+<decimal>    ::= <digit>+ "." <digit>+
+<string>     ::= '"' <any character except '"'>* '"'
 <letter>     ::= "a" | "ą" | "b" | ... | "ż"
 <identifier> ::= (<letter> | "_") (<letter> | <digit> | "_")* | "$"
 <unary_op>   ::= "+" | "-"
-<binary_op>  ::= "+" | "-" | "*" | "/"
-<expr>       ::= <integer> | <identifier> | <expr> <binary_op> <expr>
-               | "(" <expr> ")" | <unary_op> <expr> | <identifier> "(" <expr> ")"
+<binary_op>  ::= "+" | "-" | "*" | ".*" | "/" | "^"
+<expr>       ::= <integer> | <decimal> | <identifier> | <expr> <binary_op> <expr>
+               | "(" <expr> ")" | <unary_op> <expr> | <identifier> "(" <expr> ("," <expr>)* ")"
+               | <identifier> "(" <string> ")"
  */
 pub fn parse_expression<T: MatrixNumber>(
     raw: &str,
@@ -181,9 +245,9 @@ pub fn parse_expression<T: MatrixNumber>(
     let mut prev_token = None;
 
     fn precedence(c: &char) -> u8 {
-        match c {
+        match *c {
             '+' | '-' => 0,
-            '*' | '/' => 1,
+            '*' | HADAMARD | '/' => 1,
             '^' => 2,
             _ => unreachable!(),
         }
@@ -194,59 +258,87 @@ pub fn parse_expression<T: MatrixNumber>(
         current: &Token,
     ) -> bool {
         match current {
-            Token::Integer(_) | Token::Identifier(_) | Token::LeftBracket => matches!(
-                previous,
-                None | Some(WorkingToken::LeftBracket)
-                    | Some(WorkingToken::BinaryOp(_))
-                    | Some(WorkingToken::UnaryOp(_))
-                    | Some(WorkingToken::Function(_))
-            ),
+            Token::Integer(_) | Token::Decimal(_) | Token::Identifier(_) | Token::LeftBracket => {
+                matches!(
+                    previous,
+                    None | Some(WorkingToken::LeftBracket)
+                        | Some(WorkingToken::BinaryOp(_))
+                        | Some(WorkingToken::UnaryOp(_))
+                        | Some(WorkingToken::Function(..))
+                        | Some(WorkingToken::Comma)
+                )
+            }
             Token::Operator(_) => matches!(
                 previous,
                 None | Some(WorkingToken::RightBracket)
                     | Some(WorkingToken::Type(_))
                     | Some(WorkingToken::BinaryOp(_))
                     | Some(WorkingToken::LeftBracket)
+                    | Some(WorkingToken::Comma)
             ),
-            Token::RightBracket => matches!(
+            Token::RightBracket | Token::Comma => matches!(
                 previous,
                 Some(WorkingToken::RightBracket) | Some(WorkingToken::Type(_))
             ),
+            // strings are consumed together with the text function they are passed to
+            Token::String(_) => false,
         }
     }
 
     while let Some(token) = tokenizer.next_token()? {
         if !validate_neighbours(&prev_token, &token) {
             match prev_token {
-                Some(prev_token) => {
-                    bail!("Invalid expression! The {token} cannot follow {prev_token}")
-                }
-                None => bail!("Invalid expression! The {token} cannot be the first token!"),
+                Some(prev_token) => bail!(Error::UnexpectedToken {
+                    token: token.to_string(),
+                    previous: prev_token.to_string(),
+                }),
+                None => bail!(Error::UnexpectedFirstToken {
+                    token: token.to_string()
+                }),
             }
         }
 
         prev_token = match &token {
             Token::Integer(num) => {
                 outputs.push_back(WorkingToken::Type(Type::Scalar(
-                    T::from_u64(*num).context(format!(
-                        "Number conversion failed! {num:?} cannot be parsed into {:?}",
-                        std::any::type_name::<T>()
-                    ))?,
+                    T::from_u64(*num).ok_or_else(|| Error::NumberConversion {
+                        number: format!("{num:?}"),
+                        type_name: format!("{:?}", std::any::type_name::<T>()),
+                    })?,
                 )));
                 outputs.back()
             }
+            Token::Decimal(num) => {
+                outputs.push_back(WorkingToken::Type(Type::Scalar(parse_decimal(num)?)));
+                outputs.back()
+            }
+            Token::String(_) => unreachable!(),
             Token::Identifier(id) => {
                 if let Some(value) = env.get_value(id) {
                     outputs.push_back(WorkingToken::Type(value.clone()));
                     outputs.back()
                 } else if env.get_function(id).is_some() {
-                    operators.push_front(WorkingToken::Function(id.clone()));
+                    operators.push_front(WorkingToken::Function(id.clone(), 1));
                     operators.front()
+                } else if let Some(function) = env.get_text_function(id) {
+                    let argument = match (
+                        tokenizer.next_token()?,
+                        tokenizer.next_token()?,
+                        tokenizer.next_token()?,
+                    ) {
+                        (
+                            Some(Token::LeftBracket),
+                            Some(Token::String(argument)),
+                            Some(Token::RightBracket),
+                        ) => argument,
+                        _ => bail!(Error::TextFunctionArgument {
+                            function: id.to_string()
+                        }),
+                    };
+                    outputs.push_back(WorkingToken::Type(function(&argument, env)?));
+                    outputs.back()
                 } else {
-                    bail!(
-                        "Undefined identifier! Object \"{}\" is unknown.",
-                        id.to_string()
-                    )
+                    bail!(Error::UndefinedIdentifier { id: id.to_string() })
                 }
             }
             Token::LeftBracket => {
@@ -263,11 +355,11 @@ pub fn parse_expression<T: MatrixNumber>(
                     outputs.push_back(op);
                 }
                 if !left_found {
-                    bail!("Mismatched brackets!");
+                    bail!(Error::MismatchedBrackets);
                 }
                 if let Some(op) = operators.pop_front() {
                     match op {
-                        WorkingToken::UnaryOp(_) | WorkingToken::Function(_) => {
+                        WorkingToken::UnaryOp(_) | WorkingToken::Function(..) => {
                             outputs.push_back(op)
                         }
                         _ => operators.push_front(op),
@@ -275,20 +367,34 @@ pub fn parse_expression<T: MatrixNumber>(
                 }
                 Some(&WorkingToken::RightBracket)
             }
+            Token::Comma => {
+                // the arguments are separated by the comma, so the previous
+                // one is finished
+                while !matches!(operators.front(), Some(WorkingToken::LeftBracket)) {
+                    outputs.push_back(operators.pop_front().ok_or(Error::MismatchedBrackets)?);
+                }
+                match operators.get_mut(1) {
+                    Some(WorkingToken::Function(_, arguments)) => *arguments += 1,
+                    _ => bail!(Error::InvalidExpression),
+                }
+                Some(&WorkingToken::Comma)
+            }
             Token::Operator(op)
                 if matches!(
                     prev_token,
-                    None | Some(WorkingToken::LeftBracket) | Some(WorkingToken::BinaryOp(_))
+                    None | Some(WorkingToken::LeftBracket)
+                        | Some(WorkingToken::BinaryOp(_))
+                        | Some(WorkingToken::Comma)
                 ) =>
             {
                 if "+-".contains(*op) {
                     operators.push_front(WorkingToken::UnaryOp(*op));
                     operators.front()
                 } else {
-                    bail!("Operator {op} cannot be used as a unary operator.")
+                    bail!(Error::InvalidUnaryOperator { operator: *op })
                 }
             }
-            Token::Operator(op) if "+-*/^".contains(*op) => {
+            Token::Operator(op) if "+-*/^".contains(*op) || *op == HADAMARD => {
                 while let Some(stack_token) = operators.pop_front() {
                     if let WorkingToken::BinaryOp(stack_op) = stack_token {
                         if precedence(&stack_op) >= precedence(op) {
@@ -307,13 +413,13 @@ pub fn parse_expression<T: MatrixNumber>(
                 operators.push_front(WorkingToken::BinaryOp(*op));
                 operators.front()
             }
-            Token::Operator(_) => bail!("Assignment is not allowed in expressions!"),
+            Token::Operator(_) => bail!(Error::AssignmentInExpression),
         };
     }
 
     while let Some(token) = operators.pop_front() {
         if matches!(token, WorkingToken::LeftBracket) {
-            bail!("Mismatched brackets!");
+            bail!(Error::MismatchedBrackets);
         }
         outputs.push_back(token);
     }
@@ -323,23 +429,26 @@ pub fn parse_expression<T: MatrixNumber>(
         match token {
             WorkingToken::Type(value) => val_stack.push_front(value),
             WorkingToken::BinaryOp(op) => {
-                let right = val_stack.pop_front().context("Invalid expression!")?;
-                let left = val_stack.pop_front().context("Invalid expression!")?;
+                let right = val_stack.pop_front().ok_or(Error::InvalidExpression)?;
+                let left = val_stack.pop_front().ok_or(Error::InvalidExpression)?;
                 val_stack.push_front(binary_op(left, right, op)?)
             }
             WorkingToken::UnaryOp(op) => {
-                let arg = val_stack.pop_front().context("Invalid expression!")?;
+                let arg = val_stack.pop_front().ok_or(Error::InvalidExpression)?;
                 val_stack.push_front(unary_op(arg, op)?);
             }
-            WorkingToken::Function(id) => {
-                let arg = val_stack.pop_front().context("Invalid expression!")?;
-                val_stack.push_front(env.get_function(&id).unwrap()(arg)?);
+            WorkingToken::Function(id, arguments) => {
+                let mut args = (0..arguments)
+                    .map(|_| val_stack.pop_front().ok_or(Error::InvalidExpression))
+                    .collect::<Result<Vec<_>, _>>()?;
+                args.reverse();
+                val_stack.push_front(env.get_function(&id).unwrap()(args)?);
             }
             _ => unreachable!(),
         }
     }
 
-    val_stack.pop_front().context("Invalid expression!")
+    Ok(val_stack.pop_front().ok_or(Error::InvalidExpression)?)
 }
 
 /*
@@ -365,6 +474,7 @@ pub fn parse_instruction<T: MatrixNumber>(
 
 #[cfg(test)]
 mod tests {
+    use crate::float::Float64;
     use crate::matrices::Matrix;
     use num_rational::Rational64;
 
@@ -523,6 +633,30 @@ mod tests {
         test_expr("C^2", c.clone() * c);
     }
 
+    #[test]
+    fn test_expression_hadamard() {
+        let mut env = Environment::new();
+        env.insert(
+            Identifier::new("A".to_string()).unwrap(),
+            Type::Matrix(im![1, 2; 3, 4]),
+        );
+        env.insert(
+            Identifier::new("B".to_string()).unwrap(),
+            Type::Matrix(im![1, 2]),
+        );
+
+        let test_expr = |raw, expected| {
+            assert_eq!(parse_expression(raw, &env).unwrap(), Type::Matrix(expected))
+        };
+
+        test_expr("A .* A", im![1, 4; 9, 16]);
+        test_expr("A.*A + A", im![2, 6; 12, 20]);
+        test_expr("2 .* A", im![2, 4; 6, 8]);
+        test_expr("B .* B * A", im![13, 18]);
+        assert!(parse_expression("A .* B", &env).is_err());
+        assert!(parse_expression(".* A", &env).is_err());
+    }
+
     #[test]
     fn test_nested_multiplication() {
         let mut env = Environment::new();
@@ -541,6 +675,51 @@ mod tests {
         test_expr("A*A*(A*A)*(A*(A*A))*A*A*A", im![34, 55; 55, 89]);
     }
 
+    #[test]
+    fn test_expression_decimals() {
+        let env = Environment::new();
+        assert_eq!(
+            parse_expression("0.5 + 1.25 * 2", &env).unwrap(),
+            Type::Scalar(Rational64::new(3, 1))
+        );
+        assert_eq!(
+            parse_expression("-0.125", &env).unwrap(),
+            Type::Scalar(Rational64::new(-1, 8))
+        );
+
+        let error = parse_expression("2^0.5", &env).unwrap_err();
+        assert_eq!(error.downcast_ref::<Error>(), Some(&Error::InvalidExponent));
+        assert_eq!(
+            parse_expression("2^2.0", &env).unwrap(),
+            Type::Scalar(Rational64::new(4, 1))
+        );
+        let error = parse_expression("2^0.5", &Environment::<Float64>::new()).unwrap_err();
+        assert_eq!(error.downcast_ref::<Error>(), Some(&Error::InvalidExponent));
+
+        let env = Environment::<i64>::new();
+        assert_eq!(parse_expression("2.00", &env).unwrap(), Type::Scalar(2));
+        assert!(parse_expression("2.5", &env).is_err());
+        assert!(parse_expression("2.", &env).is_err());
+    }
+
+    #[test]
+    fn test_load_function() {
+        let path = std::env::temp_dir().join("jp2gmd_test_load_function.csv");
+        std::fs::write(&path, "1,2\n3,4\n").unwrap();
+
+        let env = Environment::<i64>::new();
+        assert_eq!(
+            parse_expression(&format!("2 * load(\"{}\")", path.display()), &env).unwrap(),
+            Type::Matrix(im![2, 4; 6, 8])
+        );
+        std::fs::remove_file(path).unwrap();
+
+        assert!(parse_expression("load(\"/nonexistent/file.csv\")", &env).is_err());
+        assert!(parse_expression("load(2)", &env).is_err());
+        assert!(parse_expression("load(\"unterminated)", &env).is_err());
+        assert!(parse_expression("\"string\"", &env).is_err());
+    }
+
     #[test]
     fn test_invalid_expressions() {
         let env = Environment::<i64>::new();
@@ -686,4 +865,39 @@ mod tests {
             Type::Matrix(im![2, 4, 6; 8, 10, 12])
         );
     }
+
+    #[test]
+    fn test_multiple_arguments() {
+        let mut env = Environment::new();
+        env.insert(
+            Identifier::new("v".to_string()).unwrap(),
+            Type::Matrix(im![1; 2]),
+        );
+        env.insert(
+            Identifier::new("e".to_string()).unwrap(),
+            Type::Matrix(im![1; 0]),
+        );
+
+        assert_eq!(
+            parse_expression("dot(v, 3 * v) + 1", &env).unwrap(),
+            Type::Scalar(16)
+        );
+        assert_eq!(
+            parse_expression("dot(2 * v, proj(v, (1 + 1) * e - e))", &env).unwrap(),
+            Type::Scalar(2)
+        );
+
+        let error = parse_expression("transpose(v, v)", &env).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<Error>(),
+            Some(&Error::ArgumentCount {
+                expected: 1,
+                given: 2
+            })
+        );
+        assert!(parse_expression("dot(v,, v)", &env).is_err());
+        assert!(parse_expression("dot(v, )", &env).is_err());
+        assert!(parse_expression("1, 2", &env).is_err());
+        assert!(parse_expression("(1, 2)", &env).is_err());
+    }
 }
@@ -0,0 +1,117 @@
+use crate::errors::Error;
+use crate::matrices::Matrix;
+use crate::traits::MatrixNumber;
+use anyhow::bail;
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the Kronecker product `A \otimes B` - the block matrix with
+    /// the blocks `a_{ij} B`.
+    pub fn kronecker(&self, other: &Self) -> anyhow::Result<Self> {
+        let (rows, cols) = self.get_shape();
+        let (other_rows, other_cols) = other.get_shape();
+        let data = (0..rows * other_rows)
+            .map(|i| {
+                (0..cols * other_cols)
+                    .map(|j| {
+                        self.get_data()[i / other_rows][j / other_cols]
+                            .checked_mul(&other.get_data()[i % other_rows][j % other_cols])
+                    })
+                    .collect::<Option<Vec<T>>>()
+            })
+            .collect::<Option<Vec<Vec<T>>>>()
+            .ok_or_else(T::checked_ops_error)?;
+        Ok(Matrix::new_unsafe(data))
+    }
+
+    /// Returns the Hadamard (element-wise) product `A \circ B` of matrices of
+    /// the same shape.
+    pub fn hadamard(&self, other: &Self) -> anyhow::Result<Self> {
+        self.checked_operation_on_two(other, |a, b| a.checked_mul(b))
+    }
+
+    /// Returns the direct sum `A \oplus B` - the block diagonal matrix with
+    /// the blocks `A` and `B`.
+    pub fn direct_sum(&self, other: &Self) -> Self {
+        let (rows, cols) = self.get_shape();
+        let (other_rows, other_cols) = other.get_shape();
+        Matrix::filled((rows + other_rows, cols + other_cols), |i, j| {
+            match (i.checked_sub(rows), j.checked_sub(cols)) {
+                (None, None) => self.get_data()[i][j].clone(),
+                (Some(i), Some(j)) => other.get_data()[i][j].clone(),
+                _ => T::zero(),
+            }
+        })
+    }
+
+    /// Returns the commutator `[A, B] = AB - BA` of square matrices of the
+    /// same shape.
+    pub fn commutator(&self, other: &Self) -> anyhow::Result<Self> {
+        let (rows, cols) = self.get_shape();
+        if rows != cols {
+            bail!(Error::NotSquare);
+        }
+        if !self.same_shapes(other) {
+            bail!(Error::DifferentShapes);
+        }
+        self.checked_mul(other)?
+            .checked_sub(&other.checked_mul(self)?)
+    }
+
+    /// Returns the outer product `uv^T` of column vectors.
+    pub fn outer(&self, other: &Self) -> anyhow::Result<Self> {
+        let (u, v) = (self.column_vector()?, other.column_vector()?);
+        let data = u
+            .iter()
+            .map(|a| v.iter().map(|b| a.checked_mul(b)).collect())
+            .collect::<Option<Vec<Vec<T>>>>()
+            .ok_or_else(T::checked_ops_error)?;
+        Ok(Matrix::new_unsafe(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::im;
+
+    #[test]
+    fn test_kronecker() {
+        assert_eq!(
+            im![1, 2; 3, 4].kronecker(&im![0, 5; 6, 7]).unwrap(),
+            im![0, 5, 0, 10; 6, 7, 12, 14; 0, 15, 0, 20; 18, 21, 24, 28]
+        );
+        assert_eq!(im![1, -1].kronecker(&im![1; 2]).unwrap(), im![1, -1; 2, -2]);
+        assert!(im![i64::MAX].kronecker(&im![2]).is_err());
+    }
+
+    #[test]
+    fn test_hadamard_and_direct_sum() {
+        assert_eq!(
+            im![1, 2; 3, 4].hadamard(&im![5, 6; 7, 8]).unwrap(),
+            im![5, 12; 21, 32]
+        );
+        assert!(im![1, 2].hadamard(&im![1; 2]).is_err());
+        assert!(im![i64::MAX].hadamard(&im![2]).is_err());
+
+        assert_eq!(
+            im![1, 2].direct_sum(&im![3; 4]),
+            im![1, 2, 0; 0, 0, 3; 0, 0, 4]
+        );
+        assert_eq!(Matrix::empty().direct_sum(&im![1]), im![1]);
+    }
+
+    #[test]
+    fn test_commutator_and_outer() {
+        let (a, b) = (im![0, 1; 0, 0], im![0, 0; 1, 0]);
+        assert_eq!(a.commutator(&b).unwrap(), im![1, 0; 0, -1]);
+        assert_eq!(a.commutator(&a).unwrap(), im![0, 0; 0, 0]);
+        assert!(im![1, 2].commutator(&im![1, 2]).is_err());
+        assert!(a.commutator(&im![1]).is_err());
+
+        assert_eq!(
+            im![1; 2].outer(&im![3; 4; 5]).unwrap(),
+            im![3, 4, 5; 6, 8, 10]
+        );
+        assert!(im![1, 2].outer(&im![1; 2]).is_err());
+    }
+}
@@ -0,0 +1,700 @@
+use crate::{
+    errors::Error,
+    float::Float64,
+    locale::Locale,
+    rationals::{square_free_decomposition, RationalDisplay},
+    traits::{
+        BoxedShape, CheckedOpsError, CheckedSqrt, Exportable, FieldConversion, GuiDisplayable,
+        LaTeXable,
+    },
+};
+use egui::{epaint::TextShape, pos2, vec2, Color32, FontId, Shape};
+use num_rational::{ParseRatioError, Rational64};
+use num_traits::{
+    CheckedAdd, CheckedDiv, CheckedMul, CheckedRem, CheckedSub, FromPrimitive, Num, One, Signed,
+    ToPrimitive, Zero,
+};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+/// Number of the form `a + b sqrt(d)` with rational `a`, `b` and square-free
+/// `d`, so e.g. `sqrt(2)/2` is exact. Numbers with different radicands
+/// cannot be combined - such operations return `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quadratic {
+    rational: Rational64,
+    coefficient: Rational64,
+    /// Square-free radicand greater than one, or one for rationals.
+    radicand: i64,
+}
+
+impl Quadratic {
+    /// Returns `rational + coefficient sqrt(radicand)` with the square factors
+    /// of the radicand moved to the coefficient, e.g. `sqrt(12)` becomes
+    /// `2 sqrt(3)`. Returns `None` for negative or too large radicands.
+    pub fn new(rational: Rational64, coefficient: Rational64, radicand: i64) -> Option<Self> {
+        let (square_root, square_free) = square_free_decomposition(radicand.try_into().ok()?)?;
+        let coefficient = coefficient.checked_mul(&Rational64::from_integer(square_root as i64))?;
+        match square_free {
+            0 => Some(rational.into()),
+            1 => Some(rational.checked_add(&coefficient)?.into()),
+            _ => Some(Self::normalized(rational, coefficient, square_free as i64)),
+        }
+    }
+
+    fn normalized(rational: Rational64, coefficient: Rational64, radicand: i64) -> Self {
+        Quadratic {
+            rational,
+            coefficient,
+            radicand: if coefficient.is_zero() { 1 } else { radicand },
+        }
+    }
+
+    pub fn is_rational(&self) -> bool {
+        self.coefficient.is_zero()
+    }
+
+    /// Radicand of the result of an operation on both numbers, `None` if they
+    /// have different radicands.
+    fn common_radicand(&self, other: &Self) -> Option<i64> {
+        match (self.is_rational(), other.is_rational()) {
+            (true, _) => Some(other.radicand),
+            (_, true) => Some(self.radicand),
+            _ => (self.radicand == other.radicand).then_some(self.radicand),
+        }
+    }
+
+    fn conjugate(&self) -> Self {
+        Quadratic {
+            coefficient: -self.coefficient,
+            ..*self
+        }
+    }
+
+    /// `a^2 - b^2 d`, the product of the number and its conjugate.
+    fn norm(&self) -> Option<Rational64> {
+        let radicand = Rational64::from_integer(self.radicand);
+        self.rational.checked_mul(&self.rational)?.checked_sub(
+            &self
+                .coefficient
+                .checked_mul(&self.coefficient)?
+                .checked_mul(&radicand)?,
+        )
+    }
+
+    fn compare_to_zero(&self) -> Ordering {
+        let rational = self.rational.cmp(&Rational64::zero());
+        let coefficient = self.coefficient.cmp(&Rational64::zero());
+        match (rational, coefficient) {
+            (sign, Ordering::Equal) | (Ordering::Equal, sign) => sign,
+            (a, b) if a == b => a,
+            // the signs differ, so the part with the larger square wins
+            (sign, _) => match self.norm() {
+                Some(norm) if norm.is_positive() => sign,
+                Some(_) => sign.reverse(),
+                None => self.to_f64().unwrap_or(0.).total_cmp(&0.),
+            },
+        }
+    }
+
+    /// The largest integer not greater than the number.
+    fn floor(&self) -> Option<i64> {
+        if self.is_rational() {
+            return self.rational.floor().to_integer().into();
+        }
+        let mut floor = self.to_f64()?.floor() as i64;
+        let difference = |n: i64| self.checked_sub(&Quadratic::from_integer(n));
+        while difference(floor)?.compare_to_zero() == Ordering::Less {
+            floor = floor.checked_sub(1)?;
+        }
+        while difference(floor.checked_add(1)?)?.compare_to_zero() != Ordering::Less {
+            floor += 1;
+        }
+        Some(floor)
+    }
+
+    fn from_integer(n: i64) -> Self {
+        Rational64::from_integer(n).into()
+    }
+}
+
+impl From<Rational64> for Quadratic {
+    fn from(rational: Rational64) -> Self {
+        Self::normalized(rational, Rational64::zero(), 1)
+    }
+}
+
+impl From<Quadratic> for Float64 {
+    fn from(value: Quadratic) -> Self {
+        value.to_f64().unwrap_or(f64::NAN).into()
+    }
+}
+
+impl CheckedAdd for Quadratic {
+    fn checked_add(&self, v: &Self) -> Option<Self> {
+        Some(Self::normalized(
+            self.rational.checked_add(&v.rational)?,
+            self.coefficient.checked_add(&v.coefficient)?,
+            self.common_radicand(v)?,
+        ))
+    }
+}
+
+impl CheckedSub for Quadratic {
+    fn checked_sub(&self, v: &Self) -> Option<Self> {
+        self.checked_add(&-*v)
+    }
+}
+
+impl CheckedMul for Quadratic {
+    fn checked_mul(&self, v: &Self) -> Option<Self> {
+        let radicand = self.common_radicand(v)?;
+        let rational = self.rational.checked_mul(&v.rational)?.checked_add(
+            &self
+                .coefficient
+                .checked_mul(&v.coefficient)?
+                .checked_mul(&Rational64::from_integer(radicand))?,
+        )?;
+        let coefficient = self
+            .rational
+            .checked_mul(&v.coefficient)?
+            .checked_add(&self.coefficient.checked_mul(&v.rational)?)?;
+        Some(Self::normalized(rational, coefficient, radicand))
+    }
+}
+
+/// The divisor is made rational by multiplying both numbers by its conjugate.
+impl CheckedDiv for Quadratic {
+    fn checked_div(&self, v: &Self) -> Option<Self> {
+        let numerator = self.checked_mul(&v.conjugate())?;
+        let norm = v.norm()?;
+        Some(Self::normalized(
+            numerator.rational.checked_div(&norm)?,
+            numerator.coefficient.checked_div(&norm)?,
+            numerator.radicand,
+        ))
+    }
+}
+
+/// Remainder of the division truncated towards zero.
+impl CheckedRem for Quadratic {
+    fn checked_rem(&self, v: &Self) -> Option<Self> {
+        let quotient = Quadratic::from_integer(self.checked_div(v)?.to_i64()?);
+        self.checked_sub(&v.checked_mul(&quotient)?)
+    }
+}
+
+impl CheckedOpsError for Quadratic {
+    fn checked_ops_error() -> Error {
+        Error::NotRepresentable
+    }
+}
+
+/// Square roots of rationals and of squares of quadratic numbers, e.g.
+/// `sqrt(3 + 2 sqrt(2))` is `1 + sqrt(2)`.
+impl CheckedSqrt for Quadratic {
+    fn checked_sqrt(&self) -> Option<Self> {
+        if self.is_rational() {
+            // sqrt(p/q) = sqrt(pq)/q
+            let (numer, denom) = (*self.rational.numer(), *self.rational.denom());
+            let radicand = numer.checked_mul(denom)?;
+            return Quadratic::new(Rational64::zero(), Rational64::new(1, denom), radicand);
+        }
+        // (x + y sqrt(d))^2 = x^2 + d y^2 + 2xy sqrt(d), so x^2 = (a ± sqrt(norm)) / 2
+        let root = self.norm()?.checked_sqrt()?;
+        let two = Rational64::from_integer(2);
+        [root, -root].into_iter().find_map(|root| {
+            let x = self
+                .rational
+                .checked_add(&root)?
+                .checked_div(&two)?
+                .checked_sqrt()?;
+            let y = self.coefficient.checked_div(&two.checked_mul(&x)?)?;
+            let candidate = Self::normalized(x, y, self.radicand);
+            (candidate.checked_mul(&candidate)? == *self).then_some(candidate)
+        })
+    }
+}
+
+/// `approx` rounds the value like approximate numbers are displayed, so the
+/// result is always rational.
+impl FieldConversion for Quadratic {
+    fn approx(&self) -> Option<Self> {
+        let rational = match self.is_rational() {
+            true => self.rational,
+            false => Rational64::from_f64(self.to_f64()?)?,
+        };
+        Some(rational.approx()?.into())
+    }
+
+    fn to_rational(&self) -> Option<Rational64> {
+        self.is_rational().then_some(self.rational)
+    }
+
+    fn from_rational(value: Rational64) -> Option<Self> {
+        Some(value.into())
+    }
+}
+
+impl Zero for Quadratic {
+    fn zero() -> Self {
+        Rational64::zero().into()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.rational.is_zero() && self.is_rational()
+    }
+}
+
+impl One for Quadratic {
+    fn one() -> Self {
+        Rational64::one().into()
+    }
+}
+
+// The operators panic if the result is not representable (e.g. the operands
+// have different radicands), like the operators of Rational64 on overflow.
+// Matrices, the parser and the builtins only use the checked operations,
+// which fail with `Error::NotRepresentable` instead.
+impl Add<Self> for Quadratic {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(&rhs)
+            .expect("Invalid addition of quadratic numbers!")
+    }
+}
+
+impl Sub<Self> for Quadratic {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(&rhs)
+            .expect("Invalid subtraction of quadratic numbers!")
+    }
+}
+
+impl Mul<Self> for Quadratic {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(&rhs)
+            .expect("Invalid multiplication of quadratic numbers!")
+    }
+}
+
+impl Div<Self> for Quadratic {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(&rhs)
+            .expect("Invalid division of quadratic numbers!")
+    }
+}
+
+impl Rem<Self> for Quadratic {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.checked_rem(&rhs)
+            .expect("Invalid remainder of quadratic numbers!")
+    }
+}
+
+impl Neg for Quadratic {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Quadratic {
+            rational: -self.rational,
+            coefficient: -self.coefficient,
+            radicand: self.radicand,
+        }
+    }
+}
+
+impl Num for Quadratic {
+    type FromStrRadixErr = ParseRatioError;
+
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        Rational64::from_str_radix(str, radix).map(Into::into)
+    }
+}
+
+impl Signed for Quadratic {
+    fn abs(&self) -> Self {
+        match self.is_negative() {
+            true => -*self,
+            false => *self,
+        }
+    }
+
+    /// Positive difference, zero if `self <= other`. The difference of
+    /// numbers with different radicands is only computed when it is needed.
+    fn abs_sub(&self, other: &Self) -> Self {
+        match self.checked_sub(other) {
+            Some(difference) if difference.is_positive() => difference,
+            Some(_) => Self::zero(),
+            None if self.to_f64() <= other.to_f64() => Self::zero(),
+            None => panic!("Invalid subtraction of quadratic numbers!"),
+        }
+    }
+
+    fn signum(&self) -> Self {
+        match self.compare_to_zero() {
+            Ordering::Less => -Self::one(),
+            Ordering::Equal => Self::zero(),
+            Ordering::Greater => Self::one(),
+        }
+    }
+
+    fn is_positive(&self) -> bool {
+        self.compare_to_zero() == Ordering::Greater
+    }
+
+    fn is_negative(&self) -> bool {
+        self.compare_to_zero() == Ordering::Less
+    }
+}
+
+impl FromPrimitive for Quadratic {
+    fn from_i64(n: i64) -> Option<Self> {
+        Some(Self::from_integer(n))
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(Self::from_integer(n.try_into().ok()?))
+    }
+
+    fn from_f64(n: f64) -> Option<Self> {
+        Some(Rational64::from_f64(n)?.into())
+    }
+}
+
+/// Integer conversions truncate towards zero.
+impl ToPrimitive for Quadratic {
+    fn to_i64(&self) -> Option<i64> {
+        let floor = self.floor()?;
+        match self.is_negative() && Quadratic::from_integer(floor) != *self {
+            true => floor.checked_add(1),
+            false => Some(floor),
+        }
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.to_i64()?.try_into().ok()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.rational.to_f64()? + self.coefficient.to_f64()? * (self.radicand as f64).sqrt())
+    }
+}
+
+impl FromStr for Quadratic {
+    type Err = ParseRatioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rational64::from_str(s).map(Into::into)
+    }
+}
+
+/// Written as an expression which the parser accepts, e.g. `1/2 - sqrt(3)`.
+impl Display for Quadratic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_rational() {
+            return write!(f, "{}", self.rational);
+        }
+        let coefficient = self.coefficient.abs();
+        let radical = match coefficient.is_one() {
+            true => format!("sqrt({})", self.radicand),
+            false => format!("{coefficient}*sqrt({})", self.radicand),
+        };
+        let sign = if self.coefficient.is_negative() {
+            "-"
+        } else {
+            "+"
+        };
+        match self.rational.is_zero() {
+            true if sign == "-" => write!(f, "-{radical}"),
+            true => write!(f, "{radical}"),
+            false => write!(f, "{} {sign} {radical}", self.rational),
+        }
+    }
+}
+
+impl Quadratic {
+    /// Joins the rational part and the radical with the sign of the
+    /// coefficient, e.g. `1 - 2 sqrt(3)`. `radical` gets the absolute value
+    /// of the coefficient.
+    fn join<S: AsRef<str>>(&self, rational: S, radical: S, minus: &str) -> String {
+        let sign = if self.coefficient.is_negative() {
+            minus
+        } else {
+            "+"
+        };
+        match self.rational.is_zero() {
+            true if self.coefficient.is_negative() => format!("{minus}{}", radical.as_ref()),
+            true => radical.as_ref().to_string(),
+            false => format!("{} {sign} {}", rational.as_ref(), radical.as_ref()),
+        }
+    }
+}
+
+impl LaTeXable for Quadratic {
+    fn to_latex(&self) -> String {
+        if self.is_rational() {
+            return self.rational.to_latex();
+        }
+        let coefficient = self.coefficient.abs();
+        let root = format!(r"\sqrt{{{}}}", self.radicand);
+        let radical = match (coefficient.is_integer(), RationalDisplay::current()) {
+            (false, RationalDisplay::Fraction) if coefficient.numer().is_one() => {
+                format!(r"\frac{{{root}}}{{{}}}", coefficient.denom())
+            }
+            (false, RationalDisplay::Fraction) => {
+                format!(
+                    r"\frac{{{}{root}}}{{{}}}",
+                    coefficient.numer(),
+                    coefficient.denom()
+                )
+            }
+            _ if coefficient.is_one() => root,
+            _ => format!("{}{root}", coefficient.to_latex()),
+        };
+        self.join(self.rational.to_latex(), radical, "-")
+    }
+
+    fn to_latex_single(&self) -> String {
+        if self.is_rational() {
+            self.rational.to_latex_single()
+        } else if self.rational.is_zero() && self.coefficient.is_positive() {
+            self.to_latex()
+        } else {
+            format!(r"\left({}\right)", self.to_latex())
+        }
+    }
+}
+
+impl Exportable for Quadratic {
+    fn to_typst(&self) -> String {
+        if self.is_rational() {
+            return self.rational.to_typst();
+        }
+        let coefficient = self.coefficient.abs();
+        let radical = match coefficient.is_one() {
+            true => format!("sqrt({})", self.radicand),
+            false => format!("{} sqrt({})", coefficient.to_typst(), self.radicand),
+        };
+        self.join(self.rational.to_typst(), radical, "-")
+    }
+
+    fn to_mathml(&self) -> String {
+        if self.is_rational() {
+            return self.rational.to_mathml();
+        }
+        let coefficient = self.coefficient.abs();
+        let root = format!("<msqrt><mn>{}</mn></msqrt>", self.radicand);
+        let radical = match coefficient.is_one() {
+            true => root,
+            false => format!("{}{root}", coefficient.to_mathml()),
+        };
+        let sign = if self.coefficient.is_negative() {
+            "-"
+        } else {
+            "+"
+        };
+        match self.rational.is_zero() {
+            true if sign == "-" => format!("<mo>-</mo>{radical}"),
+            true => radical,
+            false => format!("{}<mo>{sign}</mo>{radical}", self.rational.to_mathml()),
+        }
+    }
+
+    fn to_plain(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl GuiDisplayable for Quadratic {
+    fn display_string(&self, locale: &Locale) -> String {
+        if self.is_rational() {
+            return self.rational.display_string(locale);
+        }
+        let coefficient = self.coefficient.abs();
+        let radical = match coefficient.is_one() {
+            true => format!("√{}", self.radicand),
+            false => format!("{}√{}", coefficient.display_string(locale), self.radicand),
+        };
+        self.join(self.rational.display_string(locale), radical, "-")
+    }
+
+    fn to_shape(&self, ctx: &egui::Context, font_id: FontId, color: Color32) -> Shape {
+        if self.is_rational() {
+            return self.rational.to_shape(ctx, font_id, color);
+        }
+        let text = |text: String| {
+            Shape::Text(TextShape::new(
+                pos2(0., 0.),
+                ctx.fonts(|f| f.layout_no_wrap(text, font_id.clone(), color)),
+            ))
+        };
+        let coefficient = self.coefficient.abs();
+        let mut parts = vec![];
+        if !self.rational.is_zero() {
+            parts.push(self.rational.to_shape(ctx, font_id.clone(), color));
+        }
+        match (self.coefficient.is_negative(), parts.is_empty()) {
+            (true, true) => parts.push(text("-".to_string())),
+            (true, false) => parts.push(text(" - ".to_string())),
+            (false, false) => parts.push(text(" + ".to_string())),
+            (false, true) => {}
+        }
+        if !coefficient.is_one() {
+            parts.push(coefficient.to_shape(ctx, font_id.clone(), color));
+        }
+        parts.push(text(format!("√{}", self.radicand)));
+        row_shape(parts)
+    }
+}
+
+/// Places the shapes next to each other, centered vertically.
+fn row_shape(mut parts: Vec<Shape>) -> Shape {
+    let height = parts
+        .iter()
+        .map(|part| part.get_rect().height())
+        .fold(0., f32::max);
+    let mut x = 0.;
+    for part in parts.iter_mut() {
+        let rect = part.get_rect();
+        part.translate(vec2(
+            x - rect.min.x,
+            (height - rect.height()) / 2. - rect.min.y,
+        ));
+        x += rect.width();
+    }
+    Shape::Vec(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::{Environment, Type};
+    use crate::parser::parse_expression;
+
+    fn q(rational: (i64, i64), coefficient: (i64, i64), radicand: i64) -> Quadratic {
+        Quadratic::new(
+            Rational64::new(rational.0, rational.1),
+            Rational64::new(coefficient.0, coefficient.1),
+            radicand,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(q((0, 1), (1, 1), 12), q((0, 1), (2, 1), 3));
+        assert_eq!(q((1, 1), (1, 1), 9), Quadratic::from_integer(4));
+        assert!(q((1, 2), (0, 1), 2).is_rational());
+        assert!(Quadratic::new(Rational64::zero(), Rational64::one(), -1).is_none());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let sqrt2 = q((0, 1), (1, 1), 2);
+        let sqrt3 = q((0, 1), (1, 1), 3);
+        assert_eq!(sqrt2 * sqrt2, Quadratic::from_integer(2));
+        assert_eq!(sqrt2 + sqrt2, q((0, 1), (2, 1), 2));
+        assert_eq!(Quadratic::one() / sqrt2, q((0, 1), (1, 2), 2));
+        assert_eq!(
+            q((1, 1), (1, 1), 2).checked_div(&q((1, 1), (-1, 1), 2)),
+            Some(q((-3, 1), (-2, 1), 2))
+        );
+        assert_eq!(sqrt2.checked_add(&sqrt3), None);
+        assert_eq!(sqrt2.checked_div(&Quadratic::zero()), None);
+
+        let seven = Quadratic::from_integer(7);
+        assert_eq!(seven % sqrt2, q((7, 1), (-4, 1), 2));
+        assert_eq!(sqrt2.checked_rem(&sqrt3), None);
+        assert_eq!(
+            seven.checked_rem(&sqrt3.checked_add(&Quadratic::one()).unwrap()),
+            Some(q((5, 1), (-2, 1), 3))
+        );
+        assert_eq!(q((1, 1), (1, 1), 2).checked_rem(&sqrt3), None);
+        assert_eq!(sqrt2.abs_sub(&sqrt3), Quadratic::zero());
+        assert_eq!(sqrt3.abs_sub(&Quadratic::one()), q((-1, 1), (1, 1), 3));
+        assert_eq!(Quadratic::one().abs_sub(&sqrt3), Quadratic::zero());
+    }
+
+    #[test]
+    fn test_not_representable_error() {
+        let env = Environment::<Quadratic>::new();
+        for expression in [
+            "sqrt(2) + sqrt(3)",
+            "sqrt(2) * sqrt(3) - sqrt(5)",
+            "(1 + sqrt(2)) / sqrt(3)",
+        ] {
+            let error = parse_expression(expression, &env).unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<Error>(),
+                Some(&Error::NotRepresentable)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sign_and_integer_part() {
+        let value = q((-3, 2), (1, 1), 2); // -1.5 + 1.414...
+        assert!(value.is_negative());
+        assert!(q((-1, 1), (1, 1), 2).is_positive());
+        assert_eq!(value.to_i64(), Some(0));
+        assert_eq!(q((0, 1), (-1, 1), 2).to_i64(), Some(-1));
+        assert_eq!(q((0, 1), (1, 1), 50).to_i64(), Some(7));
+        assert_eq!(q((0, 1), (-1, 1), 2).abs(), q((0, 1), (1, 1), 2));
+    }
+
+    #[test]
+    fn test_sqrt() {
+        let sqrt = |n: Rational64| Quadratic::from(n).checked_sqrt();
+        assert_eq!(sqrt(Rational64::new(1, 2)), Some(q((0, 1), (1, 2), 2)));
+        assert_eq!(
+            sqrt(Rational64::new(9, 4)),
+            Some(Rational64::new(3, 2).into())
+        );
+        assert_eq!(sqrt(Rational64::from_integer(-4)), None);
+        assert_eq!(
+            q((3, 1), (2, 1), 2).checked_sqrt(),
+            Some(q((1, 1), (1, 1), 2))
+        );
+        assert_eq!(q((1, 1), (1, 1), 2).checked_sqrt(), None);
+    }
+
+    #[test]
+    fn test_latex() {
+        assert_eq!(q((0, 1), (1, 2), 2).to_latex(), r"\frac{\sqrt{2}}{2}");
+        assert_eq!(q((1, 1), (-3, 4), 2).to_latex(), r"1 - \frac{3\sqrt{2}}{4}");
+        assert_eq!(q((1, 2), (2, 1), 3).to_latex(), r"\frac{1}{2} + 2\sqrt{3}");
+        assert_eq!(q((0, 1), (-1, 1), 5).to_latex(), r"-\sqrt{5}");
+        assert_eq!(
+            q((1, 1), (1, 1), 2).to_latex_single(),
+            r"\left(1 + \sqrt{2}\right)"
+        );
+        assert_eq!(q((0, 1), (1, 2), 2).to_typst(), "frac(1, 2) sqrt(2)");
+    }
+
+    #[test]
+    fn test_display_is_parsable() {
+        let env = Environment::<Quadratic>::new();
+        for value in [
+            q((1, 2), (-3, 4), 2),
+            q((0, 1), (-1, 1), 5),
+            q((2, 1), (1, 1), 3),
+        ] {
+            assert_eq!(
+                parse_expression(&value.to_string(), &env).unwrap(),
+                Type::Scalar(value)
+            );
+        }
+    }
+}
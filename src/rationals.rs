@@ -1,19 +1,413 @@
 use crate::{
     constants::{
         FRACTION_FONT_SIZE_RATIO, FRACTION_HMARGIN, FRACTION_LINE_WIDTH, FRACTION_VMARGIN,
+        MAX_APPROXIMATION_DENOMINATOR, MAX_RADICAND, MAX_REPEATING_DIGITS,
+    },
+    float::FloatFormat,
+    locale::Locale,
+    traits::{
+        number_to_mathml, BoxedShape, CheckedOpsError, CheckedSqrt, Exportable, FieldConversion,
+        GuiDisplayable, LaTeXable, MatrixNumber,
     },
-    traits::{BoxedShape, GuiDisplayable, LaTeXable},
 };
-use egui::{pos2, vec2, FontId, Rect, Rounding, Shape};
+use egui::{epaint::TextShape, pos2, vec2, FontId, Rect, Rounding, Shape};
+use lazy_static::lazy_static;
 use num_rational::Rational64;
 use num_traits::sign::Signed;
+use num_traits::{CheckedMul, ToPrimitive, Zero};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+impl CheckedOpsError for Rational64 {}
+
+/// `approx` rounds to as many fractional digits as approximate numbers are
+/// displayed with, e.g. `1/3` to `333/1000`.
+impl FieldConversion for Rational64 {
+    fn approx(&self) -> Option<Self> {
+        let precision = FloatFormat::current().precision as u32;
+        let scale = Rational64::from_integer(10_i64.checked_pow(precision)?);
+        Some(self.checked_mul(&scale)?.round() / scale)
+    }
+
+    fn to_rational(&self) -> Option<Rational64> {
+        Some(*self)
+    }
+
+    fn from_rational(value: Rational64) -> Option<Self> {
+        Some(value)
+    }
+}
+
+/// Returns the best rational approximation of `value` with denominator not
+/// greater than `max_denominator`, computed with continued fractions.
+/// Returns `None` if `value` is not finite or does not fit into `Rational64`.
+pub fn rationalize(value: impl Into<f64>, max_denominator: i64) -> Option<Rational64> {
+    rationalize_within(value.into(), 0., max_denominator)
+}
+
+/// Rationalizes a decimal literal, e.g. `0.333333` becomes `1/3` - the result
+/// is the simplest fraction which rounds to the same digits. Fractions whose
+/// squared denominator exceeds the precision of the literal are coincidental,
+/// so e.g. `0.12` stays `3/25` instead of `2/17`. Returns `None` if `raw` is
+/// not a decimal literal.
+pub fn rationalize_decimal(raw: &str) -> Option<Rational64> {
+    let raw = raw.trim();
+    let (integer, fraction) = raw.split_once('.')?;
+    if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let scale = 10_i64.checked_pow(fraction.len() as u32)?;
+    let exact = Rational64::new((integer.to_string() + fraction).parse().ok()?, scale);
+    let tolerance = 0.5 / scale as f64;
+    match rationalize_within(raw.parse().ok()?, tolerance, MAX_APPROXIMATION_DENOMINATOR) {
+        Some(simple) if simple.denom().checked_mul(simple.denom())? <= scale => Some(simple),
+        _ => Some(exact),
+    }
+}
+
+/// Returns the first convergent of the continued fraction of `value` which
+/// differs from it by less than `tolerance` (or the last one with denominator
+/// not greater than `max_denominator`).
+fn rationalize_within(value: f64, tolerance: f64, max_denominator: i64) -> Option<Rational64> {
+    if !value.is_finite() || value.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    // convergents p/q of the continued fraction
+    let (mut p0, mut q0, mut p1, mut q1) = (0_i64, 1_i64, 1_i64, 0_i64);
+    let mut x = value.abs();
+    loop {
+        let a = x.floor() as i64;
+        let next = a
+            .checked_mul(p1)
+            .and_then(|p| p.checked_add(p0))
+            .zip(a.checked_mul(q1).and_then(|q| q.checked_add(q0)));
+        match next {
+            Some((p2, q2)) if q2 <= max_denominator => {
+                (p0, q0, p1, q1) = (p1, q1, p2, q2);
+            }
+            _ => break,
+        }
+        let fraction = x - a as f64;
+        let error = (p1 as f64 / q1 as f64 - value.abs()).abs();
+        if fraction == 0. || error == 0. || error < tolerance {
+            break;
+        }
+        x = 1. / fraction;
+    }
+    let result = Rational64::new(p1, q1);
+    Some(if value < 0. { -result } else { result })
+}
+
+/// Partial quotients `[a0; a1, a2, ...]` of a continued fraction. The
+/// expansion is `truncated` if it did not end within the maximal number of
+/// terms, e.g. for irrational numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContinuedFraction {
+    pub terms: Vec<i64>,
+    pub truncated: bool,
+}
+
+/// Returns at most `max_terms` partial quotients of the continued fraction of
+/// `value`. The expansion of exact numbers ends when the remainder is zero.
+/// Returns `None` if a quotient does not fit into `i64`.
+pub fn continued_fraction<T: MatrixNumber>(
+    value: &T,
+    max_terms: usize,
+) -> Option<ContinuedFraction> {
+    let mut terms = vec![];
+    let mut x = value.clone();
+    loop {
+        let mut a = x.to_i64()?;
+        let mut fraction = x.checked_sub(&T::from_i64(a)?)?;
+        // the quotient is the floor, not the truncation
+        if fraction.is_negative() && !fraction.is_zero() {
+            a = a.checked_sub(1)?;
+            fraction = fraction.checked_add(&T::one())?;
+        }
+        terms.push(a);
+        if fraction.is_zero() || terms.len() >= max_terms {
+            let truncated = !fraction.is_zero();
+            return Some(ContinuedFraction { terms, truncated });
+        }
+        x = T::one().checked_div(&fraction)?;
+    }
+}
+
+/// LaTeX representation, e.g. `3 + \cfrac{1}{7 + \cfrac{1}{16}}` for
+/// `[3; 7, 16]`. Truncated expansions end with `+ \cdots`.
+impl LaTeXable for ContinuedFraction {
+    fn to_latex(&self) -> String {
+        fn nested(terms: &[i64], truncated: bool) -> String {
+            match terms {
+                [] => String::new(),
+                [last] if truncated => format!(r"{last} + \cdots"),
+                [last] => last.to_string(),
+                [first, rest @ ..] => {
+                    format!(r"{first} + \cfrac{{1}}{{{}}}", nested(rest, truncated))
+                }
+            }
+        }
+        nested(&self.terms, self.truncated)
+    }
+}
+
+/// Square root of `n` if it is a perfect square.
+pub fn perfect_square_root(n: u128) -> Option<u128> {
+    let mut root = (n as f64).sqrt() as u128;
+    while root.checked_mul(root).is_none_or(|square| square > n) {
+        root -= 1;
+    }
+    while (root + 1)
+        .checked_mul(root + 1)
+        .is_some_and(|square| square <= n)
+    {
+        root += 1;
+    }
+    (root * root == n).then_some(root)
+}
+
+/// Splits `n` into `s^2 * d` with square-free `d`, e.g. `12` into `(2, 3)`.
+/// Returns `None` if `n` is greater than `MAX_RADICAND`.
+pub fn square_free_decomposition(mut n: u64) -> Option<(u64, u64)> {
+    if n > MAX_RADICAND {
+        return None;
+    }
+    let (mut square_root, mut square_free) = (1, 1);
+    let mut factor = 2;
+    while factor * factor <= n {
+        while n.is_multiple_of(factor * factor) {
+            n /= factor * factor;
+            square_root *= factor;
+        }
+        if n.is_multiple_of(factor) {
+            n /= factor;
+            square_free *= factor;
+        }
+        factor += 1;
+    }
+    Some((square_root, square_free * n))
+}
+
+/// Square roots of perfect squares of rationals, e.g. `4/9` to `2/3`.
+impl CheckedSqrt for Rational64 {
+    fn checked_sqrt(&self) -> Option<Self> {
+        if self.is_negative() {
+            return None;
+        }
+        let numer = perfect_square_root(self.numer().unsigned_abs() as u128)?;
+        let denom = perfect_square_root(self.denom().unsigned_abs() as u128)?;
+        Some(Rational64::new(
+            numer.try_into().ok()?,
+            denom.try_into().ok()?,
+        ))
+    }
+}
+
+lazy_static! {
+    static ref RATIONAL_DISPLAY: RwLock<RationalDisplay> = RwLock::new(RationalDisplay::default());
+}
+
+thread_local! {
+    static RATIONAL_DISPLAY_OVERRIDE: Cell<Option<RationalDisplay>> = const { Cell::new(None) };
+}
+
+/// How rationals are displayed in the GUI and in LaTeX. The numbers are
+/// always stored exactly, only their presentation changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RationalDisplay {
+    /// Stacked fraction, e.g. `7/3`.
+    #[default]
+    Fraction,
+    /// Integer part followed by a proper fraction, e.g. `2 1/3`.
+    Mixed,
+    /// Decimal with the repeating digits in parentheses, e.g. `2.(3)`.
+    Repeating,
+    /// Decimal rounded like approximate numbers, e.g. `2.333`.
+    Fixed,
+}
+
+impl RationalDisplay {
+    pub const ALL: [RationalDisplay; 4] = [
+        RationalDisplay::Fraction,
+        RationalDisplay::Mixed,
+        RationalDisplay::Repeating,
+        RationalDisplay::Fixed,
+    ];
+
+    /// Name of the mode, also used as the translation key.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RationalDisplay::Fraction => "Fraction",
+            RationalDisplay::Mixed => "Mixed number",
+            RationalDisplay::Repeating => "Repeating decimal",
+            RationalDisplay::Fixed => "Fixed decimal",
+        }
+    }
+
+    /// Mode of the current window if it has its own, the global one otherwise.
+    pub fn current() -> Self {
+        RATIONAL_DISPLAY_OVERRIDE
+            .with(Cell::get)
+            .unwrap_or_else(|| {
+                *RATIONAL_DISPLAY
+                    .read()
+                    .expect("Rational display lock is poisoned!")
+            })
+    }
+
+    pub fn set(display: Self) {
+        *RATIONAL_DISPLAY
+            .write()
+            .expect("Rational display lock is poisoned!") = display;
+    }
+
+    /// Calls `f` with rationals displayed in `display` instead of the global
+    /// mode, e.g. in a window with its own mode. `None` keeps the global mode.
+    pub fn scoped<R>(display: Option<Self>, f: impl FnOnce() -> R) -> R {
+        let previous = RATIONAL_DISPLAY_OVERRIDE.with(|cell| cell.replace(display));
+        let result = f();
+        RATIONAL_DISPLAY_OVERRIDE.with(|cell| cell.set(previous));
+        result
+    }
+
+    /// Plain text with the given decimal separator, e.g. `2 1/3` or `0.(3)`.
+    pub fn format_plain(&self, value: &Rational64, format: FloatFormat) -> String {
+        if value.is_integer() {
+            return value.to_string();
+        }
+        match self {
+            RationalDisplay::Fraction => None,
+            RationalDisplay::Mixed => {
+                mixed_parts(value).map(|(integer, fraction)| format!("{integer} {fraction}"))
+            }
+            RationalDisplay::Repeating => {
+                decimal_expansion(value).map(|(integer, fixed, repeating)| {
+                    let repeating = match repeating.is_empty() {
+                        true => repeating,
+                        false => format!("({repeating})"),
+                    };
+                    format!("{integer}{}{fixed}{repeating}", format.decimal_separator)
+                })
+            }
+            RationalDisplay::Fixed => Some(format.format_plain(to_f64(value))),
+        }
+        .unwrap_or_else(|| value.to_string())
+    }
+
+    /// LaTeX, e.g. `2\tfrac{1}{3}` or `0.\overline{3}`. Fixed decimals are
+    /// only displayed, in LaTeX they are written as fractions.
+    pub fn format_latex(&self, value: &Rational64, format: FloatFormat) -> String {
+        if value.is_integer() {
+            return value.numer().to_string();
+        }
+        match self {
+            RationalDisplay::Fraction => None,
+            RationalDisplay::Mixed => mixed_parts(value).map(|(integer, fraction)| {
+                format!(
+                    r"{integer}\tfrac{{{}}}{{{}}}",
+                    fraction.numer(),
+                    fraction.denom()
+                )
+            }),
+            RationalDisplay::Repeating => {
+                decimal_expansion(value).map(|(integer, fixed, repeating)| {
+                    let separator = match format.decimal_separator {
+                        ',' => "{,}".to_string(),
+                        separator => separator.to_string(),
+                    };
+                    let repeating = match repeating.is_empty() {
+                        true => repeating,
+                        false => format!(r"\overline{{{repeating}}}"),
+                    };
+                    format!("{integer}{separator}{fixed}{repeating}")
+                })
+            }
+            // Rounding would lose exactness in the exported LaTeX.
+            RationalDisplay::Fixed => None,
+        }
+        .unwrap_or_else(|| fraction_latex(value))
+    }
+}
+
+fn to_f64(value: &Rational64) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Integer part (with the sign) and the positive proper fraction, e.g.
+/// `(-2, 1/3)` for `-7/3`. `None` if the absolute value is less than one.
+fn mixed_parts(value: &Rational64) -> Option<(String, Rational64)> {
+    let integer = value.trunc();
+    if integer.is_zero() {
+        return None;
+    }
+    Some((integer.numer().to_string(), (value - integer).abs()))
+}
+
+/// Decimal expansion of the value split into the integer part (with the
+/// sign), the fixed and the repeating fractional digits, e.g.
+/// `("-1", "1", "6")` for `-7/6`. `None` if the digits do not repeat within
+/// `MAX_REPEATING_DIGITS` digits.
+fn decimal_expansion(value: &Rational64) -> Option<(String, String, String)> {
+    let numer = value.numer().unsigned_abs();
+    let denom = value.denom().unsigned_abs();
+    let sign = if value.is_negative() { "-" } else { "" };
+    let integer = format!("{sign}{}", numer / denom);
+
+    let mut digits = String::new();
+    let mut positions = HashMap::new();
+    let mut remainder = numer % denom;
+    while remainder != 0 {
+        if let Some(&start) = positions.get(&remainder) {
+            let (fixed, repeating) = digits.split_at(start);
+            return Some((integer, fixed.to_string(), repeating.to_string()));
+        }
+        if digits.len() >= MAX_REPEATING_DIGITS {
+            return None;
+        }
+        positions.insert(remainder, digits.len());
+        let shifted = remainder as u128 * 10;
+        digits.push_str(&(shifted / denom as u128).to_string());
+        remainder = (shifted % denom as u128) as u64;
+    }
+    Some((integer, digits, String::new()))
+}
+
+fn fraction_latex(value: &Rational64) -> String {
+    match value.is_integer() {
+        true => format!("{}", value.numer()),
+        false => format!(
+            "{}\\frac{{{}}}{{{}}}",
+            if value.is_positive() { "" } else { "-" },
+            value.numer().unsigned_abs(),
+            value.denom().unsigned_abs()
+        ),
+    }
+}
 
 impl LaTeXable for Rational64 {
     fn to_latex(&self) -> String {
+        RationalDisplay::current().format_latex(self, FloatFormat::current())
+    }
+
+    fn to_latex_single(&self) -> String {
+        let mixed = RationalDisplay::current() == RationalDisplay::Mixed
+            && !self.is_integer()
+            && mixed_parts(self).is_some();
+        if self.is_positive() && !mixed {
+            self.to_latex()
+        } else {
+            format!(r"\left({}\right)", self.to_latex())
+        }
+    }
+}
+
+impl Exportable for Rational64 {
+    fn to_typst(&self) -> String {
         match self.is_integer() {
             true => format!("{}", self.numer()),
             false => format!(
-                "{}\\frac{{{}}}{{{}}}",
+                "{}frac({}, {})",
                 if self.is_positive() { "" } else { "-" },
                 self.numer().unsigned_abs(),
                 self.denom().unsigned_abs()
@@ -21,18 +415,27 @@ impl LaTeXable for Rational64 {
         }
     }
 
-    fn to_latex_single(&self) -> String {
-        if self.is_positive() {
-            self.to_latex()
-        } else {
-            format!(r"\left({}\right)", self.to_latex())
+    fn to_mathml(&self) -> String {
+        match self.is_integer() {
+            true => number_to_mathml(&self.numer().to_string()),
+            false => format!(
+                "{}<mfrac><mn>{}</mn><mn>{}</mn></mfrac>",
+                if self.is_positive() { "" } else { "<mo>-</mo>" },
+                self.numer().unsigned_abs(),
+                self.denom().unsigned_abs()
+            ),
         }
     }
+
+    fn to_plain(&self) -> String {
+        self.to_string()
+    }
 }
 
 impl GuiDisplayable for Rational64 {
-    fn display_string(&self, _locale: &crate::locale::Locale) -> String {
-        self.to_string()
+    fn display_string(&self, locale: &Locale) -> String {
+        let format = FloatFormat::for_locale(locale, FloatFormat::current().precision);
+        RationalDisplay::current().format_plain(self, format)
     }
 
     fn to_shape(
@@ -41,8 +444,19 @@ impl GuiDisplayable for Rational64 {
         font_id: egui::FontId,
         color: egui::Color32,
     ) -> egui::Shape {
+        let display = RationalDisplay::current();
         if self.is_integer() {
             self.numer().to_shape(ctx, font_id, color)
+        } else if display == RationalDisplay::Mixed && mixed_parts(self).is_some() {
+            mixed_shape(self, ctx, font_id, color)
+        } else if display == RationalDisplay::Repeating && decimal_expansion(self).is_some()
+            || display == RationalDisplay::Fixed
+        {
+            let text = display.format_plain(self, FloatFormat::current());
+            Shape::Text(TextShape::new(
+                pos2(0., 0.),
+                ctx.fonts(|f| f.layout_no_wrap(text, font_id, color)),
+            ))
         } else {
             let mut num_shape = self.numer().to_shape(
                 ctx,
@@ -88,6 +502,28 @@ impl GuiDisplayable for Rational64 {
     }
 }
 
+/// Integer part followed by the stacked proper fraction, centered vertically.
+fn mixed_shape(
+    value: &Rational64,
+    ctx: &egui::Context,
+    font_id: FontId,
+    color: egui::Color32,
+) -> Shape {
+    let integer = value.trunc();
+    let mut integer_shape = integer.numer().to_shape(ctx, font_id.clone(), color);
+    let mut fraction_shape = (value - integer).abs().to_shape(ctx, font_id, color);
+
+    let integer_rect = integer_shape.get_rect();
+    let fraction_rect = fraction_shape.get_rect();
+    integer_shape.translate(vec2(
+        0.,
+        (fraction_rect.height() - integer_rect.height()).max(0.) / 2.,
+    ));
+    fraction_shape.translate(vec2(integer_rect.width() + FRACTION_HMARGIN, 0.));
+
+    Shape::Vec(vec![integer_shape, fraction_shape])
+}
+
 // Macro to generate a Rational64 from a integer.
 // `ri!(1)` is equivalent to `Rational64::from_integer(1)`, but shorter.
 // ri stands for Rational from Integer.
@@ -103,7 +539,7 @@ macro_rules! ri {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::traits::MatrixNumber;
+    use crate::float::Float64;
     use num_rational::Rational64;
 
     #[test]
@@ -142,6 +578,18 @@ mod tests {
         assert_eq!(r.to_latex(), "0");
     }
 
+    #[test]
+    fn test_fraction_export() {
+        let r = Rational64::new(-2, 6);
+        assert_eq!(r.to_typst(), "-frac(1, 3)");
+        assert_eq!(
+            r.to_mathml(),
+            "<mo>-</mo><mfrac><mn>1</mn><mn>3</mn></mfrac>"
+        );
+        assert_eq!(r.to_plain(), "-1/3");
+        assert_eq!(Rational64::new(8, 2).to_typst(), "4");
+    }
+
     #[test]
     fn test_matrix_num() {
         fn test<T: MatrixNumber>(_: T) {}
@@ -149,4 +597,156 @@ mod tests {
         let r = Rational64::new(4, 1);
         test(r);
     }
+
+    #[test]
+    fn test_mixed_display() {
+        let format = FloatFormat::default();
+        let mixed = RationalDisplay::Mixed;
+        assert_eq!(
+            mixed.format_latex(&Rational64::new(7, 3), format),
+            r"2\tfrac{1}{3}"
+        );
+        assert_eq!(
+            mixed.format_latex(&Rational64::new(-7, 3), format),
+            r"-2\tfrac{1}{3}"
+        );
+        assert_eq!(
+            mixed.format_latex(&Rational64::new(1, 3), format),
+            r"\frac{1}{3}"
+        );
+        assert_eq!(
+            mixed.format_plain(&Rational64::new(-7, 3), format),
+            "-2 1/3"
+        );
+        assert_eq!(mixed.format_plain(&ri!(4), format), "4");
+    }
+
+    #[test]
+    fn test_decimal_display() {
+        let format = FloatFormat::default();
+        let comma = FloatFormat {
+            decimal_separator: ',',
+            ..format
+        };
+        let repeating = RationalDisplay::Repeating;
+        assert_eq!(
+            repeating.format_plain(&Rational64::new(1, 3), format),
+            "0.(3)"
+        );
+        assert_eq!(
+            repeating.format_plain(&Rational64::new(-7, 6), comma),
+            "-1,1(6)"
+        );
+        assert_eq!(
+            repeating.format_plain(&Rational64::new(3, 8), format),
+            "0.375"
+        );
+        assert_eq!(
+            repeating.format_latex(&Rational64::new(1, 7), comma),
+            r"0{,}\overline{142857}"
+        );
+        // the period of 1/97 is too long, so it stays a fraction
+        assert_eq!(
+            repeating.format_plain(&Rational64::new(1, 97), format),
+            "1/97"
+        );
+
+        let fixed = RationalDisplay::Fixed;
+        assert_eq!(fixed.format_plain(&Rational64::new(2, 3), format), "0.667");
+        assert_eq!(
+            fixed.format_latex(&Rational64::new(-1, 8), comma),
+            r"-\frac{1}{8}"
+        );
+    }
+
+    #[test]
+    fn test_scoped_display() {
+        let r = Rational64::new(5, 2);
+        let latex = RationalDisplay::scoped(Some(RationalDisplay::Mixed), || {
+            (r.to_latex(), r.to_latex_single())
+        });
+        assert_eq!(
+            latex,
+            (
+                r"2\tfrac{1}{2}".to_string(),
+                r"\left(2\tfrac{1}{2}\right)".to_string()
+            )
+        );
+        assert_eq!(r.to_latex(), r"\frac{5}{2}");
+    }
+
+    #[test]
+    fn test_rationalize() {
+        assert_eq!(rationalize(0.5, 1000), Some(Rational64::new(1, 2)));
+        assert_eq!(rationalize(-1. / 3., 1000), Some(Rational64::new(-1, 3)));
+        assert_eq!(
+            rationalize(std::f64::consts::PI, 1000),
+            Some(Rational64::new(355, 113))
+        );
+        assert_eq!(
+            rationalize(std::f64::consts::PI, 100),
+            Some(Rational64::new(22, 7))
+        );
+        assert_eq!(rationalize(42., 1), Some(Rational64::from_integer(42)));
+        assert_eq!(rationalize(0., 10), Some(Rational64::from_integer(0)));
+        assert_eq!(rationalize(f64::NAN, 10), None);
+        assert_eq!(rationalize(1e30, 10), None);
+    }
+
+    #[test]
+    fn test_rationalize_decimal() {
+        assert_eq!(rationalize_decimal("0.333333"), Some(Rational64::new(1, 3)));
+        assert_eq!(rationalize_decimal("-0.6667"), Some(Rational64::new(-2, 3)));
+        assert_eq!(rationalize_decimal("0.12"), Some(Rational64::new(3, 25)));
+        assert_eq!(
+            rationalize_decimal("3.14159"),
+            Some(Rational64::new(355, 113))
+        );
+        assert_eq!(
+            rationalize_decimal("0.1234"),
+            Some(Rational64::new(617, 5000))
+        );
+        assert_eq!(rationalize_decimal("2.50"), Some(Rational64::new(5, 2)));
+        assert_eq!(rationalize_decimal("0.1"), Some(Rational64::new(1, 10)));
+        assert_eq!(rationalize_decimal("12"), None);
+        assert_eq!(rationalize_decimal("1/3"), None);
+    }
+
+    #[test]
+    fn test_continued_fraction() {
+        let terms = |value: Option<ContinuedFraction>| value.map(|fraction| fraction.terms);
+        assert_eq!(
+            terms(continued_fraction(&Rational64::new(415, 93), 20)),
+            Some(vec![4, 2, 6, 7])
+        );
+        assert_eq!(
+            terms(continued_fraction(&Rational64::new(-7, 3), 20)),
+            Some(vec![-3, 1, 2])
+        );
+        assert_eq!(terms(continued_fraction(&5_i64, 20)), Some(vec![5]));
+        let pi = continued_fraction(&Float64::from(std::f64::consts::PI), 4).unwrap();
+        assert_eq!(pi.terms, vec![3, 7, 15, 1]);
+        assert!(pi.truncated);
+        assert_eq!(
+            pi.to_latex(),
+            r"3 + \cfrac{1}{7 + \cfrac{1}{15 + \cfrac{1}{1 + \cdots}}}"
+        );
+
+        let exact = continued_fraction(&Rational64::new(415, 93), 4).unwrap();
+        assert!(!exact.truncated);
+        assert_eq!(
+            exact.to_latex(),
+            r"4 + \cfrac{1}{2 + \cfrac{1}{6 + \cfrac{1}{7}}}"
+        );
+        assert_eq!(continued_fraction(&5_i64, 20).unwrap().to_latex(), "5");
+    }
+
+    #[test]
+    fn test_rational_field_conversion() {
+        assert_eq!(
+            Rational64::new(1, 3).approx(),
+            Some(Rational64::new(333, 1000))
+        );
+        assert_eq!(Rational64::new(1, 3).exact(), Some(Rational64::new(1, 3)));
+    }
 }
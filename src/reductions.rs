@@ -0,0 +1,170 @@
+use crate::errors::Error;
+use crate::float::Float64;
+use crate::matrices::Matrix;
+use crate::traits::MatrixNumber;
+use anyhow::bail;
+
+fn checked_sum<'a, T: MatrixNumber + 'a>(
+    values: impl IntoIterator<Item = &'a T>,
+) -> anyhow::Result<T> {
+    values
+        .into_iter()
+        .try_fold(T::zero(), |sum, x| sum.checked_add(x))
+        .ok_or_else(|| T::checked_ops_error().into())
+}
+
+/// The larger of two numbers, the first one if they are equal.
+fn larger<T: MatrixNumber>(a: T, b: &T) -> anyhow::Result<T> {
+    let difference = b.checked_sub(&a).ok_or_else(T::checked_ops_error)?;
+    Ok(if difference.is_positive() {
+        b.clone()
+    } else {
+        a
+    })
+}
+
+/// The smaller of two numbers, the first one if they are equal.
+fn smaller<T: MatrixNumber>(a: T, b: &T) -> anyhow::Result<T> {
+    let difference = b.checked_sub(&a).ok_or_else(T::checked_ops_error)?;
+    Ok(if difference.is_negative() {
+        b.clone()
+    } else {
+        a
+    })
+}
+
+impl<T: MatrixNumber> Matrix<T> {
+    /// Returns the trace - the sum of the entries on the diagonal of a square
+    /// matrix.
+    pub fn trace(&self) -> anyhow::Result<T> {
+        let (rows, cols) = self.get_shape();
+        if rows != cols {
+            bail!(Error::NotSquare);
+        }
+        checked_sum((0..rows).map(|i| &self.get_data()[i][i]))
+    }
+
+    /// Returns the sum of all entries.
+    pub fn sum(&self) -> anyhow::Result<T> {
+        checked_sum(self.get_data().iter().flatten())
+    }
+
+    /// Returns the column vector of the sums of the rows.
+    pub fn row_sums(&self) -> anyhow::Result<Self> {
+        let sums = self
+            .get_data()
+            .iter()
+            .map(|row| Ok(vec![checked_sum(row)?]))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Matrix::new_unsafe(sums))
+    }
+
+    /// Returns the row vector of the sums of the columns.
+    pub fn column_sums(&self) -> anyhow::Result<Self> {
+        Ok(self.transpose().row_sums()?.transpose())
+    }
+
+    /// Returns the largest entry, fails for empty matrices.
+    pub fn max(&self) -> anyhow::Result<T> {
+        self.reduce(larger)
+    }
+
+    /// Returns the smallest entry, fails for empty matrices.
+    pub fn min(&self) -> anyhow::Result<T> {
+        self.reduce(smaller)
+    }
+
+    fn reduce(&self, choose: impl Fn(T, &T) -> anyhow::Result<T>) -> anyhow::Result<T> {
+        let mut entries = self.get_data().iter().flatten();
+        let first = entries.next().ok_or(Error::EmptyMatrix)?.clone();
+        entries.try_fold(first, choose)
+    }
+
+    /// Returns the matrix of the absolute values of the entries.
+    fn abs(&self) -> Self {
+        Matrix::filled(self.get_shape(), |i, j| self.get_data()[i][j].abs())
+    }
+
+    /// Returns the 1-norm - the largest sum of the absolute values in a
+    /// column.
+    pub fn norm_1(&self) -> anyhow::Result<T> {
+        self.abs().column_sums()?.max()
+    }
+
+    /// Returns the infinity norm - the largest sum of the absolute values in
+    /// a row.
+    pub fn norm_inf(&self) -> anyhow::Result<T> {
+        self.abs().row_sums()?.max()
+    }
+
+    /// Returns the sum of the squares of all entries - the square of the
+    /// Frobenius norm.
+    pub fn sum_of_squares(&self) -> anyhow::Result<T> {
+        self.get_data()
+            .iter()
+            .flatten()
+            .try_fold(T::zero(), |sum, x| sum.checked_add(&x.checked_mul(x)?))
+            .ok_or_else(|| T::checked_ops_error().into())
+    }
+
+    /// Returns the Frobenius norm `\sqrt{\sum_{i,j} a_{ij}^2}`, which is
+    /// generally irrational, so it is approximated.
+    pub fn frobenius_norm(&self) -> anyhow::Result<Float64> {
+        let square = self
+            .sum_of_squares()?
+            .to_f64()
+            .ok_or(Error::ConversionFailed)?;
+        Ok(Float64::from(square.sqrt()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{im, ri, rm, rv};
+    use num_rational::Rational64;
+
+    #[test]
+    fn test_sums() {
+        let m = im![1, -2, 3; -4, 5, -6; 7, 8, 9];
+        assert_eq!(m.trace().unwrap(), 15);
+        assert_eq!(m.sum().unwrap(), 21);
+        assert_eq!(m.row_sums().unwrap(), im![2; -5; 24]);
+        assert_eq!(m.column_sums().unwrap(), im![4, 11, 6]);
+        assert!(im![1, 2].trace().is_err());
+        assert_eq!(Matrix::<i64>::empty().trace().unwrap(), 0);
+        assert!(Matrix::new_unsafe(vec![vec![i64::MAX], vec![1]])
+            .sum()
+            .is_err());
+    }
+
+    #[test]
+    fn test_min_max() {
+        let m = rm![1, -2; 3, 0]
+            .checked_mul_scl(&Rational64::new(1, 3))
+            .unwrap();
+        assert_eq!(m.max().unwrap(), ri!(1));
+        assert_eq!(m.min().unwrap(), Rational64::new(-2, 3));
+        assert!(matches!(
+            Matrix::<i64>::empty()
+                .max()
+                .unwrap_err()
+                .downcast::<Error>()
+                .unwrap(),
+            Error::EmptyMatrix
+        ));
+    }
+
+    #[test]
+    fn test_norms() {
+        let m = im![1, -2; -3, 4];
+        assert_eq!(m.norm_1().unwrap(), 6);
+        assert_eq!(m.norm_inf().unwrap(), 7);
+        assert_eq!(m.sum_of_squares().unwrap(), 30);
+        assert_eq!(m.frobenius_norm().unwrap(), Float64::from(30_f64.sqrt()));
+        assert_eq!(
+            rm![3, 0; 0, -4].frobenius_norm().unwrap(),
+            Float64::from(5.)
+        );
+    }
+}
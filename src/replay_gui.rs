@@ -0,0 +1,176 @@
+use crate::constants::{
+    FONT_ID, MODIFIED_ROW_COLOR, PIVOT_COLOR, REPLAY_STEP_DURATION, TEXT_COLOR, VALUE_PADDING,
+};
+use crate::locale::Locale;
+use crate::matrices::Matrix;
+use crate::matrix_algorithms::Aftermath;
+use crate::steps::Step;
+use crate::traits::{BoxedShape, MatrixNumber};
+use crate::State;
+use egui::{vec2, Color32};
+use std::time::Duration;
+
+pub struct ReplayContent<K: MatrixNumber> {
+    title: String,
+    initial: Matrix<K>,
+    steps: Vec<Step<K>>,
+    current: usize,
+    playing: bool,
+    last_switch: f64,
+}
+
+pub struct ReplayState<K: MatrixNumber> {
+    replay_content: Option<ReplayContent<K>>,
+}
+
+impl<K: MatrixNumber> Default for ReplayState<K> {
+    fn default() -> Self {
+        Self {
+            replay_content: None,
+        }
+    }
+}
+
+/// Opens the replay window with the steps of Gaussian elimination.
+pub fn set_replay<K: MatrixNumber>(
+    state: &mut ReplayState<K>,
+    title: String,
+    Aftermath { initial, steps, .. }: Aftermath<K>,
+) {
+    state.replay_content = if initial.is_empty() {
+        None
+    } else {
+        Some(ReplayContent {
+            title,
+            initial,
+            steps,
+            current: 0,
+            playing: false,
+            last_switch: 0.,
+        })
+    };
+}
+
+pub fn display_replay<K: MatrixNumber>(ctx: &egui::Context, state: &mut State<K>, locale: &Locale) {
+    if let Some(content) = &mut state.replay.replay_content {
+        if !display_replay_is_some(ctx, content, locale) {
+            state.replay.replay_content = None;
+        }
+    }
+}
+
+/// Displays the replay window, returns whether it should stay open.
+fn display_replay_is_some<K: MatrixNumber>(
+    ctx: &egui::Context,
+    content: &mut ReplayContent<K>,
+    locale: &Locale,
+) -> bool {
+    let mut replay_opened = true;
+    let now = ctx.input(|i| i.time);
+    // The initial matrix is the first frame.
+    let last = content.steps.len();
+
+    if content.playing {
+        if now - content.last_switch >= REPLAY_STEP_DURATION.as_secs_f64() {
+            content.current = (content.current + 1).min(last);
+            content.last_switch = now;
+        }
+        content.playing = content.current < last;
+        ctx.request_repaint_after(Duration::from_millis(50));
+    }
+
+    egui::Window::new(locale.get_translated("Replay") + " " + &content.title)
+        .open(&mut replay_opened)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(content.current > 0, egui::Button::new("⏮"))
+                    .on_hover_text(locale.get_translated("Previous"))
+                    .clicked()
+                {
+                    content.current -= 1;
+                    content.playing = false;
+                }
+                let play_label = if content.playing {
+                    locale.get_translated("Pause")
+                } else {
+                    locale.get_translated("Play")
+                };
+                if ui.button(play_label).clicked() {
+                    if !content.playing && content.current == last {
+                        content.current = 0;
+                    }
+                    content.playing = !content.playing;
+                    content.last_switch = now;
+                }
+                if ui
+                    .add_enabled(content.current < last, egui::Button::new("⏭"))
+                    .on_hover_text(locale.get_translated("Next"))
+                    .clicked()
+                {
+                    content.current += 1;
+                    content.playing = false;
+                }
+                ui.label(format!(
+                    "{} {} / {} ({})",
+                    locale.get_translated("Step"),
+                    content.current + 1,
+                    last + 1,
+                    locale.get_plural("{count} steps", content.steps.len(), &[])
+                ));
+            });
+
+            let (matrix, pivot, modified_rows) = match content.current.checked_sub(1) {
+                Some(index) => {
+                    let step = &content.steps[index];
+                    ui.label(step.describe(locale));
+                    (
+                        &step.matrix,
+                        Some(step.pivot),
+                        step.operation.modified_rows(),
+                    )
+                }
+                None => {
+                    ui.label(locale.get_translated("Initial matrix"));
+                    (&content.initial, None, vec![])
+                }
+            };
+            let mut shape = matrix.to_shape_colored(ctx, FONT_ID, |i, j| {
+                step_cell_color(pivot, &modified_rows, (i, j))
+            });
+            let rect = shape.get_rect();
+
+            ui.set_min_width(rect.width() + 2. * VALUE_PADDING);
+            ui.separator();
+
+            let bar_height = ui.min_size().y;
+            ui.add_space(rect.height() + VALUE_PADDING);
+
+            shape.translate(
+                ui.clip_rect().min.to_vec2()
+                    + vec2(
+                        (ui.min_size().x - rect.width()) / 2.,
+                        bar_height + VALUE_PADDING,
+                    ),
+            );
+            ui.painter().add(shape);
+        });
+
+    replay_opened
+}
+
+/// Color of a cell - the pivot and the modified rows are highlighted.
+fn step_cell_color(
+    pivot: Option<(usize, usize)>,
+    modified_rows: &[usize],
+    (i, j): (usize, usize),
+) -> Color32 {
+    if pivot == Some((i, j)) {
+        PIVOT_COLOR
+    } else if modified_rows.contains(&i) {
+        MODIFIED_ROW_COLOR
+    } else {
+        TEXT_COLOR
+    }
+}
@@ -0,0 +1,534 @@
+use crate::locale::Locale;
+use crate::matrices::Matrix;
+use crate::traits::{ExportFormat, Exportable, LaTeXable, MatrixNumber};
+
+/// Symbol of a row used in the transitions when no language is chosen,
+/// e.g. `w_{1} \leftrightarrow w_{2}`.
+const ROW_SYMBOL: &str = "w";
+
+/// Language-dependent LaTeX notation of the row operations - the symbol of a
+/// row and optional `\text{...}` annotations placed under the arrows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notation {
+    row_symbol: String,
+    annotations: Option<Annotations>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Annotations {
+    swap: String,
+    scale: String,
+    add_multiple: String,
+}
+
+impl Default for Notation {
+    fn default() -> Self {
+        Self {
+            row_symbol: ROW_SYMBOL.to_string(),
+            annotations: None,
+        }
+    }
+}
+
+impl Notation {
+    /// Notation in the language of the locale, annotated if `annotated` is set.
+    pub fn new(locale: &Locale, annotated: bool) -> Self {
+        Self {
+            row_symbol: locale.get_translated("Row symbol"),
+            annotations: annotated.then(|| Annotations {
+                swap: locale.get_translated("swap rows"),
+                scale: locale.get_translated("divide row by"),
+                add_multiple: locale.get_translated("add multiples of rows"),
+            }),
+        }
+    }
+
+    fn row(&self, index: usize) -> String {
+        format!("{}_{{{}}}", self.row_symbol, index + 1)
+    }
+
+    fn typst_row(&self, index: usize) -> String {
+        format!("{}_({})", self.row_symbol, index + 1)
+    }
+
+    fn mathml_row(&self, index: usize) -> String {
+        format!(
+            "<msub><mi>{}</mi><mn>{}</mn></msub>",
+            self.row_symbol,
+            index + 1
+        )
+    }
+}
+
+/// Elementary row operation. Rows are indexed from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowOperation<T: MatrixNumber> {
+    /// Swaps two rows.
+    Swap(usize, usize),
+    /// Divides the row by the (nonzero) divisor.
+    Scale { row: usize, divisor: T },
+    /// Adds the `source` row multiplied by the coefficient to the `target` row.
+    AddMultiple {
+        target: usize,
+        source: usize,
+        coefficient: T,
+    },
+    /// Several operations performed at once, e.g. elimination of a whole column.
+    Combined(Vec<RowOperation<T>>),
+}
+
+impl<T: MatrixNumber> RowOperation<T> {
+    /// Returns the rows changed by the operation.
+    pub fn modified_rows(&self) -> Vec<usize> {
+        match self {
+            RowOperation::Swap(i, j) => vec![*i, *j],
+            RowOperation::Scale { row, .. } => vec![*row],
+            RowOperation::AddMultiple { target, .. } => vec![*target],
+            RowOperation::Combined(operations) => operations
+                .iter()
+                .flat_map(RowOperation::modified_rows)
+                .collect(),
+        }
+    }
+
+    /// Plain text representation, e.g. `w2 - 3/4 w1`.
+    pub fn to_plain(&self) -> String {
+        match self {
+            RowOperation::Swap(i, j) => format!("w{} <-> w{}", i + 1, j + 1),
+            RowOperation::Scale { row, divisor } => {
+                format!("w{} : {}", row + 1, divisor.to_plain())
+            }
+            RowOperation::AddMultiple {
+                target,
+                source,
+                coefficient,
+            } => {
+                let (sign, abs) = split_sign(coefficient);
+                let multiple = match abs {
+                    Some(abs) if abs.is_one() => String::new(),
+                    Some(abs) => abs.to_plain() + " ",
+                    None => format!("({}) ", coefficient.to_plain()),
+                };
+                format!("w{} {sign} {multiple}w{}", target + 1, source + 1)
+            }
+            RowOperation::Combined(operations) => operations
+                .iter()
+                .map(RowOperation::to_plain)
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    /// Natural language description of the operation in the given language,
+    /// e.g. "Subtract 3 times row 1 from row 2".
+    pub fn describe(&self, locale: &Locale) -> String {
+        match self {
+            RowOperation::Swap(i, j) => locale.get_translated_with(
+                "Swap rows {first} and {second}",
+                &[
+                    ("first", (i + 1).to_string()),
+                    ("second", (j + 1).to_string()),
+                ],
+            ),
+            RowOperation::Scale { row, divisor } => locale.get_translated_with(
+                "Divide row {row} by {divisor}",
+                &[
+                    ("row", (row + 1).to_string()),
+                    ("divisor", divisor.to_plain()),
+                ],
+            ),
+            RowOperation::AddMultiple {
+                target,
+                source,
+                coefficient,
+            } => {
+                let (sign, abs) = split_sign(coefficient);
+                let abs = abs.unwrap_or_else(|| coefficient.clone());
+                let args = [
+                    ("target", (target + 1).to_string()),
+                    ("source", (source + 1).to_string()),
+                    ("coefficient", abs.to_plain()),
+                ];
+                match (sign, abs.is_one()) {
+                    ('+', true) => {
+                        locale.get_translated_with("Add row {source} to row {target}", &args)
+                    }
+                    ('+', false) => locale.get_translated_with(
+                        "Add {coefficient} times row {source} to row {target}",
+                        &args,
+                    ),
+                    (_, true) => {
+                        locale.get_translated_with("Subtract row {source} from row {target}", &args)
+                    }
+                    (_, false) => locale.get_translated_with(
+                        "Subtract {coefficient} times row {source} from row {target}",
+                        &args,
+                    ),
+                }
+            }
+            RowOperation::Combined(operations) => operations
+                .iter()
+                .map(|operation| operation.describe(locale))
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+
+    /// LaTeX representation in the given notation. Additions of row
+    /// multiples are stacked, e.g. `\substack{w_{2} - w_{1}\\w_{3} + w_{1}}`.
+    pub fn to_latex_with(&self, notation: &Notation) -> String {
+        match self {
+            RowOperation::Swap(..) | RowOperation::Scale { .. } => self.to_latex_inline(notation),
+            RowOperation::AddMultiple { .. } | RowOperation::Combined(_) => {
+                format!(r"\substack{{{}}}", self.to_latex_inline(notation))
+            }
+        }
+    }
+
+    /// Translated annotation of the operation, if enabled.
+    fn annotation_text<'a>(&self, notation: &'a Notation) -> Option<&'a str> {
+        let annotations = notation.annotations.as_ref()?;
+        Some(match self {
+            RowOperation::Swap(..) => &annotations.swap,
+            RowOperation::Scale { .. } => &annotations.scale,
+            RowOperation::AddMultiple { .. } | RowOperation::Combined(_) => {
+                &annotations.add_multiple
+            }
+        })
+    }
+
+    /// Translated `\text{...}` annotation of the operation, if enabled.
+    fn annotation(&self, notation: &Notation) -> Option<String> {
+        self.annotation_text(notation)
+            .map(|text| format!(r"\text{{{text}}}"))
+    }
+
+    /// Typst representation in the given notation, e.g. `w_(2) - 3 w_(1)`.
+    /// Combined operations are separated with commas.
+    pub fn to_typst_with(&self, notation: &Notation) -> String {
+        match self {
+            RowOperation::Swap(i, j) => {
+                format!("{} <-> {}", notation.typst_row(*i), notation.typst_row(*j))
+            }
+            RowOperation::Scale { row, divisor } => {
+                let divisor = match divisor.is_negative() {
+                    true => format!("({})", divisor.to_typst()),
+                    false => divisor.to_typst(),
+                };
+                format!("{} : {divisor}", notation.typst_row(*row))
+            }
+            RowOperation::AddMultiple {
+                target,
+                source,
+                coefficient,
+            } => {
+                let (sign, abs) = split_sign(coefficient);
+                let multiple = match abs {
+                    Some(abs) if abs.is_one() => String::new(),
+                    Some(abs) => abs.to_typst() + " ",
+                    None => format!("({}) ", coefficient.to_typst()),
+                };
+                format!(
+                    "{} {sign} {multiple}{}",
+                    notation.typst_row(*target),
+                    notation.typst_row(*source)
+                )
+            }
+            RowOperation::Combined(operations) => operations
+                .iter()
+                .map(|operation| operation.to_typst_with(notation))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    /// Presentation MathML fragment in the given notation. Combined
+    /// operations are separated with commas.
+    pub fn to_mathml_with(&self, notation: &Notation) -> String {
+        match self {
+            RowOperation::Swap(i, j) => format!(
+                "{}<mo>↔</mo>{}",
+                notation.mathml_row(*i),
+                notation.mathml_row(*j)
+            ),
+            RowOperation::Scale { row, divisor } => {
+                let divisor = match divisor.is_negative() {
+                    true => format!("<mo>(</mo>{}<mo>)</mo>", divisor.to_mathml()),
+                    false => divisor.to_mathml(),
+                };
+                format!("{}<mo>:</mo>{divisor}", notation.mathml_row(*row))
+            }
+            RowOperation::AddMultiple {
+                target,
+                source,
+                coefficient,
+            } => {
+                let (sign, abs) = split_sign(coefficient);
+                let multiple = match abs {
+                    Some(abs) if abs.is_one() => String::new(),
+                    Some(abs) => abs.to_mathml(),
+                    None => format!("<mo>(</mo>{}<mo>)</mo>", coefficient.to_mathml()),
+                };
+                format!(
+                    "{}<mo>{sign}</mo>{multiple}{}",
+                    notation.mathml_row(*target),
+                    notation.mathml_row(*source)
+                )
+            }
+            RowOperation::Combined(operations) => operations
+                .iter()
+                .map(|operation| operation.to_mathml_with(notation))
+                .collect::<Vec<_>>()
+                .join("<mo>,</mo>"),
+        }
+    }
+
+    /// LaTeX representation of a single operation without stacking.
+    fn to_latex_inline(&self, notation: &Notation) -> String {
+        match self {
+            RowOperation::Swap(i, j) => {
+                format!(r"{} \leftrightarrow {}", notation.row(*i), notation.row(*j))
+            }
+            RowOperation::Scale { row, divisor } => {
+                format!(r"{} : {}", notation.row(*row), divisor.to_latex_single())
+            }
+            RowOperation::AddMultiple {
+                target,
+                source,
+                coefficient,
+            } => {
+                let (sign, abs) = split_sign(coefficient);
+                let multiple = match abs {
+                    Some(abs) if abs.is_one() => String::new(),
+                    Some(abs) => abs.to_latex(),
+                    None => coefficient.to_latex_single(),
+                };
+                format!(
+                    "{} {sign} {multiple}{}",
+                    notation.row(*target),
+                    notation.row(*source)
+                )
+            }
+            RowOperation::Combined(operations) => operations
+                .iter()
+                .map(|operation| operation.to_latex_inline(notation))
+                .collect::<Vec<_>>()
+                .join(r"\\"),
+        }
+    }
+}
+
+impl<T: MatrixNumber> LaTeXable for RowOperation<T> {
+    fn to_latex(&self) -> String {
+        self.to_latex_with(&Notation::default())
+    }
+}
+
+/// Returns the sign of the coefficient (`+` or `-`) and its absolute value,
+/// which is `None` if it cannot be represented.
+fn split_sign<T: MatrixNumber>(coefficient: &T) -> (char, Option<T>) {
+    if coefficient.is_negative() {
+        ('-', T::zero().checked_sub(coefficient))
+    } else {
+        ('+', Some(coefficient.clone()))
+    }
+}
+
+/// Single step of a derivation - the row operation, the pivot it was based
+/// on and the resulting matrix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<T: MatrixNumber> {
+    pub operation: RowOperation<T>,
+    pub pivot: (usize, usize),
+    pub matrix: Matrix<T>,
+}
+
+impl<T: MatrixNumber> Step<T> {
+    /// Plain text representation - the operation followed by the matrix drawn
+    /// with box-drawing characters.
+    pub fn to_plain(&self) -> String {
+        format!(
+            "{}\n{}",
+            self.operation.to_plain(),
+            self.matrix.export(ExportFormat::Ascii)
+        )
+    }
+
+    pub fn describe(&self, locale: &Locale) -> String {
+        self.operation.describe(locale)
+    }
+
+    /// LaTeX representation in the given notation, the annotation (if any)
+    /// is placed under the arrow.
+    pub fn to_latex_with(&self, notation: &Notation) -> String {
+        let annotation = self
+            .operation
+            .annotation(notation)
+            .map(|text| format!("[{text}]"))
+            .unwrap_or_default();
+        format!(
+            r"\xrightarrow{annotation}{{{}}} {}",
+            self.operation.to_latex_with(notation),
+            self.matrix.to_latex()
+        )
+    }
+
+    /// Typst representation in the given notation, the annotation (if any)
+    /// is placed under the arrow.
+    pub fn to_typst_with(&self, notation: &Notation) -> String {
+        let annotation = self
+            .operation
+            .annotation_text(notation)
+            .map(|text| format!("_(\"{text}\")"))
+            .unwrap_or_default();
+        format!(
+            "limits(arrow.r.long)^({}){annotation} {}",
+            self.operation.to_typst_with(notation),
+            self.matrix.to_typst()
+        )
+    }
+
+    /// Presentation MathML fragment in the given notation, the annotation
+    /// (if any) is placed under the arrow.
+    pub fn to_mathml_with(&self, notation: &Notation) -> String {
+        let arrow = r#"<mo stretchy="true">⟶</mo>"#;
+        let operation = format!("<mrow>{}</mrow>", self.operation.to_mathml_with(notation));
+        let arrow = match self.operation.annotation_text(notation) {
+            Some(text) => {
+                format!("<munderover>{arrow}<mtext>{text}</mtext>{operation}</munderover>")
+            }
+            None => format!("<mover>{arrow}{operation}</mover>"),
+        };
+        format!("{arrow}{}", self.matrix.to_mathml())
+    }
+}
+
+impl<T: MatrixNumber> LaTeXable for Step<T> {
+    fn to_latex(&self) -> String {
+        self.to_latex_with(&Notation::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::Language;
+    use crate::{ri, rm, rv};
+    use num_rational::Rational64;
+
+    fn combined() -> RowOperation<Rational64> {
+        RowOperation::Combined(vec![
+            RowOperation::AddMultiple {
+                target: 1,
+                source: 0,
+                coefficient: ri!(-1),
+            },
+            RowOperation::AddMultiple {
+                target: 2,
+                source: 0,
+                coefficient: Rational64::new(3, 4),
+            },
+        ])
+    }
+
+    #[test]
+    fn test_operation_latex() {
+        assert_eq!(
+            RowOperation::Swap::<Rational64>(0, 2).to_latex(),
+            r"w_{1} \leftrightarrow w_{3}"
+        );
+        assert_eq!(
+            RowOperation::Scale {
+                row: 1,
+                divisor: ri!(-2)
+            }
+            .to_latex(),
+            r"w_{2} : \left(-2\right)"
+        );
+        assert_eq!(
+            combined().to_latex(),
+            r"\substack{w_{2} - w_{1}\\w_{3} + \frac{3}{4}w_{1}}"
+        );
+    }
+
+    #[test]
+    fn test_operation_plain() {
+        assert_eq!(
+            RowOperation::Swap::<Rational64>(0, 2).to_plain(),
+            "w1 <-> w3"
+        );
+        assert_eq!(combined().to_plain(), "w2 - w1, w3 + 3/4 w1");
+        assert_eq!(combined().modified_rows(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_operation_describe() {
+        let locale = Locale::new(Language::default());
+
+        assert_eq!(
+            RowOperation::Scale {
+                row: 0,
+                divisor: Rational64::new(1, 2)
+            }
+            .describe(&locale),
+            "Divide row 1 by 1/2"
+        );
+        assert_eq!(
+            combined().describe(&locale),
+            "Subtract row 1 from row 2; Add 3/4 times row 1 to row 3"
+        );
+    }
+
+    #[test]
+    fn test_step_render() {
+        let step = Step {
+            operation: RowOperation::Swap(0, 1),
+            pivot: (0, 0),
+            matrix: rm![1, 0; 0, 1],
+        };
+
+        assert_eq!(
+            step.to_latex(),
+            r"\xrightarrow{w_{1} \leftrightarrow w_{2}} \left[\begin{array}{cc}1 & 0\\0 & 1\end{array}\right]"
+        );
+        assert!(step.to_plain().starts_with("w1 <-> w2\n┌"));
+        assert_eq!(
+            step.to_typst_with(&Notation::default()),
+            r#"limits(arrow.r.long)^(w_(1) <-> w_(2)) mat(delim: "[", 1, 0; 0, 1)"#
+        );
+        assert!(step.to_mathml_with(&Notation::default()).starts_with(
+            r#"<mover><mo stretchy="true">⟶</mo><mrow><msub><mi>w</mi><mn>1</mn></msub><mo>↔</mo>"#
+        ));
+    }
+
+    #[test]
+    fn test_operation_typst_and_mathml() {
+        let notation = Notation::default();
+        assert_eq!(
+            combined().to_typst_with(&notation),
+            "w_(2) - w_(1), w_(3) + frac(3, 4) w_(1)"
+        );
+        assert_eq!(
+            RowOperation::Scale {
+                row: 1,
+                divisor: ri!(-2)
+            }
+            .to_typst_with(&notation),
+            "w_(2) : (-2)"
+        );
+        assert_eq!(
+            RowOperation::Scale {
+                row: 0,
+                divisor: ri!(-2)
+            }
+            .to_mathml_with(&notation),
+            "<msub><mi>w</mi><mn>1</mn></msub><mo>:</mo><mo>(</mo><mo>-</mo><mn>2</mn><mo>)</mo>"
+        );
+        assert_eq!(
+            combined().to_mathml_with(&notation),
+            "<msub><mi>w</mi><mn>2</mn></msub><mo>-</mo><msub><mi>w</mi><mn>1</mn></msub><mo>,</mo>\
+             <msub><mi>w</mi><mn>3</mn></msub><mo>+</mo><mfrac><mn>3</mn><mn>4</mn></mfrac>\
+             <msub><mi>w</mi><mn>1</mn></msub>"
+        );
+    }
+}
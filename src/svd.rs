@@ -0,0 +1,301 @@
+use crate::constants::MAX_JACOBI_SWEEPS;
+use crate::decompositions::Derivation;
+use crate::errors::Error;
+use crate::float::{Float64, Tolerance};
+use crate::matrices::Matrix;
+use anyhow::bail;
+
+/// Singular value decomposition `A = U \Sigma V^T` of an `m x n` matrix with
+/// `k = min(m, n)` singular values in descending order. `U` is `m x k` and `V`
+/// is `n x k`, their columns belonging to nonzero singular values are
+/// orthonormal.
+#[derive(Debug, Clone)]
+pub struct Svd {
+    pub u: Matrix<Float64>,
+    pub singular_values: Vec<Float64>,
+    pub v: Matrix<Float64>,
+}
+
+/// Columns of a matrix of floats.
+type Columns = Vec<Vec<f64>>;
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+/// Rotates the columns `p` and `q` by the angle with cosine `c` and sine `s`.
+fn rotate(columns: &mut [Vec<f64>], (p, q): (usize, usize), (c, s): (f64, f64)) {
+    let (left, right) = columns.split_at_mut(q);
+    for (x, y) in left[p].iter_mut().zip(right[0].iter_mut()) {
+        (*x, *y) = (c * *x - s * *y, s * *x + c * *y);
+    }
+}
+
+/// One-sided Jacobi (Hestenes) method - rotates pairs of columns until all of
+/// them are orthogonal. The norms of the resulting columns are the singular
+/// values, the accumulated rotations form `V`. Expects at least as many rows
+/// as columns.
+fn jacobi(mut u: Columns) -> anyhow::Result<(Columns, Vec<f64>, Columns)> {
+    let n = u.len();
+    let mut v: Columns = (0..n)
+        .map(|j| (0..n).map(|i| if i == j { 1. } else { 0. }).collect())
+        .collect();
+
+    for _ in 0..MAX_JACOBI_SWEEPS {
+        let mut rotated = false;
+        for p in 0..n {
+            for q in p + 1..n {
+                let alpha = dot(&u[p], &u[p]);
+                let beta = dot(&u[q], &u[q]);
+                let gamma = dot(&u[p], &u[q]);
+                if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                    continue;
+                }
+                rotated = true;
+                let zeta = (beta - alpha) / (2. * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1. + zeta * zeta).sqrt());
+                let c = 1. / (1. + t * t).sqrt();
+                rotate(&mut u, (p, q), (c, c * t));
+                rotate(&mut v, (p, q), (c, c * t));
+            }
+        }
+        if !rotated {
+            let sigma: Vec<f64> = u.iter().map(|column| dot(column, column).sqrt()).collect();
+            for (column, &s) in u.iter_mut().zip(&sigma) {
+                column
+                    .iter_mut()
+                    .for_each(|x| *x = if s > 0. { *x / s } else { 0. });
+            }
+            let mut order: Vec<usize> = (0..n).collect();
+            order.sort_by(|&i, &j| sigma[j].total_cmp(&sigma[i]));
+            return Ok((
+                order.iter().map(|&i| u[i].clone()).collect(),
+                order.iter().map(|&i| sigma[i]).collect(),
+                order.iter().map(|&i| v[i].clone()).collect(),
+            ));
+        }
+    }
+    bail!(Error::NotConverged)
+}
+
+fn float_columns(m: &Matrix<Float64>) -> Columns {
+    m.columns()
+        .into_iter()
+        .map(|column| column.into_iter().map(f64::from).collect())
+        .collect()
+}
+
+fn float_matrix(columns: Columns) -> Matrix<Float64> {
+    Matrix::from_columns(
+        &columns
+            .into_iter()
+            .map(|column| column.into_iter().map(Float64::from).collect())
+            .collect::<Vec<_>>(),
+    )
+    .snapped()
+}
+
+impl Svd {
+    /// Singular values larger than this are considered nonzero - the
+    /// absolute tolerance or the relative tolerance times the largest
+    /// singular value, whichever is larger.
+    fn threshold(&self) -> f64 {
+        let tolerance = Tolerance::current();
+        let largest = self.singular_values.first().map_or(0., |&s| f64::from(s));
+        tolerance.absolute.max(tolerance.relative * largest)
+    }
+
+    /// Number of singular values above the tolerance.
+    pub fn rank(&self) -> usize {
+        let threshold = self.threshold();
+        self.singular_values
+            .iter()
+            .filter(|&&s| f64::from(s) > threshold)
+            .count()
+    }
+
+    /// The ratio of the largest and the smallest singular value. Fails for
+    /// rank deficient matrices, whose condition number is infinite.
+    pub fn condition_number(&self) -> anyhow::Result<Float64> {
+        match (self.singular_values.first(), self.singular_values.last()) {
+            (Some(&largest), Some(&smallest)) if self.rank() == self.singular_values.len() => {
+                Ok(largest / smallest)
+            }
+            _ => bail!(Error::NotFinite),
+        }
+    }
+
+    /// The Moore-Penrose pseudo-inverse `V \Sigma^+ U^T`, where `\Sigma^+`
+    /// inverts the singular values above the tolerance and keeps the others
+    /// zero.
+    pub fn pseudo_inverse(&self) -> Matrix<Float64> {
+        let threshold = self.threshold();
+        let inverted: Vec<f64> = self
+            .singular_values
+            .iter()
+            .map(|&s| f64::from(s))
+            .map(|s| if s > threshold { 1. / s } else { 0. })
+            .collect();
+        let (u, v) = (self.u.get_data(), self.v.get_data());
+        Matrix::filled((v.len(), u.len()), |i, j| {
+            Float64::from(
+                inverted
+                    .iter()
+                    .enumerate()
+                    .map(|(k, s)| f64::from(v[i][k]) * s * f64::from(u[j][k]))
+                    .sum::<f64>(),
+            )
+        })
+        .snapped()
+    }
+
+    /// The diagonal matrix of the singular values.
+    pub fn sigma(&self) -> Matrix<Float64> {
+        let k = self.singular_values.len();
+        Matrix::filled((k, k), |i, j| match i == j {
+            true => self.singular_values[i],
+            false => Float64::from(0.),
+        })
+    }
+
+    /// Derivation of the decomposed matrix with the results `U`, `\Sigma` and
+    /// `V`.
+    pub fn derivation(&self, initial: &Matrix<Float64>) -> Derivation<Float64> {
+        Derivation {
+            initial: initial.clone(),
+            equations: vec![],
+            results: vec![
+                ("U".to_string(), self.u.clone()),
+                (r"\Sigma".to_string(), self.sigma()),
+                ("V".to_string(), self.v.clone()),
+            ],
+        }
+    }
+}
+
+impl Matrix<Float64> {
+    /// Returns the singular value decomposition of the matrix, computed with
+    /// the one-sided Jacobi method.
+    pub fn svd(&self) -> anyhow::Result<Svd> {
+        let (rows, cols) = self.get_shape();
+        let (u, sigma, v) = match rows >= cols {
+            true => jacobi(float_columns(self))?,
+            // A^T = U' S V'^T implies A = V' S U'^T
+            false => {
+                let (u, sigma, v) = jacobi(float_columns(&self.transpose()))?;
+                (v, sigma, u)
+            }
+        };
+        Ok(Svd {
+            u: float_matrix(u),
+            singular_values: sigma.into_iter().map(Float64::from).collect(),
+            v: float_matrix(v),
+        })
+    }
+
+    /// Returns the Moore-Penrose pseudo-inverse of the matrix, which is the
+    /// inverse for invertible matrices.
+    pub fn pinv(&self) -> anyhow::Result<Self> {
+        Ok(self.svd()?.pseudo_inverse())
+    }
+
+    /// Returns the condition number in the 2-norm.
+    pub fn cond(&self) -> anyhow::Result<Float64> {
+        self.svd()?.condition_number()
+    }
+
+    /// Returns the numerical rank - the number of singular values above the
+    /// tolerance set by [`Tolerance::set`].
+    pub fn rank(&self) -> anyhow::Result<usize> {
+        Ok(self.svd()?.rank())
+    }
+
+    /// Returns the least squares solution `x` minimizing `|Ax - b|`, with the
+    /// smallest norm if there are many.
+    pub fn lstsq(&self, b: &Self) -> anyhow::Result<Self> {
+        if self.get_shape().0 != b.get_shape().0 {
+            bail!(Error::DifferentShapes);
+        }
+        self.pinv()?.checked_mul(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fm;
+
+    #[test]
+    fn test_svd() {
+        for a in [
+            fm![3., 2., 2.; 2., 3., -2.],
+            fm![3., 2.; 2., 3.; 2., -2.],
+            fm![1., 2.; 2., 4.],
+        ] {
+            let svd = a.svd().unwrap();
+            let product = svd
+                .u
+                .checked_mul(&svd.sigma())
+                .and_then(|us| us.checked_mul(&svd.v.transpose()))
+                .unwrap();
+            assert_eq!(product, a);
+        }
+
+        let svd = fm![3., 2., 2.; 2., 3., -2.].svd().unwrap();
+        assert_eq!(
+            svd.singular_values,
+            vec![Float64::from(5.), Float64::from(3.)]
+        );
+        assert_eq!(svd.u.get_shape(), (2, 2));
+        assert_eq!(svd.v.get_shape(), (3, 2));
+        assert_eq!(
+            svd.v.transpose().checked_mul(&svd.v).unwrap(),
+            Matrix::identity(2)
+        );
+    }
+
+    #[test]
+    fn test_rank_and_cond() {
+        let singular = fm![1., 2., 3.; 4., 5., 6.; 7., 8., 9.];
+        assert_eq!(singular.rank().unwrap(), 2);
+        assert!(singular.cond().is_err());
+
+        let diagonal = fm![4., 0.; 0., -0.5];
+        assert_eq!(diagonal.rank().unwrap(), 2);
+        assert_eq!(diagonal.cond().unwrap(), Float64::from(8.));
+        assert_eq!(fm![0., 0.].rank().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pinv() {
+        let invertible = fm![2., 1.; 1., 1.];
+        assert_eq!(
+            invertible.pinv().unwrap(),
+            invertible.inverse().unwrap().result
+        );
+
+        let singular = fm![1., 2.; 2., 4.];
+        let pinv = singular.pinv().unwrap();
+        assert_eq!(pinv, fm![0.04, 0.08; 0.08, 0.16]);
+        // A A^+ A = A
+        assert_eq!(
+            singular
+                .checked_mul(&pinv)
+                .and_then(|m| m.checked_mul(&singular))
+                .unwrap(),
+            singular
+        );
+
+        let wide = fm![1., 0., 1.];
+        assert_eq!(wide.pinv().unwrap(), fm![0.5; 0.; 0.5]);
+    }
+
+    #[test]
+    fn test_lstsq() {
+        // the line through (0, 6), (1, 0), (2, 0) fitted by least squares
+        let a = fm![0., 1.; 1., 1.; 2., 1.];
+        let b = fm![6.; 0.; 0.];
+        assert_eq!(a.lstsq(&b).unwrap(), fm![-3.; 5.]);
+        assert!(a.lstsq(&fm![1.; 2.]).is_err());
+    }
+}
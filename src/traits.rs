@@ -5,8 +5,11 @@ use num_traits::{
 };
 use std::str::FromStr;
 
+use crate::errors::Error;
 use crate::locale::Locale;
+use num_rational::Rational64;
 use num_traits::Num;
+use std::fmt::{Display, Formatter};
 
 pub trait LaTeXable {
     fn to_latex(&self) -> String;
@@ -15,10 +18,132 @@ pub trait LaTeXable {
     }
 }
 
+/// Text formats a value can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExportFormat {
+    #[default]
+    LaTeX,
+    Typst,
+    MathML,
+    Markdown,
+    Csv,
+    Ascii,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 6] = [
+        ExportFormat::LaTeX,
+        ExportFormat::Typst,
+        ExportFormat::MathML,
+        ExportFormat::Markdown,
+        ExportFormat::Csv,
+        ExportFormat::Ascii,
+    ];
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::LaTeX => write!(f, "LaTeX"),
+            ExportFormat::Typst => write!(f, "Typst"),
+            ExportFormat::MathML => write!(f, "MathML"),
+            ExportFormat::Markdown => write!(f, "Markdown"),
+            ExportFormat::Csv => write!(f, "CSV"),
+            ExportFormat::Ascii => write!(f, "ASCII"),
+        }
+    }
+}
+
+/// Export to formats other than LaTeX. Scalars only have to provide the
+/// Typst, MathML and plain text representations, containers (like matrices)
+/// usually override `export` to lay out their elements.
+pub trait Exportable: LaTeXable {
+    /// Typst math mode representation (without the surrounding `$`).
+    fn to_typst(&self) -> String;
+
+    /// Presentation MathML fragment (without the surrounding `<math>` tag).
+    fn to_mathml(&self) -> String;
+
+    /// Plain text representation, used in Markdown exports and plain text
+    /// derivations.
+    fn to_plain(&self) -> String;
+
+    /// Locale-independent and lossless representation, used in CSV and
+    /// ASCII exports, so they can be imported back.
+    fn to_data(&self) -> String {
+        self.to_plain()
+    }
+
+    fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::LaTeX => self.to_latex(),
+            ExportFormat::Typst => self.to_typst(),
+            ExportFormat::MathML => wrap_mathml(&self.to_mathml()),
+            ExportFormat::Markdown => self.to_plain(),
+            ExportFormat::Csv | ExportFormat::Ascii => self.to_data(),
+        }
+    }
+}
+
+/// Wraps a MathML fragment into a standalone `<math>` element.
+pub fn wrap_mathml(fragment: &str) -> String {
+    format!(r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">{fragment}</math>"#)
+}
+
+/// MathML fragment for a (possibly negative) number written in decimal notation.
+pub fn number_to_mathml(number: &str) -> String {
+    match number.strip_prefix('-') {
+        Some(abs) => format!("<mo>-</mo><mn>{abs}</mn>"),
+        None => format!("<mn>{number}</mn>"),
+    }
+}
+
 pub trait CheckedOps: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv {}
 
 impl<T> CheckedOps for T where T: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv {}
 
+/// Conversions used by the `approx` and `exact` functions, which keep the
+/// number type. By default both leave the value unchanged. Algorithms working
+/// over the rationals, e.g. finding eigenvalues, convert with `to_rational` and
+/// `from_rational`.
+pub trait FieldConversion: Clone {
+    /// Whether the numbers are exact, i.e. not floating point.
+    const IS_EXACT: bool = true;
+
+    /// Value rounded the way approximate numbers are displayed.
+    fn approx(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+
+    /// Value replaced with the nearest simple fraction.
+    fn exact(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+
+    /// The value as a rational number, if it is one.
+    fn to_rational(&self) -> Option<Rational64> {
+        None
+    }
+
+    /// Rational number in the number type, if it can be represented.
+    fn from_rational(_value: Rational64) -> Option<Self> {
+        None
+    }
+}
+
+/// Square root within the number type, e.g. only of perfect squares for
+/// integers. Returns `None` if it cannot be represented.
+pub trait CheckedSqrt: Sized {
+    fn checked_sqrt(&self) -> Option<Self>;
+}
+
+/// Error reported when a checked operation of the number type returns `None`.
+pub trait CheckedOpsError {
+    fn checked_ops_error() -> Error {
+        Error::Overflow
+    }
+}
+
 pub trait GuiDisplayable {
     fn display_string(&self, locale: &Locale) -> String;
 
@@ -41,10 +166,14 @@ impl BoxedShape for Shape {
 pub trait MatrixNumber:
     Num
     + CheckedOps
+    + CheckedOpsError
+    + CheckedSqrt
+    + FieldConversion
     + FromPrimitive
     + ToPrimitive
     + Signed
     + LaTeXable
+    + Exportable
     + GuiDisplayable
     + Clone
     + FromStr
@@ -55,10 +184,14 @@ pub trait MatrixNumber:
 impl<T> MatrixNumber for T where
     T: Num
         + CheckedOps
+        + CheckedOpsError
+        + CheckedSqrt
+        + FieldConversion
         + FromPrimitive
         + ToPrimitive
         + Signed
         + LaTeXable
+        + Exportable
         + GuiDisplayable
         + Clone
         + FromStr
@@ -79,6 +212,27 @@ macro_rules! to_string_to_latex {
     }
 }
 
+#[macro_export]
+macro_rules! exportable_for_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl Exportable for $t {
+                fn to_typst(&self) -> String {
+                    self.to_string()
+                }
+
+                fn to_mathml(&self) -> String {
+                    number_to_mathml(&self.to_string())
+                }
+
+                fn to_plain(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    }
+}
+
 #[macro_export]
 macro_rules! gui_displayable_for_primitive {
     ($($t:ty),*) => {
@@ -100,12 +254,51 @@ macro_rules! gui_displayable_for_primitive {
     }
 }
 
+/// Implements [`CheckedOpsError`], [`FieldConversion`] and [`CheckedSqrt`],
+/// the remaining traits required by [`MatrixNumber`].
+#[macro_export]
+macro_rules! number_traits_for_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl CheckedOpsError for $t {}
+
+            impl FieldConversion for $t {
+                fn to_rational(&self) -> Option<num_rational::Rational64> {
+                    Some(num_rational::Rational64::from_integer(i64::try_from(*self).ok()?))
+                }
+
+                fn from_rational(value: num_rational::Rational64) -> Option<Self> {
+                    match value.is_integer() {
+                        true => <$t>::try_from(value.to_integer()).ok(),
+                        false => None,
+                    }
+                }
+            }
+
+            impl CheckedSqrt for $t {
+                fn checked_sqrt(&self) -> Option<Self> {
+                    let square = u128::try_from(*self).ok()?;
+                    let root = $crate::rationals::perfect_square_root(square)?;
+                    <$t>::try_from(root).ok()
+                }
+            }
+        )*
+    }
+}
+
 // We add LaTeX support for all the basic types
 to_string_to_latex!(i8, i16, i32, i64, i128, isize);
 
+// We add export support for all the basic types
+exportable_for_primitive!(i8, i16, i32, i64, i128, isize);
+
 // We add display support for all the basic types
 gui_displayable_for_primitive!(i8, i16, i32, i64, i128, isize);
 
+// Checked operations, conversions and square roots of all the basic types fail on overflow
+// and they are always exact
+number_traits_for_primitive!(i8, i16, i32, i64, i128, isize);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,4 +320,15 @@ mod tests {
 
         test_matrix_number!(i8, i16, i32, i64, i128, isize);
     }
+
+    #[test]
+    fn test_primitive_export() {
+        assert_eq!((-7).export(ExportFormat::Typst), "-7");
+        assert_eq!((-7).export(ExportFormat::Csv), "-7");
+        assert_eq!(
+            12.export(ExportFormat::MathML),
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block"><mn>12</mn></math>"#
+        );
+        assert_eq!((-3).to_mathml(), "<mo>-</mo><mn>3</mn>");
+    }
 }